@@ -0,0 +1,118 @@
+//! A rough benchmark of the derived implementation on a struct with many arms, which converts the
+//! key once and matches on it, against a hand written implementation that converts the key for
+//! every comparison, as a chain of `key.as_ref() == "..."` checks would.
+//!
+//! The derive has always converted the key once, matching on `key.as_ref()` before it bound the
+//! key to `k`, so this measures what converting once saves rather than a change to the derive.
+//!
+//! Run with `cargo bench --bench many_arms`.
+
+use guzzle::Guzzle;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUNDS: usize = 2_000_000;
+
+/// A key type whose conversion isn't free
+struct SlowKey(String);
+
+impl AsRef<str> for SlowKey {
+    fn as_ref(&self) -> &str {
+        self.0.trim_start_matches(' ')
+    }
+}
+
+#[derive(Default, Guzzle)]
+struct Derived {
+    #[guzzle(keys = ["a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7"])]
+    a: String,
+    #[guzzle(keys = ["b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7"])]
+    b: String,
+    #[guzzle(keys = ["c0", "c1", "c2", "c3", "c4", "c5", "c6", "c7"])]
+    c: String,
+}
+
+/// The same struct, guzzled by converting the key for every comparison
+#[derive(Default)]
+struct PerArm {
+    a: String,
+    b: String,
+    c: String,
+}
+
+impl Guzzle for PerArm {
+    fn guzzle<T>(&mut self, (key, value): (T, String)) -> Option<(T, String)>
+    where
+        T: AsRef<str>,
+    {
+        if key.as_ref() == "a0"
+            || key.as_ref() == "a1"
+            || key.as_ref() == "a2"
+            || key.as_ref() == "a3"
+            || key.as_ref() == "a4"
+            || key.as_ref() == "a5"
+            || key.as_ref() == "a6"
+            || key.as_ref() == "a7"
+        {
+            self.a = value;
+        } else if key.as_ref() == "b0"
+            || key.as_ref() == "b1"
+            || key.as_ref() == "b2"
+            || key.as_ref() == "b3"
+            || key.as_ref() == "b4"
+            || key.as_ref() == "b5"
+            || key.as_ref() == "b6"
+            || key.as_ref() == "b7"
+        {
+            self.b = value;
+        } else if key.as_ref() == "c0"
+            || key.as_ref() == "c1"
+            || key.as_ref() == "c2"
+            || key.as_ref() == "c3"
+            || key.as_ref() == "c4"
+            || key.as_ref() == "c5"
+            || key.as_ref() == "c6"
+            || key.as_ref() == "c7"
+        {
+            self.c = value;
+        } else {
+            return Some((key, value));
+        }
+        None
+    }
+}
+
+/// Every key the structs consume, along with as many they don't, each behind some spaces for
+/// `SlowKey` to trim
+fn keys() -> Vec<String> {
+    ["a", "b", "c", "d"]
+        .iter()
+        .flat_map(|prefix| (0..8).map(move |index| format!("  {}{}", prefix, index)))
+        .collect()
+}
+
+fn run<G: Guzzle>(guzzler: &mut G, keys: &[String]) -> Duration {
+    let start = Instant::now();
+    let mut leftovers = 0;
+    for round in 0..ROUNDS {
+        let key = SlowKey(keys[round % keys.len()].clone());
+        if black_box(&mut *guzzler)
+            .guzzle((key, String::new()))
+            .is_some()
+        {
+            leftovers += 1;
+        }
+    }
+    assert_eq!(leftovers, ROUNDS / 4);
+    start.elapsed()
+}
+
+fn main() {
+    let keys = keys();
+    // Warm up before timing either
+    run(&mut Derived::default(), &keys);
+    let per_arm = run(&mut PerArm::default(), &keys);
+    let derived = run(&mut Derived::default(), &keys);
+    println!("per arm (key.as_ref() each time): {:?}", per_arm);
+    println!("derived (converted once):         {:?}", derived);
+}
@@ -1,10 +1,13 @@
 extern crate regex;
 
-use std::fs::write;
-use std::fs::File;
 use regex::Regex;
 use std::error::Error;
-use std::io::{BufReader, BufRead};
+use std::fs::write;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+#[path = "build/readme.rs"]
+mod readme;
 
 fn create_readme() -> Result<(), Box<dyn Error>> {
     let comment_capture = Regex::new(r"^//!\s?(?P<comment>.*)")?;
@@ -22,7 +25,7 @@ fn create_readme() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    write("README.md", readme_lines.join("\n")).expect("Could not write to README.md");
+    write("README.md", readme::render(readme_lines)).expect("Could not write to README.md");
     Ok(())
 }
 
@@ -0,0 +1,58 @@
+/// Turns the contents of the `//!` comments at the top of lib.rs into Markdown for the README.
+///
+/// Code fences are copied verbatim, except that rustdoc's hidden lines (those beginning `# `) in
+/// Rust examples are shown, since the README has no way to hide them and the examples should be
+/// complete.
+pub fn render<I>(comment_lines: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut fence: Option<bool> = None;
+    let mut readme_lines = vec![];
+    for line in comment_lines {
+        let line = line.as_ref();
+        let trimmed = line.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            // Opening a fence records whether it's Rust, closing it goes back to plain Markdown
+            fence = match fence {
+                Some(_) => None,
+                None => Some(is_rust(info)),
+            };
+            readme_lines.push(line.to_string());
+        } else if fence == Some(true) {
+            readme_lines.push(unhide(line));
+        } else {
+            readme_lines.push(line.to_string());
+        }
+    }
+    readme_lines.join("\n")
+}
+
+/// Rustdoc treats a code block as Rust unless it's told it's something else
+fn is_rust(info: &str) -> bool {
+    info.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|attribute| !attribute.is_empty())
+        .all(|attribute| {
+            matches!(
+                attribute,
+                "rust" | "ignore" | "no_run" | "should_panic" | "compile_fail" | "edition2018"
+            )
+        })
+}
+
+/// Strips the marker from a hidden doctest line, leaving the line's indentation alone
+fn unhide(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    if trimmed == "#" {
+        String::new()
+    } else if let Some(rest) = trimmed.strip_prefix("# ") {
+        format!("{}{}", indent, rest)
+    } else if let Some(rest) = trimmed.strip_prefix("##") {
+        // `##` is how rustdoc escapes a line that really does start with `#`
+        format!("{}#{}", indent, rest)
+    } else {
+        line.to_string()
+    }
+}
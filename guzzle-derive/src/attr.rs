@@ -1,13 +1,14 @@
 use quote::quote;
+use std::convert::TryFrom;
 use std::default::Default;
 use std::ops::Deref;
 use syn::{
     bracketed, parenthesized,
     parse::{Parse, ParseBuffer},
     punctuated::Punctuated,
-    Attribute, Expr, Field, Ident, LitStr, Token
+    Attribute, Expr, Field, GenericArgument, Ident, Lit, LitInt, LitStr, Meta, MetaNameValue,
+    PathArguments, Token, Type, TypePath,
 };
-use std::convert::TryFrom;
 
 type SynResult<T> = Result<T, syn::Error>;
 
@@ -51,23 +52,135 @@ type SynResult<T> = Result<T, syn::Error>;
 pub struct FieldAttribute<'a> {
     field: &'a Ident,
     attribute: GuzzleAttribute,
+    doc: String,
 }
 
 impl<'a> FieldAttribute<'a> {
-    pub fn get_arm_parts(&self) -> Vec<(&Ident, &LitStr, &Option<Expr>)> {
-        self.attribute.keyed_attribute()
-            .map(|keyed_attr| {
-                keyed_attr.keys
-                        .iter()
-                        .map(|matcher| (self.field, matcher, &keyed_attr.parser))
-                        .collect()
+    /// The name of the field
+    pub fn get_field(&self) -> &Ident {
+        self.field
+    }
+
+    pub fn get_arm_parts(&self) -> Vec<(&Ident, &LitStr, &GuzzleKeyedAttribute)> {
+        self.attribute
+            .keyed_attributes()
+            .iter()
+            .flat_map(|keyed_attr| {
+                keyed_attr
+                    .keys
+                    .iter()
+                    .map(move |matcher| (self.field, matcher, keyed_attr))
+            })
+            .collect()
+    }
+
+    /// The field name along with each of its keyed attributes that match keys by their prefix
+    pub fn get_prefixed(&self) -> Vec<(&Ident, &LitStr, &GuzzleKeyedAttribute)> {
+        self.attribute
+            .keyed_attributes()
+            .iter()
+            .filter_map(|keyed_attr| {
+                let key_prefix = keyed_attr.key_prefix.as_ref()?;
+                Some((self.field, key_prefix, keyed_attr))
+            })
+            .collect()
+    }
+
+    /// The field name along with each of its keyed attributes that match keys with a regex
+    pub fn get_regexes(&self) -> Vec<(&Ident, &LitStr, &GuzzleKeyedAttribute)> {
+        self.attribute
+            .keyed_attributes()
+            .iter()
+            .filter_map(|keyed_attr| {
+                let key_regex = keyed_attr.key_regex.as_ref()?;
+                Some((self.field, key_regex, keyed_attr))
             })
-            .unwrap_or_default()
+            .collect()
     }
 
-    pub fn get_recursion(&self) -> Option<&Ident> {
+    pub fn get_recursion(&self) -> Option<&DeepGuzzle> {
         self.attribute.recurse_attribute()
     }
+
+    /// The field name along with each of its keyed attributes, if the field is consumed from its
+    /// keys
+    pub fn get_keyed(&self) -> Vec<(&Ident, &GuzzleKeyedAttribute)> {
+        self.attribute
+            .keyed_attributes()
+            .iter()
+            .map(|keyed_attr| (self.field, keyed_attr))
+            .collect()
+    }
+
+    /// The field name, if the field holds the `GuzzleState`
+    pub fn get_state(&self) -> Option<&Ident> {
+        match self.attribute {
+            GuzzleAttribute::State => Some(self.field),
+            _ => None,
+        }
+    }
+
+    /// The field name, if the field catches everything nothing else consumed
+    pub fn get_rest(&self) -> Option<&Ident> {
+        match self.attribute {
+            GuzzleAttribute::Rest => Some(self.field),
+            _ => None,
+        }
+    }
+
+    /// The field name, if the field has been marked with `no_guzzle`
+    pub fn get_ignored(&self) -> Option<&Ident> {
+        match self.attribute {
+            GuzzleAttribute::NoGuzzle => Some(self.field),
+            _ => None,
+        }
+    }
+
+    /// Renames keys that were taken from the field's name, leaving keys that were given alone
+    pub fn rename_field_keys(&mut self, rename: impl Fn(&str) -> String) {
+        if let GuzzleAttribute::KeyedAttribute(keyed_attrs) = &mut self.attribute {
+            keyed_attrs
+                .iter_mut()
+                .filter(|keyed_attr| keyed_attr.named_after_field)
+                .for_each(|keyed_attr| {
+                    keyed_attr.keys = Keys(
+                        keyed_attr
+                            .keys
+                            .iter()
+                            .map(|key| LitStr::new(&rename(&key.value()), key.span()))
+                            .collect(),
+                    )
+                });
+        }
+    }
+
+    /// Prepends the container's prefix to every key the field is guzzled from. Deep guzzle fields
+    /// are only offered keys behind the prefix, as if they had `#[deep_guzzle(prefix = ...)]`.
+    pub fn add_prefix(&mut self, prefix: &LitStr) {
+        match &mut self.attribute {
+            GuzzleAttribute::KeyedAttribute(keyed_attrs) => keyed_attrs
+                .iter_mut()
+                .for_each(|keyed_attr| keyed_attr.add_prefix(prefix)),
+            GuzzleAttribute::RecurseAttribute(deep_guzzle) => {
+                let own_prefix = deep_guzzle
+                    .prefix
+                    .as_ref()
+                    .map(LitStr::value)
+                    .unwrap_or_default();
+                deep_guzzle.prefix = Some(LitStr::new(
+                    &format!("{}{}", prefix.value(), own_prefix),
+                    prefix.span(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// The doc comment on the field, with each line trimmed and joined with new lines. Fields
+    /// without doc comments have an empty string.
+    pub fn get_doc(&self) -> &str {
+        &self.doc
+    }
 }
 
 impl<'a> TryFrom<&'a Field> for FieldAttribute<'a> {
@@ -77,37 +190,275 @@ impl<'a> TryFrom<&'a Field> for FieldAttribute<'a> {
         // Default value for keys is just the name of the field
         let name_ident = field.ident.clone().unwrap();
 
+        let mut attributes = vec![];
+        for attr in &field.attrs {
+            if let Some(new_attribute) = raw_attr_to_guzzle_attr(&name_ident, attr)? {
+                attributes.push((attr, new_attribute));
+            }
+        }
+
+        // Recursing and matching keys directly contradict each other, so rather than silently
+        // picking one we make the user choose
+        let recurses = attributes
+            .iter()
+            .any(|(_, attribute)| matches!(attribute, GuzzleAttribute::RecurseAttribute(_)));
+        let keyed = attributes
+            .iter()
+            .find(|(_, attribute)| matches!(attribute, GuzzleAttribute::KeyedAttribute(_)));
+        if let (true, Some((attr, _))) = (recurses, keyed) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "A field can't be both #[deep_guzzle] and #[guzzle(...)], either recurse into the \
+                 field or match keys for it directly",
+            ));
+        }
+        // Only keyed attributes can be combined, anything else says what the whole field is for
+        let special = attributes
+            .iter()
+            .find(|(_, attribute)| !matches!(attribute, GuzzleAttribute::KeyedAttribute(_)));
+        if let Some((special_attr, special)) = special {
+            let other = attributes
+                .iter()
+                .find(|(attr, _)| !std::ptr::eq(*attr, *special_attr));
+            if let Some((attr, _)) = other {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    format!(
+                        "{} can't be combined with any other guzzle attribute on the same field",
+                        special.describe()
+                    ),
+                ));
+            }
+        }
+
         // Unless otherwise turned off we'll default to a keyed attribute with the same name as the
-        // field (see below)
-        let mut attribute = GuzzleAttribute::from_ident(&name_ident);
+        // field (see below). If there are several keyed attributes, eg for different slots, they
+        // all apply.
+        let mut attributes = attributes.into_iter().map(|(_, attribute)| attribute);
+        let mut attribute = match attributes.next() {
+            Some(GuzzleAttribute::KeyedAttribute(mut keyed_attrs)) => {
+                for attribute in attributes {
+                    if let GuzzleAttribute::KeyedAttribute(more_keyed_attrs) = attribute {
+                        keyed_attrs.extend(more_keyed_attrs);
+                    }
+                }
+                GuzzleAttribute::KeyedAttribute(keyed_attrs)
+            }
+            Some(attribute) => attribute,
+            None => GuzzleAttribute::from_ident(&name_ident),
+        };
 
-        for attr in &field.attrs {
-            if let Some(new_attribute) = raw_attr_to_guzzle_attr(&name_ident, &attr)? {
-                attribute = new_attribute;
-                break;
+        // Sets can't be pushed onto, so collecting into one inserts instead, optional fields are
+        // wrapped in `Some`, and vecs collect every value
+        if let GuzzleAttribute::KeyedAttribute(keyed_attrs) = &mut attribute {
+            let set = is_type(&field.ty, &["HashSet", "BTreeSet"]);
+            let option = is_type(&field.ty, &["Option"]);
+            let vec = is_type(&field.ty, &["Vec"]);
+            let map = is_type(&field.ty, &["HashMap", "BTreeMap"]);
+            let from_str = !holds_strings(&field.ty);
+            let string = is_type(&field.ty, &["String"]);
+            let value_ty = parsed_type(&field.ty);
+            keyed_attrs.iter_mut().for_each(|keyed_attr| {
+                keyed_attr.value_ty = Some(value_ty.clone());
+                keyed_attr.string = string;
+                keyed_attr.set |= set;
+                keyed_attr.option = option;
+                keyed_attr.vec = vec;
+                keyed_attr.map = map;
+                keyed_attr.from_str = from_str;
+            });
+        }
+        // An optional deep guzzle field is only filled in once it consumes something
+        if let GuzzleAttribute::RecurseAttribute(deep_guzzle) = &mut attribute {
+            deep_guzzle.option = is_type(&field.ty, &["Option"]);
+            // While a `Vec` is a repeater, with each row's keys starting with the prefix and its
+            // index, eg `rows_0_title`. The prefix defaults to the field's name.
+            deep_guzzle.vec = is_type(&field.ty, &["Vec"]);
+            if deep_guzzle.vec && deep_guzzle.prefix.is_none() {
+                let prefix = format!("{}_", name_ident);
+                deep_guzzle.prefix = Some(LitStr::new(&prefix, name_ident.span()));
             }
+            deep_guzzle.ty = if deep_guzzle.option || deep_guzzle.vec {
+                first_type_argument(&field.ty).cloned().map(Box::new)
+            } else {
+                Some(Box::new(field.ty.clone()))
+            };
         }
 
+        let doc = doc_from_attrs(&field.attrs);
+
         let field = field.ident.as_ref().unwrap();
-        Ok(FieldAttribute { field, attribute })
+        Ok(FieldAttribute {
+            field,
+            attribute,
+            doc,
+        })
+    }
+}
+
+/// Whether the type looks like one of the named types. Only the last segment of the path is
+/// checked, so aliases won't be recognised.
+pub(crate) fn is_type(ty: &Type, names: &[&str]) -> bool {
+    match ty {
+        Type::Path(TypePath { path, .. }) => path
+            .segments
+            .last()
+            .map(|segment| names.iter().any(|name| segment.ident == name))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Whether the values guzzled into the type are strings, looking inside collections, options and
+/// the arrays used for slots. Anything else is parsed with `FromStr` when it has no parser.
+fn holds_strings(ty: &Type) -> bool {
+    match ty {
+        Type::Array(array) => holds_strings(&array.elem),
+        Type::Path(TypePath { path, .. }) => match path.segments.last() {
+            Some(segment) if segment.ident == "String" => true,
+            Some(_) if is_type(ty, &["Vec", "HashSet", "BTreeSet", "Option"]) => {
+                first_type_argument(ty).map(holds_strings).unwrap_or(false)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The type each value is parsed into, looking inside the same collections, options and arrays as
+/// `holds_strings`
+fn parsed_type(ty: &Type) -> &Type {
+    match ty {
+        Type::Array(array) => parsed_type(&array.elem),
+        _ if is_type(ty, &["Vec", "HashSet", "BTreeSet", "Option"]) => {
+            first_type_argument(ty).map(parsed_type).unwrap_or(ty)
+        }
+        _ => ty,
+    }
+}
+
+/// The first type in the angle brackets of a type such as `Option<T>`
+fn first_type_argument(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(TypePath { path, .. }) => match &path.segments.last()?.arguments {
+            PathArguments::AngleBracketed(arguments) => match arguments.args.first() {
+                Some(GenericArgument::Type(inner)) => Some(inner),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
     }
 }
 
-fn raw_attr_to_guzzle_attr(ident: &Ident, attribute: &Attribute) -> SynResult<Option<GuzzleAttribute>> {
+/// Doc comments arrive as `#[doc = "..."]` attributes, one per line
+fn doc_from_attrs(attributes: &[Attribute]) -> String {
+    attributes
+        .iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(doc),
+                ..
+            })) if path.is_ident("doc") => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn raw_attr_to_guzzle_attr(
+    ident: &Ident,
+    attribute: &Attribute,
+) -> SynResult<Option<GuzzleAttribute>> {
     let path = &attribute.path;
     let attr = match quote!(#path).to_string().as_ref() {
         "guzzle" => {
             let tokens = attribute.tokens.clone();
             let mut keyed_attr: GuzzleKeyedAttribute = syn::parse2(tokens)?;
 
-            // If we have a keyed attribute with no keys, we will use the ident
-            if keyed_attr.keys.is_empty() {
+            // None of these fields are matched against keys, so keys for them would be ignored
+            let markers: Vec<_> = [
+                ("state", keyed_attr.state),
+                ("rest", keyed_attr.rest),
+                ("skip", keyed_attr.skip),
+            ]
+            .iter()
+            .filter(|(_, marked)| *marked)
+            .map(|(marker, _)| *marker)
+            .collect();
+            if let [first, second, ..] = markers.as_slice() {
+                return Err(syn::Error::new_spanned(
+                    attribute,
+                    format!(
+                        "A field can't be both #[guzzle({})] and #[guzzle({})]",
+                        first, second
+                    ),
+                ));
+            }
+            if let (Some(marker), Some(key)) = (markers.first(), keyed_attr.keys.first()) {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("A #[guzzle({})] field isn't guzzled from any keys", marker),
+                ));
+            }
+
+            // Skipped fields are left alone, the same as with #[no_guzzle]
+            if keyed_attr.skip {
+                return Ok(Some(GuzzleAttribute::NoGuzzle));
+            }
+
+            // The state field isn't guzzled into, it keeps track of what has been
+            if keyed_attr.state {
+                return Ok(Some(GuzzleAttribute::State));
+            }
+
+            // Nor is the rest field, it gets whatever is left
+            if keyed_attr.rest {
+                return Ok(Some(GuzzleAttribute::Rest));
+            }
+
+            // Keys with their own parsers are matched like any other key
+            let key_parser_keys = keyed_attr.key_parsers.iter().map(|(key, _)| key.clone());
+            keyed_attr.keys.0.extend(key_parser_keys);
+
+            // A renamed field's canonical key comes first, so it's also the one that's output
+            if let Some(rename) = &keyed_attr.rename {
+                keyed_attr.keys.0.insert(0, rename.clone());
+            }
+
+            // The same key twice would only generate an unreachable arm
+            let mut keys_seen = vec![];
+            for key in keyed_attr.keys.iter() {
+                if keys_seen.contains(&key.value()) {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("duplicate key `{}` in keys list", key.value()),
+                    ));
+                }
+                keys_seen.push(key.value());
+            }
+
+            // If we have a keyed attribute with no keys, we will use the ident, unless it's
+            // matching keys by their prefix instead
+            if keyed_attr.keys.is_empty() && !keyed_attr.matches_pattern() {
                 keyed_attr.keys = Keys::from_ident(ident);
+                keyed_attr.named_after_field = true;
             }
 
-            Some(GuzzleAttribute::KeyedAttribute(keyed_attr))
+            Some(GuzzleAttribute::KeyedAttribute(vec![keyed_attr]))
+        }
+        "deep_guzzle" => {
+            let DeepGuzzleOptions { prefix, max_rows } = syn::parse2(attribute.tokens.clone())?;
+            Some(GuzzleAttribute::RecurseAttribute(DeepGuzzle {
+                field: ident.clone(),
+                prefix,
+                max_rows,
+                option: false,
+                vec: false,
+                ty: None,
+            }))
         }
-        "deep_guzzle" => Some(GuzzleAttribute::RecurseAttribute(ident.clone())),
         "no_guzzle" => Some(GuzzleAttribute::NoGuzzle),
         _ => None,
     };
@@ -132,46 +483,230 @@ fn raw_attr_to_guzzle_attr(ident: &Ident, attribute: &Attribute) -> SynResult<Op
 /// }
 /// ```
 pub enum GuzzleAttribute {
-    KeyedAttribute(GuzzleKeyedAttribute),
-    RecurseAttribute(Ident),
+    /// One for each `#[guzzle(...)]` on the field
+    KeyedAttribute(Vec<GuzzleKeyedAttribute>),
+    RecurseAttribute(DeepGuzzle),
     NoGuzzle,
+    State,
+    Rest,
 }
 
 impl GuzzleAttribute {
+    /// How the attribute is written, for errors
+    fn describe(&self) -> &'static str {
+        match self {
+            GuzzleAttribute::KeyedAttribute(_) => "#[guzzle(...)]",
+            GuzzleAttribute::RecurseAttribute(_) => "#[deep_guzzle]",
+            GuzzleAttribute::NoGuzzle => "#[no_guzzle]",
+            GuzzleAttribute::State => "#[guzzle(state)]",
+            GuzzleAttribute::Rest => "#[guzzle(rest)]",
+        }
+    }
+
     fn from_ident(ident: &Ident) -> Self {
-        GuzzleAttribute::KeyedAttribute(
-            GuzzleKeyedAttribute::from_ident(ident)
-        )
+        GuzzleAttribute::KeyedAttribute(vec![GuzzleKeyedAttribute::from_ident(ident)])
     }
 
-    pub fn keyed_attribute(&self) -> Option<&GuzzleKeyedAttribute> {
+    pub fn keyed_attributes(&self) -> &[GuzzleKeyedAttribute] {
         match self {
-            GuzzleAttribute::KeyedAttribute(attribute) => Some(attribute),
-            _ => None,
+            GuzzleAttribute::KeyedAttribute(attributes) => attributes,
+            _ => &[],
         }
     }
 
-    pub fn recurse_attribute(&self) -> Option<&Ident> {
+    pub fn recurse_attribute(&self) -> Option<&DeepGuzzle> {
         match self {
-            GuzzleAttribute::RecurseAttribute(ident) => Some(ident),
+            GuzzleAttribute::RecurseAttribute(deep_guzzle) => Some(deep_guzzle),
             _ => None,
         }
     }
 }
 
+/// A field that is guzzled by its own `Guzzle` implementation
+pub struct DeepGuzzle {
+    pub field: Ident,
+    /// Only keys starting with this are offered to the field, and without the prefix
+    pub prefix: Option<LitStr>,
+    /// For repeaters, the most rows a key can ask for, rather than `DEFAULT_MAX_ROWS`
+    pub max_rows: Option<LitInt>,
+    /// The field is an `Option`, which stays `None` until the inner value consumes something
+    pub option: bool,
+    /// The field is a `Vec` of rows, each guzzled from keys with its index after the prefix
+    pub vec: bool,
+    /// The type that's guzzled into, which for an `Option` or `Vec` is the type inside it
+    pub ty: Option<Box<Type>>,
+}
+
+/// The optional brackets after `#[deep_guzzle]`, eg `#[deep_guzzle(prefix = "loc_")]`
+struct DeepGuzzleOptions {
+    prefix: Option<LitStr>,
+    max_rows: Option<LitInt>,
+}
+
+impl Parse for DeepGuzzleOptions {
+    fn parse(input: &ParseBuffer) -> SynResult<Self> {
+        let mut options = DeepGuzzleOptions {
+            prefix: None,
+            max_rows: None,
+        };
+        if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            while !content.is_empty() {
+                let name: Ident = content.parse()?;
+                content.parse::<Token![=]>()?;
+                if name == "prefix" {
+                    options.prefix = Some(content.parse()?);
+                } else if name == "max_rows" {
+                    let max_rows: LitInt = content.parse()?;
+                    max_rows.base10_parse::<usize>()?;
+                    options.max_rows = Some(max_rows);
+                } else {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        format!(
+                            "Unknown deep_guzzle attribute `{}`, expected `prefix` or `max_rows`",
+                            name
+                        ),
+                    ));
+                }
+                if !content.is_empty() {
+                    content.parse::<Token![,]>()?;
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
 #[derive(Default)]
 pub struct GuzzleKeyedAttribute {
     pub keys: Keys,
+    /// No keys were given, so the only key is the field's name and `rename_all` applies to it
+    pub named_after_field: bool,
+    /// The canonical key for the field, used in place of its name both for matching and output
+    pub rename: Option<LitStr>,
     pub parser: Option<Expr>,
+    /// Like `parser`, but returns an `Option`, handing the pair back rather than consuming it when
+    /// it returns `None`. Also written `parser_opt`.
+    pub opt_parser: Option<Expr>,
+    /// Values the `opt_parser` turns down are consumed and thrown away rather than handed back
+    pub drop_rejected: Option<Ident>,
+    /// Like `parser`, but returns a `Result`. `TryGuzzle` turns errors into a
+    /// `GuzzleError::Parse`, while `Guzzle` hands the pair back.
+    pub try_parser: Option<Expr>,
+    /// Parses the `toml::Value` for `TomlGuzzle` rather than using `FromToml`
+    pub toml_parser: Option<Expr>,
+    /// Turns a reference to each value back into a string for `Unguzzle` rather than using
+    /// `Display`, for values whose parser isn't the reverse of `Display`
+    pub unparser: Option<Expr>,
+    /// Keys that are parsed with their own parser rather than `parser`
+    pub key_parsers: Vec<(LitStr, Expr)>,
+    /// Parses the value given the key it arrived under, for fields fed by several keys
+    pub parser_with_key: Option<Expr>,
+    /// `fn(String) -> String`s applied to the value in order before it's parsed
+    pub transforms: Vec<Expr>,
+    /// A method on the struct that's called with each parsed value rather than setting the field
+    pub setter: Option<Ident>,
+    /// Any key starting with this is consumed, and the parser is given the rest of the key along
+    /// with the value as a `(suffix, value)` tuple
+    pub key_prefix: Option<LitStr>,
+    /// Any key this pattern matches is consumed, and the parser is given the whole key along with
+    /// the value as a `(key, value)` tuple. Needs guzzle's `regex` feature.
+    pub key_regex: Option<LitStr>,
+    /// When set, the pair is only consumed if the value is exactly this
+    pub when_value: Option<LitStr>,
+    /// When set, `finalize` will parse this into the field if none of its keys were seen
+    pub default_if_absent: Option<LitStr>,
+    /// When set, `finalize` will set the field to this expression if none of its keys were seen
+    pub default: Option<Expr>,
+    /// Marks the field that holds the `GuzzleState` rather than a field to be guzzled
+    pub state: bool,
+    /// Marks the field that receives every pair nothing else consumed
+    pub rest: bool,
+    /// The field isn't guzzled at all, like `#[no_guzzle]`
+    pub skip: bool,
+    /// The field is guzzled as usual but left out by `Unguzzle`
+    pub skip_unguzzle: bool,
+    /// The type each value is parsed into, which for collections is the type inside them
+    pub value_ty: Option<Type>,
+    /// Push each value onto a collection rather than overwriting the field
+    pub collect: bool,
+    /// Keep every value, concatenating them onto a `String` or collecting them otherwise
+    pub append: Option<Ident>,
+    /// Goes between the values appended onto a `String`
+    pub append_separator: Option<LitStr>,
+    /// The field is a `String`, so appending concatenates onto it
+    pub string: bool,
+    /// Collect into a set, inserting values rather than pushing them
+    pub set: bool,
+    /// The field is an `Option`, so parsed values are wrapped in `Some`
+    pub option: bool,
+    /// The field is a `Vec`, so values are collected unless they're bytes
+    pub vec: bool,
+    /// The field is a map, so parsed `(key, value)` tuples are inserted into it
+    pub map: bool,
+    /// The field doesn't hold strings, so without a parser its values are parsed with `FromStr`
+    pub from_str: bool,
+    /// Split collected values on this delimiter, pushing each part
+    pub split: Option<LitStr>,
+    /// Put the value into this index of an array of `Option`s rather than the field itself
+    pub slot: Option<LitInt>,
+    /// The field is expected to be guzzled at some point, see `Guzzle::remaining_required`
+    pub required: Option<Ident>,
+    /// Empty values are consumed without touching the field, as if the key had never appeared
+    pub empty_is_missing: Option<Ident>,
+    /// What to do with a value for a field that's already been guzzled: `"first"`, `"last"` or
+    /// `"error"`
+    pub duplicates: Option<LitStr>,
 }
 
 impl GuzzleKeyedAttribute {
     pub fn from_ident(ident: &Ident) -> GuzzleKeyedAttribute {
         GuzzleKeyedAttribute {
             keys: Keys::from_ident(ident),
-            parser: None,
+            named_after_field: true,
+            ..GuzzleKeyedAttribute::default()
+        }
+    }
+
+    /// Whether values are pushed onto the field rather than replacing it. `Vec` fields collect
+    /// repeated keys automatically, except when the values aren't strings, eg in bytes mode where
+    /// a `Vec<u8>` is the value itself, as do maps.
+    pub fn collects(&self, raw_values: bool) -> bool {
+        let appends = self.append.is_some() && !self.string;
+        self.collect || appends || self.map || (self.vec && !raw_values)
+    }
+
+    /// Whether values are concatenated onto a `String` field rather than replacing it
+    pub fn concatenates(&self) -> bool {
+        self.append.is_some() && self.string
+    }
+
+    /// Prepends a prefix to each of the keys, including those with their own parser
+    pub fn add_prefix(&mut self, prefix: &LitStr) {
+        self.keys = self.keys.prefixed(prefix);
+        self.key_parsers
+            .iter_mut()
+            .for_each(|(key, _)| *key = prefix_key(prefix, key));
+        if let Some(key_prefix) = &self.key_prefix {
+            self.key_prefix = Some(prefix_key(prefix, key_prefix));
         }
     }
+
+    /// Whether keys are matched by `key_prefix` or `key_regex` rather than exactly
+    pub fn matches_pattern(&self) -> bool {
+        self.key_prefix.is_some() || self.key_regex.is_some()
+    }
+
+    /// The parser to use for values under this key, preferring one paired with the key itself
+    pub fn parser_for(&self, matcher: &LitStr) -> Option<&Expr> {
+        self.key_parsers
+            .iter()
+            .find(|(key, _)| key.value() == matcher.value())
+            .map(|(_, parser)| parser)
+            .or(self.parser.as_ref())
+    }
 }
 
 impl Parse for GuzzleKeyedAttribute {
@@ -187,7 +722,72 @@ impl Parse for GuzzleKeyedAttribute {
 
             punctuated_attrs.into_iter().for_each(|attr| match attr {
                 RawGuzzleKeyedAttribute::Keys(keys) => guzzle_attributes.keys = keys,
+                RawGuzzleKeyedAttribute::Rename(rename) => guzzle_attributes.rename = Some(rename),
                 RawGuzzleKeyedAttribute::Parser(parser) => guzzle_attributes.parser = Some(parser),
+                RawGuzzleKeyedAttribute::OptParser(parser) => {
+                    guzzle_attributes.opt_parser = Some(parser)
+                }
+                RawGuzzleKeyedAttribute::TryParser(parser) => {
+                    guzzle_attributes.try_parser = Some(parser)
+                }
+                RawGuzzleKeyedAttribute::TomlParser(parser) => {
+                    guzzle_attributes.toml_parser = Some(parser)
+                }
+                RawGuzzleKeyedAttribute::Unparser(unparser) => {
+                    guzzle_attributes.unparser = Some(unparser)
+                }
+                RawGuzzleKeyedAttribute::KeyParsers(KeyParsers(key_parsers)) => {
+                    guzzle_attributes.key_parsers = key_parsers
+                }
+                RawGuzzleKeyedAttribute::Transforms(Transforms(transforms)) => {
+                    guzzle_attributes.transforms = transforms
+                }
+                RawGuzzleKeyedAttribute::Setter(setter) => guzzle_attributes.setter = Some(setter),
+                RawGuzzleKeyedAttribute::ParserWithKey(parser) => {
+                    guzzle_attributes.parser_with_key = Some(parser)
+                }
+                RawGuzzleKeyedAttribute::Duplicates(duplicates) => {
+                    guzzle_attributes.duplicates = Some(duplicates)
+                }
+                RawGuzzleKeyedAttribute::KeyPrefix(prefix) => {
+                    guzzle_attributes.key_prefix = Some(prefix)
+                }
+                RawGuzzleKeyedAttribute::KeyRegex(regex) => {
+                    guzzle_attributes.key_regex = Some(regex)
+                }
+                RawGuzzleKeyedAttribute::WhenValue(value) => {
+                    guzzle_attributes.when_value = Some(value)
+                }
+                RawGuzzleKeyedAttribute::DefaultIfAbsent(value) => {
+                    guzzle_attributes.default_if_absent = Some(value)
+                }
+                RawGuzzleKeyedAttribute::Default(default) => {
+                    guzzle_attributes.default = Some(default)
+                }
+                RawGuzzleKeyedAttribute::State => guzzle_attributes.state = true,
+                RawGuzzleKeyedAttribute::Rest => guzzle_attributes.rest = true,
+                RawGuzzleKeyedAttribute::Skip => guzzle_attributes.skip = true,
+                RawGuzzleKeyedAttribute::SkipUnguzzle => guzzle_attributes.skip_unguzzle = true,
+                RawGuzzleKeyedAttribute::Collect => guzzle_attributes.collect = true,
+                RawGuzzleKeyedAttribute::Append(ident, separator) => {
+                    guzzle_attributes.append = Some(ident);
+                    guzzle_attributes.append_separator = separator;
+                }
+                RawGuzzleKeyedAttribute::Set => {
+                    guzzle_attributes.collect = true;
+                    guzzle_attributes.set = true;
+                }
+                RawGuzzleKeyedAttribute::Split(split) => guzzle_attributes.split = Some(split),
+                RawGuzzleKeyedAttribute::Slot(slot) => guzzle_attributes.slot = Some(slot),
+                RawGuzzleKeyedAttribute::Required(ident) => {
+                    guzzle_attributes.required = Some(ident)
+                }
+                RawGuzzleKeyedAttribute::DropRejected(ident) => {
+                    guzzle_attributes.drop_rejected = Some(ident)
+                }
+                RawGuzzleKeyedAttribute::EmptyIsMissing(ident) => {
+                    guzzle_attributes.empty_is_missing = Some(ident)
+                }
             });
         }
         Ok(guzzle_attributes)
@@ -196,7 +796,111 @@ impl Parse for GuzzleKeyedAttribute {
 
 pub enum RawGuzzleKeyedAttribute {
     Keys(Keys),
+    Rename(LitStr),
     Parser(Expr),
+    OptParser(Expr),
+    TryParser(Expr),
+    TomlParser(Expr),
+    Unparser(Expr),
+    KeyParsers(KeyParsers),
+    Transforms(Transforms),
+    Setter(Ident),
+    ParserWithKey(Expr),
+    Duplicates(LitStr),
+    KeyPrefix(LitStr),
+    KeyRegex(LitStr),
+    WhenValue(LitStr),
+    DefaultIfAbsent(LitStr),
+    Default(Expr),
+    State,
+    Rest,
+    Skip,
+    SkipUnguzzle,
+    Collect,
+    Set,
+    Split(LitStr),
+    Slot(LitInt),
+    Required(Ident),
+    EmptyIsMissing(Ident),
+    DropRejected(Ident),
+    Append(Ident, Option<LitStr>),
+}
+
+/// The field attributes that stand on their own, and those that take a value
+const KEYED_FLAGS: &[&str] = &[
+    "state",
+    "rest",
+    "skip",
+    "skip_unguzzle",
+    "collect",
+    "set",
+    "required",
+    "empty_is_missing",
+    "drop_rejected",
+    "append",
+];
+const KEYED_VALUES: &[&str] = &[
+    "keys",
+    "rename",
+    "parser",
+    "opt_parser",
+    "parser_opt",
+    "try_parser",
+    "toml_parser",
+    "unparser",
+    "key_parsers",
+    "map",
+    "setter",
+    "parser_with_key",
+    "duplicates",
+    "append",
+    "key_prefix",
+    "key_regex",
+    "when_value",
+    "default_if_absent",
+    "default",
+    "split",
+    "slot",
+];
+
+/// Explains why an attribute wasn't recognised: it's a flag that was given a value, it needed a
+/// value and didn't get one, or it doesn't exist at all
+pub(crate) fn unknown_attribute(name: &Ident, flags: &[&str], values: &[&str]) -> syn::Error {
+    let name_str = name.to_string();
+    let message = if flags.contains(&name_str.as_str()) {
+        format!("`{}` doesn't take a value, write it on its own", name_str)
+    } else if values.contains(&name_str.as_str()) {
+        format!("`{}` needs a value, eg `{} = ...`", name_str, name_str)
+    } else {
+        let mut known: Vec<_> = flags.iter().chain(values).collect();
+        known.sort();
+        known.dedup();
+        let known: Vec<_> = known
+            .into_iter()
+            .map(|known| format!("`{}`", known))
+            .collect();
+        format!(
+            "Unknown guzzle attribute `{}`, expected one of {}",
+            name_str,
+            known.join(", ")
+        )
+    };
+    syn::Error::new(name.span(), message)
+}
+
+/// Parses a parser or other function given to an attribute, explaining what was expected if it
+/// isn't an expression
+fn parse_function(name: &Ident, input: &ParseBuffer) -> SynResult<Expr> {
+    let span = input.span();
+    input.parse().map_err(|error: syn::Error| {
+        syn::Error::new(
+            span,
+            format!(
+                "`{}` must be a function or closure, eg `{} = my_parser`: {}",
+                name, name, error
+            ),
+        )
+    })
 }
 
 impl Parse for RawGuzzleKeyedAttribute {
@@ -210,11 +914,56 @@ impl Parse for RawGuzzleKeyedAttribute {
 
             match name_str.as_ref() {
                 "keys" => Ok(RawGuzzleKeyedAttribute::Keys(input.parse()?)),
-                "parser" => Ok(RawGuzzleKeyedAttribute::Parser(input.parse()?)),
-                _ => Err(input.error(format!("Unknown key: {}", name_str))),
+                "rename" => Ok(RawGuzzleKeyedAttribute::Rename(input.parse()?)),
+                "parser" => Ok(RawGuzzleKeyedAttribute::Parser(parse_function(
+                    &name, input,
+                )?)),
+                "opt_parser" | "parser_opt" => Ok(RawGuzzleKeyedAttribute::OptParser(
+                    parse_function(&name, input)?,
+                )),
+                "try_parser" => Ok(RawGuzzleKeyedAttribute::TryParser(parse_function(
+                    &name, input,
+                )?)),
+                "toml_parser" => Ok(RawGuzzleKeyedAttribute::TomlParser(parse_function(
+                    &name, input,
+                )?)),
+                "unparser" => Ok(RawGuzzleKeyedAttribute::Unparser(parse_function(
+                    &name, input,
+                )?)),
+                "key_parsers" => Ok(RawGuzzleKeyedAttribute::KeyParsers(input.parse()?)),
+                "map" => Ok(RawGuzzleKeyedAttribute::Transforms(input.parse()?)),
+                "setter" => Ok(RawGuzzleKeyedAttribute::Setter(input.parse()?)),
+                "parser_with_key" => Ok(RawGuzzleKeyedAttribute::ParserWithKey(parse_function(
+                    &name, input,
+                )?)),
+                "duplicates" => Ok(RawGuzzleKeyedAttribute::Duplicates(parse_duplicates(
+                    input,
+                )?)),
+                "append" => Ok(RawGuzzleKeyedAttribute::Append(name, Some(input.parse()?))),
+                "key_prefix" => Ok(RawGuzzleKeyedAttribute::KeyPrefix(input.parse()?)),
+                "key_regex" => Ok(RawGuzzleKeyedAttribute::KeyRegex(input.parse()?)),
+                "when_value" => Ok(RawGuzzleKeyedAttribute::WhenValue(input.parse()?)),
+                "default_if_absent" => Ok(RawGuzzleKeyedAttribute::DefaultIfAbsent(input.parse()?)),
+                "default" => Ok(RawGuzzleKeyedAttribute::Default(input.parse()?)),
+                "split" => Ok(RawGuzzleKeyedAttribute::Split(input.parse()?)),
+                "slot" => Ok(RawGuzzleKeyedAttribute::Slot(input.parse()?)),
+                _ => Err(unknown_attribute(&name, KEYED_FLAGS, KEYED_VALUES)),
             }
         } else {
-            Err(input.error("Attributes must be listed as `key = value`"))
+            // Flags on their own
+            match name_str.as_ref() {
+                "state" => Ok(RawGuzzleKeyedAttribute::State),
+                "rest" => Ok(RawGuzzleKeyedAttribute::Rest),
+                "skip" => Ok(RawGuzzleKeyedAttribute::Skip),
+                "skip_unguzzle" => Ok(RawGuzzleKeyedAttribute::SkipUnguzzle),
+                "collect" => Ok(RawGuzzleKeyedAttribute::Collect),
+                "set" => Ok(RawGuzzleKeyedAttribute::Set),
+                "required" => Ok(RawGuzzleKeyedAttribute::Required(name)),
+                "empty_is_missing" => Ok(RawGuzzleKeyedAttribute::EmptyIsMissing(name)),
+                "drop_rejected" => Ok(RawGuzzleKeyedAttribute::DropRejected(name)),
+                "append" => Ok(RawGuzzleKeyedAttribute::Append(name, None)),
+                _ => Err(unknown_attribute(&name, KEYED_FLAGS, KEYED_VALUES)),
+            }
         }
     }
 }
@@ -228,6 +977,16 @@ impl Keys {
     pub fn from_ident(ident: &Ident) -> Keys {
         Keys(vec![LitStr::new(ident.to_string().as_str(), ident.span())])
     }
+
+    /// The same keys, each with the prefix prepended
+    pub fn prefixed(&self, prefix: &LitStr) -> Keys {
+        Keys(self.0.iter().map(|key| prefix_key(prefix, key)).collect())
+    }
+}
+
+/// Prepends the prefix to the key, keeping the key's span so errors still point at it
+fn prefix_key(prefix: &LitStr, key: &LitStr) -> LitStr {
+    LitStr::new(&format!("{}{}", prefix.value(), key.value()), key.span())
 }
 
 impl Parse for Keys {
@@ -241,7 +1000,12 @@ impl Parse for Keys {
         let content;
         bracketed!(content in input);
         let parser = Punctuated::<LitStr, Token![,]>::parse_separated_nonempty;
-        let parsed_keys = parser(&content)?;
+        let parsed_keys = parser(&content).map_err(|error| {
+            syn::Error::new(
+                error.span(),
+                "keys must be a list of string literals, eg `keys = [\"title\", \"post_title\"]`",
+            )
+        })?;
         Ok(Keys(parsed_keys.into_iter().collect()))
     }
 }
@@ -254,10 +1018,71 @@ impl Deref for Keys {
     }
 }
 
+/// Pairs of keys and the parser to use for each
+/// ```text
+/// [guzzle(key_parsers = [("meters", m_parser), ("feet", f_parser)])]
+///                       ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+///                                      this part
+/// ```
+pub struct KeyParsers(Vec<(LitStr, Expr)>);
+
+impl Parse for KeyParsers {
+    fn parse(input: &ParseBuffer) -> Result<Self, syn::Error> {
+        let content;
+        bracketed!(content in input);
+        let parser = Punctuated::<KeyParser, Token![,]>::parse_separated_nonempty;
+        let parsed = parser(&content)?;
+        Ok(KeyParsers(
+            parsed
+                .into_iter()
+                .map(|KeyParser(key, parser)| (key, parser))
+                .collect(),
+        ))
+    }
+}
+
+/// Parses a duplicate policy, which has to be one `Guzzle` knows how to follow
+pub(crate) fn parse_duplicates(input: &ParseBuffer) -> Result<LitStr, syn::Error> {
+    let duplicates: LitStr = input.parse()?;
+    match duplicates.value().as_ref() {
+        "first" | "last" | "error" => Ok(duplicates),
+        _ => Err(syn::Error::new(
+            duplicates.span(),
+            "duplicates must be \"first\", \"last\" or \"error\"",
+        )),
+    }
+}
+
+/// The bracketed list of transforms given to `map`, eg `[trim, to_lowercase]`
+pub struct Transforms(Vec<Expr>);
+
+impl Parse for Transforms {
+    fn parse(input: &ParseBuffer) -> Result<Self, syn::Error> {
+        let content;
+        bracketed!(content in input);
+        let transforms = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+        Ok(Transforms(transforms.into_iter().collect()))
+    }
+}
+
+/// A single `("key", parser)` pair
+struct KeyParser(LitStr, Expr);
+
+impl Parse for KeyParser {
+    fn parse(input: &ParseBuffer) -> Result<Self, syn::Error> {
+        let content;
+        parenthesized!(content in input);
+        let key = content.parse()?;
+        content.parse::<Token![,]>()?;
+        let parser = content.parse()?;
+        Ok(KeyParser(key, parser))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::attr::{RawGuzzleKeyedAttribute, GuzzleKeyedAttribute};
+    use crate::attr::{GuzzleKeyedAttribute, RawGuzzleKeyedAttribute};
     use quote::quote;
     use syn::{parse::Parser, parse2, punctuated::Punctuated, LitStr, Token};
 
@@ -315,6 +1140,391 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn doc_comments() -> Result<(), syn::Error> {
+        let field: syn::FieldsNamed = parse2(quote! {
+            {
+                /// The first line
+                ///    and the second
+                documented: String,
+                undocumented: String,
+            }
+        })?;
+        let mut fields = field.named.iter();
+        let documented = FieldAttribute::try_from(fields.next().unwrap())?;
+        let undocumented = FieldAttribute::try_from(fields.next().unwrap())?;
+        assert_eq!(documented.get_doc(), "The first line\nand the second");
+        assert_eq!(undocumented.get_doc(), "");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_parser_opt() -> Result<(), syn::Error> {
+        let token_stream = quote! { ( parser_opt = parse_colour, drop_rejected ) };
+        let attributes: GuzzleKeyedAttribute = parse2(token_stream)?;
+        assert!(attributes.opt_parser.is_some());
+        assert!(attributes.drop_rejected.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_empty_is_missing() -> Result<(), syn::Error> {
+        let attributes: GuzzleKeyedAttribute = parse2(quote! { ( empty_is_missing ) })?;
+        assert!(attributes.empty_is_missing.is_some());
+        let attributes: GuzzleKeyedAttribute = parse2(quote! { ( keys = ["type"] ) })?;
+        assert!(attributes.empty_is_missing.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_map() -> Result<(), syn::Error> {
+        let token_stream = quote! { ( map = [trim, crate::strip_tags], parser = u64_parser ) };
+        let attributes: GuzzleKeyedAttribute = parse2(token_stream)?;
+        let transforms = attributes
+            .transforms
+            .iter()
+            .map(|transform| quote! { #transform });
+        let transforms: Vec<_> = transforms.map(|transform| transform.to_string()).collect();
+        assert_eq!(transforms, vec!["trim", "crate :: strip_tags"]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_setter() -> Result<(), syn::Error> {
+        let attributes: GuzzleKeyedAttribute = parse2(quote! { ( setter = set_lat ) })?;
+        assert_eq!(attributes.setter.unwrap().to_string(), "set_lat");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_parser_with_key() -> Result<(), syn::Error> {
+        let token_stream = quote! { ( keys = ["width_cm", "width_in"], parser_with_key = size ) };
+        let attributes: GuzzleKeyedAttribute = parse2(token_stream)?;
+        assert!(attributes.parser.is_none());
+        assert!(attributes.parser_with_key.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_append() -> Result<(), syn::Error> {
+        let attributes: GuzzleKeyedAttribute = parse2(quote! { ( append ) })?;
+        assert!(attributes.append.is_some());
+        assert!(attributes.append_separator.is_none());
+        let attributes: GuzzleKeyedAttribute = parse2(quote! { ( append = ", " ) })?;
+        assert!(attributes.append.is_some());
+        assert_eq!(attributes.append_separator.unwrap().value(), ", ");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_when_value() -> Result<(), syn::Error> {
+        let token_stream = quote! { ( keys = ["type"], when_value = "post" ) };
+        let attributes: GuzzleKeyedAttribute = parse2(token_stream)?;
+        assert_eq!("post", attributes.when_value.unwrap().value());
+        Ok(())
+    }
+
+    #[test]
+    fn rename_comes_first() -> Result<(), syn::Error> {
+        let field: syn::FieldsNamed = parse2(quote! {
+            {
+                #[guzzle(keys = ["title"], rename = "post_title")]
+                title: String,
+            }
+        })?;
+        let field_attribute = FieldAttribute::try_from(field.named.first().unwrap())?;
+        let keys: Vec<_> = field_attribute
+            .get_arm_parts()
+            .into_iter()
+            .map(|(_, matcher, _)| matcher.value())
+            .collect();
+        assert_eq!(keys, vec!["post_title".to_string(), "title".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn contradictory_attributes() -> Result<(), syn::Error> {
+        let fields: syn::FieldsNamed = parse2(quote! {
+            {
+                #[no_guzzle]
+                #[guzzle(keys = ["title"])]
+                title: String,
+                #[guzzle(state)]
+                #[deep_guzzle]
+                state: GuzzleState,
+                #[guzzle(rest, keys = ["extra"])]
+                rest: Vec<(String, String)>,
+                #[guzzle(state, rest)]
+                both: GuzzleState,
+                #[guzzle(keys = ["a"])]
+                #[guzzle(keys = ["b"], slot = 1)]
+                slots: [Option<String>; 2],
+            }
+        })?;
+        let errors: Vec<_> = fields
+            .named
+            .iter()
+            .map(|field| {
+                FieldAttribute::try_from(field)
+                    .err()
+                    .map(|error| error.to_string())
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                Some(
+                    "#[no_guzzle] can't be combined with any other guzzle attribute on the same \
+                     field"
+                        .to_string()
+                ),
+                Some(
+                    "#[guzzle(state)] can't be combined with any other guzzle attribute on the \
+                     same field"
+                        .to_string()
+                ),
+                Some("A #[guzzle(rest)] field isn't guzzled from any keys".to_string()),
+                Some("A field can't be both #[guzzle(state)] and #[guzzle(rest)]".to_string()),
+                None,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_attributes_are_explained() {
+        let error = |token_stream| parse2::<GuzzleKeyedAttribute>(token_stream).err().unwrap();
+        assert_eq!(
+            error(quote! { ( state = true ) }).to_string(),
+            "`state` doesn't take a value, write it on its own"
+        );
+        assert_eq!(
+            error(quote! { ( parser ) }).to_string(),
+            "`parser` needs a value, eg `parser = ...`"
+        );
+        assert!(error(quote! { ( kyes = ["title"] ) })
+            .to_string()
+            .starts_with("Unknown guzzle attribute `kyes`, expected one of `append`, "));
+        assert!(error(quote! { ( parser = ) })
+            .to_string()
+            .starts_with("`parser` must be a function or closure"));
+        assert!(error(quote! { ( keys = [title] ) })
+            .to_string()
+            .starts_with("keys must be a list of string literals"));
+    }
+
+    #[test]
+    fn skip_is_no_guzzle() -> Result<(), syn::Error> {
+        let fields: syn::FieldsNamed = parse2(quote! {
+            {
+                #[guzzle(skip)]
+                cache: Cache,
+                #[guzzle(skip, keys = ["cache"])]
+                keyed: Cache,
+            }
+        })?;
+        let mut fields = fields.named.iter();
+        let skipped = FieldAttribute::try_from(fields.next().unwrap())?;
+        assert!(skipped.get_keyed().is_empty());
+        let error = FieldAttribute::try_from(fields.next().unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "A #[guzzle(skip)] field isn't guzzled from any keys"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_flag() -> Result<(), syn::Error> {
+        let token_stream = quote! { ( state ) };
+        let attributes: GuzzleKeyedAttribute = parse2(token_stream)?;
+        assert!(attributes.state);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_collect_and_split() -> Result<(), syn::Error> {
+        let token_stream = quote! { ( keys = ["tags"], collect, split = "|" ) };
+        let attributes: GuzzleKeyedAttribute = parse2(token_stream)?;
+        assert!(attributes.collect);
+        assert_eq!("|", attributes.split.unwrap().value());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_key_parsers() -> Result<(), syn::Error> {
+        let field: syn::FieldsNamed = parse2(quote! {
+            {
+                #[guzzle(keys = ["meters"], parser = m_parser, key_parsers = [("feet", f_parser)])]
+                distance: f64,
+            }
+        })?;
+        let field_attribute = FieldAttribute::try_from(field.named.first().unwrap())?;
+        let parsers: Vec<_> = field_attribute
+            .get_arm_parts()
+            .into_iter()
+            .map(|(_, matcher, keyed_attr)| {
+                let parser = keyed_attr.parser_for(matcher).unwrap();
+                (matcher.value(), quote!(#parser).to_string())
+            })
+            .collect();
+        assert_eq!(
+            parsers,
+            vec![
+                ("meters".to_string(), "m_parser".to_string()),
+                ("feet".to_string(), "f_parser".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn key_prefix_has_no_default_key() -> Result<(), syn::Error> {
+        let field: syn::FieldsNamed = parse2(quote! {
+            {
+                #[guzzle(key_prefix = "gallery_image_")]
+                gallery: Vec<(String, String)>,
+            }
+        })?;
+        let field_attribute = FieldAttribute::try_from(field.named.first().unwrap())?;
+        assert!(field_attribute.get_arm_parts().is_empty());
+        let prefixes: Vec<_> = field_attribute
+            .get_prefixed()
+            .into_iter()
+            .map(|(field, key_prefix, _)| (field.to_string(), key_prefix.value()))
+            .collect();
+        assert_eq!(
+            prefixes,
+            vec![("gallery".to_string(), "gallery_image_".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_deep_guzzle_prefix() -> Result<(), syn::Error> {
+        let fields: syn::FieldsNamed = parse2(quote! {
+            {
+                #[deep_guzzle(prefix = "loc_")]
+                location: Location,
+                #[deep_guzzle]
+                other: Location,
+            }
+        })?;
+        let prefixes = fields
+            .named
+            .iter()
+            .map(|field| {
+                let field_attribute = FieldAttribute::try_from(field)?;
+                let deep_guzzle = field_attribute.get_recursion().unwrap();
+                Ok(deep_guzzle.prefix.as_ref().map(LitStr::value))
+            })
+            .collect::<SynResult<Vec<_>>>()?;
+        assert_eq!(prefixes, vec![Some("loc_".to_string()), None]);
+        Ok(())
+    }
+
+    #[test]
+    fn detect_sets() -> Result<(), syn::Error> {
+        let fields: syn::FieldsNamed = parse2(quote! {
+            {
+                #[guzzle(collect)]
+                tags: std::collections::HashSet<String>,
+                #[guzzle(set)]
+                aliased: Tags,
+                #[guzzle(collect)]
+                list: Vec<String>,
+            }
+        })?;
+        let sets = fields
+            .named
+            .iter()
+            .map(|field| {
+                let field_attribute = FieldAttribute::try_from(field)?;
+                Ok(field_attribute.get_keyed()[0].1.set)
+            })
+            .collect::<SynResult<Vec<_>>>()?;
+        assert_eq!(sets, vec![true, true, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn detect_from_str() -> Result<(), syn::Error> {
+        let fields: syn::FieldsNamed = parse2(quote! {
+            {
+                name: String,
+                aliases: Vec<std::string::String>,
+                columns: [Option<String>; 3],
+                count: u64,
+                scores: Vec<f32>,
+                floor: Option<i8>,
+            }
+        })?;
+        let from_str = fields
+            .named
+            .iter()
+            .map(|field| {
+                let field_attribute = FieldAttribute::try_from(field)?;
+                Ok(field_attribute.get_keyed()[0].1.from_str)
+            })
+            .collect::<SynResult<Vec<_>>>()?;
+        assert_eq!(from_str, vec![false, false, false, true, true, true]);
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_keyed_attributes() -> Result<(), syn::Error> {
+        let field: syn::FieldsNamed = parse2(quote! {
+            {
+                #[guzzle(keys = ["col0"], slot = 0)]
+                #[guzzle(keys = ["col2"], slot = 2)]
+                columns: [Option<String>; 3],
+            }
+        })?;
+        let field_attribute = FieldAttribute::try_from(field.named.first().unwrap())?;
+        let slots: Vec<_> = field_attribute
+            .get_arm_parts()
+            .into_iter()
+            .map(|(_, matcher, keyed_attr)| {
+                let slot: usize = keyed_attr.slot.as_ref().unwrap().base10_parse().unwrap();
+                (matcher.value(), slot)
+            })
+            .collect();
+        assert_eq!(
+            slots,
+            vec![("col0".to_string(), 0), ("col2".to_string(), 2)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ignore_unrelated_attributes() -> Result<(), syn::Error> {
+        let field: syn::FieldsNamed = parse2(quote! {
+            {
+                #[allow(unused)]
+                #[serde(rename = "other")]
+                #[cfg_attr(test, something(else))]
+                basic: String,
+            }
+        })?;
+        let field_attribute = FieldAttribute::try_from(field.named.first().unwrap())?;
+        let keys: Vec<_> = field_attribute
+            .get_arm_parts()
+            .into_iter()
+            .map(|(_, matcher, _)| matcher.value())
+            .collect();
+        assert_eq!(keys, vec!["basic".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_unknown_flag() {
+        let token_stream = quote! { ( not_a_flag ) };
+        assert!(parse2::<GuzzleKeyedAttribute>(token_stream).is_err());
+    }
+
+    #[allow(dead_code)]
     fn test_parser(s: String) -> String {
         s
     }
@@ -0,0 +1,654 @@
+use crate::attr::{parse_duplicates, unknown_attribute, Keys};
+use quote::quote;
+use std::convert::TryFrom;
+use syn::{
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseBuffer},
+    parse_quote,
+    punctuated::Punctuated,
+    Attribute, DeriveInput, Ident, LitStr, Path, Token, Type,
+};
+
+type SynResult<T> = Result<T, syn::Error>;
+
+/// This structure models the guzzle attribute when it is applied to the struct itself rather than
+/// to one of its fields. Container attributes change how the whole implementation is generated.
+///
+/// ```ignore
+/// #[derive(Guzzle)]
+/// #[guzzle(never_panic)]
+/// struct GuzzleExample {
+///     /// `u64_parser` returns an `Option`, and the key and value are handed back when it's
+///     /// `None`. A plain `parser` here would be a compile error.
+///     #[guzzle(opt_parser = u64_parser)]
+///     other_types: u64,
+/// }
+/// ```
+#[derive(Default)]
+pub struct ContainerAttribute {
+    /// Every field has to be parsed by `FromStr`, an `opt_parser` or a `try_parser`, so values
+    /// that can't be parsed are handed back rather than panicking. Transforms, setters, defaults
+    /// and `finish` aren't checked, as they can't turn a value down.
+    pub never_panic: bool,
+    /// Generate `BytesGuzzle` rather than `Guzzle`, so keys and values are bytes not strings
+    pub bytes: bool,
+    /// Also generate a monomorphic inherent `guzzle_one` method alongside the trait
+    pub inherent: bool,
+    /// Also generate an inherent `SCHEMA` constant and `guzzle_schema` method listing the keys
+    pub schema: bool,
+    /// Generate `TomlGuzzle` rather than `Guzzle`, for values that have already been parsed from
+    /// TOML
+    pub toml: bool,
+    /// Unmatched keys starting with this are an error for `TryGuzzle` rather than a leftover
+    pub strict_prefix: Option<LitStr>,
+    /// Every unmatched key is an error for `TryGuzzle`, and `try_guzzle_all` lists them all
+    pub deny_unknown_keys: Option<Ident>,
+    /// Named sets of keys that are only consumed once every key in the set has arrived
+    pub groups: Vec<(Ident, Keys)>,
+    /// Prepended to every key, including those listed in `keys`, in groups and those offered to
+    /// deep guzzle fields
+    pub prefix: Option<LitStr>,
+    /// How keys taken from field names are renamed to match the data's convention
+    pub rename_all: Option<RenameRule>,
+    /// For enums, the key whose value chooses the variant
+    pub tag: Option<LitStr>,
+    /// The type of the values, if they aren't `String`
+    pub value: Option<Type>,
+    /// Find the arm for a key with a perfect hash rather than comparing it with every key
+    pub perfect_hash: Option<LitStr>,
+    /// Every field treats empty values as if their key had never appeared
+    pub empty_is_missing: Option<Ident>,
+    /// The duplicate policy for fields that don't have their own
+    pub duplicates: Option<LitStr>,
+    /// A method called by `finalize`, and so by `from_metadata_finalized`, once everything has
+    /// been guzzled and defaults filled in
+    pub finish: Option<Ident>,
+    /// Where guzzle is, for crates that re-export it rather than depending on it directly
+    pub krate: Option<Path>,
+    /// Values are `&'a str` borrowed for the struct's lifetime, so fields can borrow them too
+    pub borrowed: Option<Ident>,
+}
+
+impl ContainerAttribute {
+    /// The path generated code reaches guzzle through, `::guzzle` unless `crate` says otherwise
+    pub fn krate(&self) -> proc_macro2::TokenStream {
+        match &self.krate {
+            Some(krate) => quote! { #krate },
+            None => quote! { ::guzzle },
+        }
+    }
+
+    /// The type of the values being guzzled, `String` unless `value` says otherwise
+    pub fn value_type(&self) -> proc_macro2::TokenStream {
+        match &self.value {
+            Some(value) => quote! { #value },
+            None => quote! { String },
+        }
+    }
+
+    /// The key a field would be guzzled from if it were named after the field, once it's been
+    /// renamed and prefixed
+    pub fn field_key(&self, field: &str) -> String {
+        let key = match self.rename_all {
+            Some(rule) => rule.apply(field),
+            None => field.to_string(),
+        };
+        match &self.prefix {
+            Some(prefix) => format!("{}{}", prefix.value(), key),
+            None => key,
+        }
+    }
+}
+
+impl TryFrom<&DeriveInput> for ContainerAttribute {
+    type Error = syn::Error;
+
+    fn try_from(ast: &DeriveInput) -> SynResult<Self> {
+        let mut container_attribute = ContainerAttribute::default();
+
+        for attr in ast.attrs.iter().filter(|attr| is_guzzle_attr(attr)) {
+            let raw_attrs: RawContainerAttributes = syn::parse2(attr.tokens.clone())?;
+            raw_attrs.0.into_iter().for_each(|raw_attr| match raw_attr {
+                RawContainerAttribute::NeverPanic => container_attribute.never_panic = true,
+                RawContainerAttribute::Bytes => container_attribute.bytes = true,
+                RawContainerAttribute::Inherent => container_attribute.inherent = true,
+                RawContainerAttribute::Schema => container_attribute.schema = true,
+                RawContainerAttribute::Toml => container_attribute.toml = true,
+                RawContainerAttribute::DenyUnknownKeys(ident) => {
+                    container_attribute.deny_unknown_keys = Some(ident)
+                }
+                RawContainerAttribute::EmptyIsMissing(ident) => {
+                    container_attribute.empty_is_missing = Some(ident)
+                }
+                RawContainerAttribute::Duplicates(duplicates) => {
+                    container_attribute.duplicates = Some(duplicates)
+                }
+                RawContainerAttribute::Finish(finish) => container_attribute.finish = Some(finish),
+                RawContainerAttribute::Crate(krate) => container_attribute.krate = Some(krate),
+                RawContainerAttribute::Borrowed(ident) => {
+                    container_attribute.borrowed = Some(ident)
+                }
+                RawContainerAttribute::StrictPrefix(prefix) => {
+                    container_attribute.strict_prefix = Some(prefix)
+                }
+                RawContainerAttribute::Group(groups) => container_attribute.groups.extend(groups),
+                RawContainerAttribute::Prefix(prefix) => container_attribute.prefix = Some(prefix),
+                RawContainerAttribute::Tag(tag) => container_attribute.tag = Some(tag),
+                RawContainerAttribute::Value(value) => container_attribute.value = Some(*value),
+                RawContainerAttribute::Matcher(matcher) => {
+                    container_attribute.perfect_hash =
+                        Some(matcher).filter(|matcher| matcher.value() == "phf")
+                }
+                RawContainerAttribute::RenameAll(rule) => {
+                    container_attribute.rename_all = Some(rule)
+                }
+            });
+        }
+
+        // Borrowed values are just another value type, borrowed for the struct's own lifetime
+        if let Some(borrowed) = &container_attribute.borrowed {
+            let lifetime = ast.generics.lifetimes().next().map(|param| &param.lifetime);
+            match (lifetime, &container_attribute.value) {
+                (Some(lifetime), None) => {
+                    container_attribute.value = Some(parse_quote! { &#lifetime str })
+                }
+                (None, _) => {
+                    return Err(syn::Error::new(
+                        borrowed.span(),
+                        "#[guzzle(borrowed)] needs a lifetime to borrow for, eg `struct Post<'a>`",
+                    ))
+                }
+                (Some(_), Some(value)) => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "#[guzzle(borrowed)] already sets the value type to `&str`",
+                    ))
+                }
+            }
+        }
+
+        // Groups refer to the keys fields are guzzled from, so they have to be prefixed the same
+        // way. Attributes may come in any order so this waits until they've all been read.
+        if let Some(prefix) = &container_attribute.prefix {
+            container_attribute
+                .groups
+                .iter_mut()
+                .for_each(|(_, keys)| *keys = keys.prefixed(prefix));
+        }
+
+        Ok(container_attribute)
+    }
+}
+
+fn is_guzzle_attr(attribute: &Attribute) -> bool {
+    let path = &attribute.path;
+    quote!(#path).to_string() == "guzzle"
+}
+
+/// The comma separated contents of a single `#[guzzle(...)]` container attribute
+struct RawContainerAttributes(Vec<RawContainerAttribute>);
+
+impl Parse for RawContainerAttributes {
+    fn parse(input: &ParseBuffer) -> SynResult<Self> {
+        let mut raw_attrs = vec![];
+        // As with fields, `#[guzzle]` with no brackets is valid, it just doesn't do anything
+        if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let punctuated_attrs: Punctuated<RawContainerAttribute, Token![,]> =
+                content.parse_terminated(RawContainerAttribute::parse)?;
+            raw_attrs.extend(punctuated_attrs);
+        }
+        Ok(RawContainerAttributes(raw_attrs))
+    }
+}
+
+pub enum RawContainerAttribute {
+    NeverPanic,
+    Bytes,
+    Inherent,
+    Schema,
+    Toml,
+    DenyUnknownKeys(Ident),
+    EmptyIsMissing(Ident),
+    Duplicates(LitStr),
+    Finish(Ident),
+    Crate(Path),
+    Borrowed(Ident),
+    StrictPrefix(LitStr),
+    Prefix(LitStr),
+    RenameAll(RenameRule),
+    Tag(LitStr),
+    Value(Box<Type>),
+    Matcher(LitStr),
+    Group(Vec<(Ident, Keys)>),
+}
+
+/// The container attributes that stand on their own, and those that take a value. `group` is
+/// followed by brackets, which is close enough to standing on its own.
+const CONTAINER_FLAGS: &[&str] = &[
+    "never_panic",
+    "bytes",
+    "inherent",
+    "schema",
+    "toml",
+    "deny_unknown_keys",
+    "empty_is_missing",
+    "borrowed",
+    "group",
+];
+const CONTAINER_VALUES: &[&str] = &[
+    "strict_prefix",
+    "prefix",
+    "tag",
+    "value",
+    "duplicates",
+    "finish",
+    "crate",
+    "matcher",
+    "rename_all",
+];
+
+impl Parse for RawContainerAttribute {
+    fn parse(input: &ParseBuffer) -> SynResult<Self> {
+        // `crate` is a keyword, so it isn't an ordinary ident
+        let name = Ident::parse_any(input)?;
+        let name_str = name.to_string();
+
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            return match name_str.as_ref() {
+                "strict_prefix" => Ok(RawContainerAttribute::StrictPrefix(input.parse()?)),
+                "prefix" => Ok(RawContainerAttribute::Prefix(input.parse()?)),
+                "tag" => Ok(RawContainerAttribute::Tag(input.parse()?)),
+                "value" => Ok(RawContainerAttribute::Value(input.parse()?)),
+                "duplicates" => Ok(RawContainerAttribute::Duplicates(parse_duplicates(input)?)),
+                "finish" => Ok(RawContainerAttribute::Finish(input.parse()?)),
+                "crate" => {
+                    let krate: LitStr = input.parse()?;
+                    Ok(RawContainerAttribute::Crate(krate.parse()?))
+                }
+                "matcher" => {
+                    let matcher: LitStr = input.parse()?;
+                    match matcher.value().as_ref() {
+                        "phf" | "match" => Ok(RawContainerAttribute::Matcher(matcher)),
+                        _ => Err(syn::Error::new(
+                            matcher.span(),
+                            "The matcher must be \"match\" or \"phf\"",
+                        )),
+                    }
+                }
+                "rename_all" => Ok(RawContainerAttribute::RenameAll(input.parse()?)),
+                _ => Err(unknown_attribute(&name, CONTAINER_FLAGS, CONTAINER_VALUES)),
+            };
+        }
+
+        match name_str.as_ref() {
+            "never_panic" => Ok(RawContainerAttribute::NeverPanic),
+            "bytes" => Ok(RawContainerAttribute::Bytes),
+            "inherent" => Ok(RawContainerAttribute::Inherent),
+            "schema" => Ok(RawContainerAttribute::Schema),
+            "toml" => Ok(RawContainerAttribute::Toml),
+            "deny_unknown_keys" => Ok(RawContainerAttribute::DenyUnknownKeys(name)),
+            "empty_is_missing" => Ok(RawContainerAttribute::EmptyIsMissing(name)),
+            "borrowed" => Ok(RawContainerAttribute::Borrowed(name)),
+            "group" => {
+                let content;
+                parenthesized!(content in input);
+                let groups: Punctuated<Group, Token![,]> =
+                    content.parse_terminated(Group::parse)?;
+                Ok(RawContainerAttribute::Group(
+                    groups
+                        .into_iter()
+                        .map(|Group(name, keys)| (name, keys))
+                        .collect(),
+                ))
+            }
+            _ => Err(unknown_attribute(&name, CONTAINER_FLAGS, CONTAINER_VALUES)),
+        }
+    }
+}
+
+/// The case conventions `rename_all` can convert field names to, named as they are in serde.
+/// Field names are assumed to be snake_case already.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    /// Converts a snake_case field name to this convention
+    pub fn apply(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+impl Parse for RenameRule {
+    fn parse(input: &ParseBuffer) -> SynResult<Self> {
+        let rule: LitStr = input.parse()?;
+        match rule.value().as_ref() {
+            "lowercase" => Ok(RenameRule::Lower),
+            "UPPERCASE" => Ok(RenameRule::Upper),
+            "PascalCase" => Ok(RenameRule::Pascal),
+            "camelCase" => Ok(RenameRule::Camel),
+            "snake_case" => Ok(RenameRule::Snake),
+            "SCREAMING_SNAKE_CASE" | "SCREAMING_SNAKE" => Ok(RenameRule::ScreamingSnake),
+            "kebab-case" => Ok(RenameRule::Kebab),
+            "SCREAMING-KEBAB-CASE" => Ok(RenameRule::ScreamingKebab),
+            _ => Err(syn::Error::new(
+                rule.span(),
+                "rename_all must be one of \"lowercase\", \"UPPERCASE\", \"PascalCase\", \
+                 \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \"kebab-case\" or \
+                 \"SCREAMING-KEBAB-CASE\"",
+            )),
+        }
+    }
+}
+
+/// A single `name = ["key1", "key2"]` inside `group(...)`
+struct Group(Ident, Keys);
+
+impl Parse for Group {
+    fn parse(input: &ParseBuffer) -> SynResult<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        Ok(Group(name, input.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::{parse2, LitStr};
+
+    #[test]
+    fn parse_never_panic() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(never_panic)]
+            struct Example {}
+        })?;
+        let container_attribute = ContainerAttribute::try_from(&input)?;
+        assert!(container_attribute.never_panic);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_multiple() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(never_panic, bytes, inherent, schema, strict_prefix = "wp_", deny_unknown_keys)]
+            struct Example {}
+        })?;
+        let container_attribute = ContainerAttribute::try_from(&input)?;
+        assert_eq!(
+            container_attribute.strict_prefix.unwrap().value(),
+            "wp_".to_string()
+        );
+        assert!(container_attribute.never_panic);
+        assert!(container_attribute.bytes);
+        assert!(container_attribute.inherent);
+        assert!(container_attribute.schema);
+        assert!(container_attribute.deny_unknown_keys.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_value() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(value = serde_json::Value, never_panic)]
+            struct Example {}
+        })?;
+        let container_attribute = ContainerAttribute::try_from(&input)?;
+        assert_eq!(
+            container_attribute.value_type().to_string(),
+            "serde_json :: Value"
+        );
+        assert!(container_attribute.never_panic);
+        assert_eq!(
+            ContainerAttribute::default().value_type().to_string(),
+            "String"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_borrowed() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(borrowed)]
+            struct Example<'a> {}
+        })?;
+        let container_attribute = ContainerAttribute::try_from(&input)?;
+        assert!(container_attribute.borrowed.is_some());
+        assert_eq!(container_attribute.value_type().to_string(), "& 'a str");
+
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(borrowed)]
+            struct Example {}
+        })?;
+        match ContainerAttribute::try_from(&input) {
+            Err(error) => assert!(error.to_string().contains("needs a lifetime")),
+            Ok(_) => panic!("borrowed without a lifetime should fail"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_duplicates() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(duplicates = "first")]
+            struct Example {}
+        })?;
+        let duplicates = ContainerAttribute::try_from(&input)?.duplicates;
+        assert_eq!(duplicates.unwrap().value(), "first");
+
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(duplicates = "oldest")]
+            struct Example {}
+        })?;
+        assert!(ContainerAttribute::try_from(&input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_container_attributes_are_explained() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(bytes = true)]
+            struct Example {}
+        })?;
+        let error = ContainerAttribute::try_from(&input).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "`bytes` doesn't take a value, write it on its own"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_crate() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(crate = "framework::storage")]
+            struct Example {}
+        })?;
+        let container = ContainerAttribute::try_from(&input)?;
+        assert_eq!(container.krate().to_string(), "framework :: storage");
+        let input: DeriveInput = parse2(quote! { struct Example {} })?;
+        let container = ContainerAttribute::try_from(&input)?;
+        assert_eq!(container.krate().to_string(), ":: guzzle");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_finish() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(finish = locate)]
+            struct Example {}
+        })?;
+        let finish = ContainerAttribute::try_from(&input)?.finish;
+        assert_eq!(finish.unwrap().to_string(), "locate");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_matcher() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(matcher = "phf")]
+            struct Example {}
+        })?;
+        assert!(ContainerAttribute::try_from(&input)?.perfect_hash.is_some());
+
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(matcher = "match")]
+            struct Example {}
+        })?;
+        assert!(ContainerAttribute::try_from(&input)?.perfect_hash.is_none());
+
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(matcher = "hash")]
+            struct Example {}
+        })?;
+        assert!(ContainerAttribute::try_from(&input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_groups() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(group(location = ["lat", "lng"], size = ["width", "height"]))]
+            struct Example {}
+        })?;
+        let container_attribute = ContainerAttribute::try_from(&input)?;
+        let groups: Vec<_> = container_attribute
+            .groups
+            .iter()
+            .map(|(name, keys)| {
+                let keys: Vec<_> = keys.iter().map(LitStr::value).collect();
+                (name.to_string(), keys)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "location".to_string(),
+                    vec!["lat".to_string(), "lng".to_string()]
+                ),
+                (
+                    "size".to_string(),
+                    vec!["width".to_string(), "height".to_string()]
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn prefix_groups() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(group(location = ["lat", "lng"]))]
+            #[guzzle(prefix = "location_")]
+            struct Example {}
+        })?;
+        let container_attribute = ContainerAttribute::try_from(&input)?;
+        assert_eq!(
+            container_attribute.prefix.unwrap().value(),
+            "location_".to_string()
+        );
+        let keys: Vec<_> = container_attribute.groups[0]
+            .1
+            .iter()
+            .map(LitStr::value)
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["location_lat".to_string(), "location_lng".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rename_rules() -> Result<(), syn::Error> {
+        let renamed: Vec<_> = [
+            "lowercase",
+            "UPPERCASE",
+            "PascalCase",
+            "camelCase",
+            "snake_case",
+            "SCREAMING_SNAKE_CASE",
+            "kebab-case",
+            "SCREAMING-KEBAB-CASE",
+        ]
+        .iter()
+        .map(|rule| {
+            let rule: RenameRule = syn::parse_str(&format!("{:?}", rule))?;
+            Ok(rule.apply("post_view_count"))
+        })
+        .collect::<SynResult<_>>()?;
+        assert_eq!(
+            renamed,
+            vec![
+                "post_view_count",
+                "POST_VIEW_COUNT",
+                "PostViewCount",
+                "postViewCount",
+                "post_view_count",
+                "POST_VIEW_COUNT",
+                "post-view-count",
+                "POST-VIEW-COUNT",
+            ]
+        );
+        let unknown: SynResult<RenameRule> = syn::parse_str("\"Title Case\"");
+        assert!(unknown.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ignore_unrelated_attributes() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[derive(Debug)]
+            struct Example {}
+        })?;
+        let container_attribute = ContainerAttribute::try_from(&input)?;
+        assert!(!container_attribute.never_panic);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_container_attribute() -> Result<(), syn::Error> {
+        let input: DeriveInput = parse2(quote! {
+            #[guzzle(not_a_thing)]
+            struct Example {}
+        })?;
+        assert!(ContainerAttribute::try_from(&input).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use crate::attr::{FieldAttribute, GuzzleKeyedAttribute};
+use crate::container::ContainerAttribute;
+use crate::proc_macro::TokenStream;
+use crate::validate::validate_fields;
+use crate::{
+    apply_container_keys, fields_to_attributes, generate_arm, generate_prefix_arm,
+    generate_regex_arm, handle_errors, hygienic, Context,
+};
+use quote::quote;
+use std::convert::TryFrom;
+use syn::{DataEnum, DeriveInput, Fields, LitStr, Variant};
+
+type SynResult<T> = Result<T, syn::Error>;
+
+/// Generates `Guzzle` for an enum annotated with `#[guzzle(tag = "...")]`. The tag key's value
+/// chooses the variant, by its name or its `#[guzzle(rename = "...")]`, and the enum becomes that
+/// variant with default fields unless it already was. Every other key is guzzled into the fields
+/// of the current variant, which take the same attributes as a struct's fields except for those
+/// that need a state or rest field.
+pub fn generate_enum_impl(ast: &DeriveInput, data: &DataEnum) -> TokenStream {
+    let container = match ContainerAttribute::try_from(ast) {
+        Ok(container) => container,
+        Err(error) => return handle_errors(vec![error]),
+    };
+    let tag = match &container.tag {
+        Some(tag) => tag,
+        None => {
+            return handle_errors(vec![syn::Error::new(
+                ast.ident.span(),
+                "Enums need #[guzzle(tag = \"...\")] naming the key that chooses the variant",
+            )])
+        }
+    };
+    let unsupported = container.bytes
+        || container.toml
+        || container.inherent
+        || container.schema
+        || container.value.is_some()
+        || container.strict_prefix.is_some()
+        || container.deny_unknown_keys.is_some()
+        || container.duplicates.is_some()
+        || container.finish.is_some()
+        || !container.groups.is_empty();
+    if unsupported {
+        return handle_errors(vec![syn::Error::new(
+            ast.ident.span(),
+            "Enums only support the tag, prefix, rename_all and never_panic container attributes",
+        )]);
+    }
+
+    let context = Context {
+        container: &container,
+        state: None,
+        rest: None,
+        fallible: false,
+        in_variant: true,
+        perfect_hash: None,
+    };
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let mut errors = vec![];
+    let mut tag_arms = vec![];
+    let mut variant_arms = vec![];
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let tag_value = match variant_tag_value(variant) {
+            Ok(tag_value) => tag_value,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let mut attributes = match fields_to_attributes(fields) {
+                    Ok(attributes) => attributes,
+                    Err(field_errors) => {
+                        errors.extend(field_errors);
+                        continue;
+                    }
+                };
+                apply_container_keys(&container, &mut attributes);
+                errors.extend(unsupported_in_variant(&attributes));
+                errors.extend(validate_fields(&container, &attributes));
+
+                let mut bound = vec![];
+                let mut arms = vec![];
+                for field_attribute in &attributes {
+                    for (field, matcher, keyed_attr) in field_attribute.get_arm_parts() {
+                        arms.push(generate_arm(&context, field, matcher, keyed_attr));
+                        bound.push(field);
+                    }
+                    for (field, key_prefix, keyed_attr) in field_attribute.get_prefixed() {
+                        arms.push(generate_prefix_arm(&context, field, key_prefix, keyed_attr));
+                        bound.push(field);
+                    }
+                    for (field, key_regex, keyed_attr) in field_attribute.get_regexes() {
+                        arms.push(generate_regex_arm(&context, field, key_regex, keyed_attr));
+                        bound.push(field);
+                    }
+                }
+                bound.dedup();
+
+                let defaults = fields.named.iter().map(|field| &field.ident);
+                tag_arms.push(quote! {
+                    #tag_value => {
+                        if !matches!(self, #name::#ident { .. }) {
+                            *self = #name::#ident { #( #defaults: Default::default() ),* };
+                        }
+                    }
+                });
+                variant_arms.push(if arms.is_empty() {
+                    quote! { #name::#ident { .. } => Some((key, value)), }
+                } else {
+                    quote! {
+                        #name::#ident { #( #bound, )* .. } => {
+                            match k {
+                                #( #arms )*
+                                _ => return Some((key, value)),
+                            };
+                            None
+                        }
+                    }
+                });
+            }
+            Fields::Unit => {
+                tag_arms.push(quote! { #tag_value => *self = #name::#ident, });
+                variant_arms.push(quote! { #name::#ident => Some((key, value)), });
+            }
+            Fields::Unnamed(_) => errors.push(syn::Error::new(
+                ident.span(),
+                "Only unit variants and variants with named fields are supported",
+            )),
+        }
+    }
+    if !errors.is_empty() {
+        return handle_errors(errors);
+    }
+
+    let gen = quote! {
+        impl #impl_generics _guzzle::Guzzle for #name #ty_generics #where_clause {
+            fn guzzle<__K>(&mut self, (key, value): (__K, String)) -> Option<(__K, String)>
+            where __K: AsRef<str>
+            {
+                let k: &str = key.as_ref();
+                if k == #tag {
+                    match value.as_str() {
+                        #( #tag_arms )*
+                        _ => return Some((key, value)),
+                    }
+                    return None;
+                }
+                match self {
+                    #( #variant_arms )*
+                }
+            }
+        }
+    };
+    hygienic(&container, gen)
+}
+
+/// The tag value that selects the variant, which is its name unless it's been renamed
+fn variant_tag_value(variant: &Variant) -> SynResult<LitStr> {
+    let mut tag_value = LitStr::new(&variant.ident.to_string(), variant.ident.span());
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("guzzle"))
+    {
+        let keyed_attr: GuzzleKeyedAttribute = syn::parse2(attr.tokens.clone())?;
+        if let Some(rename) = keyed_attr.rename {
+            tag_value = rename;
+        }
+    }
+    Ok(tag_value)
+}
+
+/// Variants don't have anywhere to keep state, so fields that need it are errors
+fn unsupported_in_variant(attributes: &[FieldAttribute]) -> Vec<syn::Error> {
+    let mut errors = vec![];
+    for field_attribute in attributes {
+        let field = field_attribute
+            .get_state()
+            .or_else(|| field_attribute.get_rest())
+            .or_else(|| {
+                field_attribute
+                    .get_recursion()
+                    .map(|deep_guzzle| &deep_guzzle.field)
+            });
+        if let Some(field) = field {
+            errors.push(syn::Error::new(
+                field.span(),
+                "state, rest and deep_guzzle fields are not supported in enum variants",
+            ));
+        }
+        for (field, keyed_attr) in field_attribute.get_keyed() {
+            let defaults = keyed_attr.default_if_absent.is_some() || keyed_attr.default.is_some();
+            if defaults || keyed_attr.required.is_some() {
+                errors.push(syn::Error::new(
+                    field.span(),
+                    "default, default_if_absent and required are not supported in enum variants",
+                ));
+            }
+            // The fields are borrowed from `self`, so its methods can't be called
+            if let Some(setter) = &keyed_attr.setter {
+                errors.push(syn::Error::new(
+                    setter.span(),
+                    "setter is not supported in enum variants",
+                ));
+            }
+            if let Some(duplicates) = &keyed_attr.duplicates {
+                errors.push(syn::Error::new(
+                    duplicates.span(),
+                    "duplicates is not supported in enum variants",
+                ));
+            }
+        }
+    }
+    errors
+}
@@ -1,33 +1,194 @@
 extern crate proc_macro;
 
-use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields, FieldsNamed};
+use crate::attr::{is_type, DeepGuzzle, FieldAttribute, GuzzleKeyedAttribute, Keys};
+use crate::container::ContainerAttribute;
+use crate::phf::PerfectHash;
 use crate::proc_macro::TokenStream;
-use crate::attr::FieldAttribute;
-use std::convert::TryInto;
+use quote::{quote, quote_spanned};
+use std::convert::{TryFrom, TryInto};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Data, DataStruct, DeriveInput, Expr, Fields, FieldsNamed,
+    Generics, Ident, LitByteStr, LitStr, Type, TypePath, WherePredicate,
+};
 
 mod attr;
+mod container;
+mod enums;
+mod phf;
+mod toml;
+mod tuples;
+mod unguzzle;
+mod validate;
+
+type SynResult<T> = Result<T, syn::Error>;
 
 #[proc_macro_derive(Guzzle, attributes(guzzle, no_guzzle, deep_guzzle))]
 pub fn guzzle_macro_derive(input: TokenStream) -> TokenStream {
     // Construct a representation of Rust code as an abstract syntax tree
-    let ast = parse_macro_input!(input);
+    let ast: DeriveInput = parse_macro_input!(input);
 
     // Build the trait implementation
-    impl_guzzle(ast)
+    match &ast.data {
+        Data::Enum(data) => return enums::generate_enum_impl(&ast, data),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(fields),
+            ..
+        }) => return tuples::generate_tuple_impl(&ast, fields),
+        _ => {}
+    }
+    impl_guzzle(ast, "Guzzle", attributes_to_generated_code)
 }
 
-fn impl_guzzle(ast: DeriveInput) -> TokenStream {
-    match &ast.data {
-        Data::Struct(s) => match &s.fields {
-            Fields::Named(fields) => impl_guzzle_named_fields(&ast, fields),
-            _ => unimplemented!(),
-        },
-        _ => unimplemented!(),
+#[proc_macro_derive(Unguzzle, attributes(guzzle, no_guzzle, deep_guzzle))]
+pub fn unguzzle_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input);
+    impl_guzzle(ast, "Unguzzle", unguzzle::attributes_to_generated_code)
+}
+
+/// `GuzzleDiff` works entirely from `Unguzzle`, so there's nothing to generate beyond the impl
+#[proc_macro_derive(GuzzleDiff)]
+pub fn guzzle_diff_macro_derive(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = parse_macro_input!(input);
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    // Any problems with the container attributes are reported by the other derives
+    let container = ContainerAttribute::try_from(&ast).unwrap_or_default();
+    let gen = quote! {
+        impl #impl_generics _guzzle::GuzzleDiff for #name #ty_generics #where_clause {}
+    };
+    hygienic(&container, gen)
+}
+
+/// Puts the generated code in an anonymous const, where guzzle is always `_guzzle` whatever it's
+/// called at the call site and none of the traits need to be imported. `String`, `vec!` and the
+/// like come from guzzle too, so the code works in `no_std` crates.
+fn hygienic(container: &ContainerAttribute, gen: proc_macro2::TokenStream) -> TokenStream {
+    let krate = container.krate();
+    let gen = quote! {
+        #[doc(hidden)]
+        const _: () = {
+            use #krate as _guzzle;
+            #[allow(unused_imports)]
+            use _guzzle::__private::*;
+            #gen
+        };
+    };
+    gen.into()
+}
+
+/// Turns the parsed struct and its attributes into an implementation
+type Generator = fn(&DeriveInput, &ContainerAttribute, Vec<FieldAttribute>) -> TokenStream;
+
+fn impl_guzzle(ast: DeriveInput, derive: &str, generator: Generator) -> TokenStream {
+    let message = match &ast.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => return impl_guzzle_named_fields(&ast, fields, generator),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(_),
+            ..
+        }) => format!(
+            "{} can't be derived for tuple structs, name the fields so each has a key",
+            derive
+        ),
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            ..
+        }) => format!(
+            "{} can't be derived for unit structs, there are no fields for keys to go into",
+            derive
+        ),
+        Data::Enum(_) => format!(
+            "{} can't be derived for enums, only for structs with named fields",
+            derive
+        ),
+        // Guzzle takes enums and tuple structs before getting here
+        Data::Union(_) if derive == "Guzzle" => {
+            "Guzzle can't be derived for unions, only for structs and enums".to_string()
+        }
+        Data::Union(_) => format!(
+            "{} can't be derived for unions, only for structs with named fields",
+            derive
+        ),
+    };
+    handle_errors(vec![syn::Error::new(ast.ident.span(), message)])
+}
+
+/// Adds the bounds the generated code needs for fields whose type is one of the struct's type
+/// parameters, so `struct Meta<T> { #[deep_guzzle] inner: T }` doesn't have to spell them out.
+/// Deep guzzle fields need to guzzle (or unguzzle), and fields parsed with `FromStr` need that and
+/// a displayable error, or `Display` to be output.
+pub(crate) fn infer_bounds(
+    ast: &DeriveInput,
+    container: &ContainerAttribute,
+    attributes: &[FieldAttribute],
+    unguzzle: bool,
+) -> Generics {
+    let params: Vec<_> = ast
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    let is_param = |ty: &Type| match ty {
+        Type::Path(TypePath { qself: None, path }) => path
+            .get_ident()
+            .is_some_and(|ident| params.contains(&ident)),
+        _ => false,
+    };
+    let value_type = container.value_type();
+    let mut predicates: Vec<WherePredicate> = vec![];
+    for field_attribute in attributes {
+        if let Some(deep_guzzle) = field_attribute.get_recursion() {
+            let ty = match &deep_guzzle.ty {
+                Some(ty) if is_param(ty) => ty,
+                _ => continue,
+            };
+            predicates.push(match (unguzzle, container.bytes, container.toml) {
+                (true, _, _) => parse_quote! { #ty: _guzzle::Unguzzle },
+                (false, true, _) => parse_quote! { #ty: _guzzle::BytesGuzzle },
+                (false, false, true) => parse_quote! { #ty: _guzzle::TomlGuzzle },
+                (false, false, false) => parse_quote! { #ty: _guzzle::Guzzle<#value_type> },
+            });
+            // Rows and optional fields are created as they're guzzled into
+            if !unguzzle && (deep_guzzle.option || deep_guzzle.vec) {
+                predicates.push(parse_quote! { #ty: Default });
+            }
+        }
+        for (_, keyed_attr) in field_attribute.get_keyed() {
+            let ty = match &keyed_attr.value_ty {
+                Some(ty) if is_param(ty) && !keyed_attr.matches_pattern() => ty,
+                _ => continue,
+            };
+            if unguzzle {
+                if keyed_attr.unparser.is_none() {
+                    predicates.push(parse_quote! { #ty: ::core::fmt::Display });
+                }
+                continue;
+            }
+            let parsed_with_from_str = keyed_attr.from_str
+                && keyed_attr.parser.is_none()
+                && keyed_attr.opt_parser.is_none()
+                && keyed_attr.try_parser.is_none()
+                && keyed_attr.parser_with_key.is_none()
+                && !container.bytes
+                && !container.toml
+                && container.value.is_none();
+            if parsed_with_from_str {
+                predicates.push(parse_quote! { #ty: ::core::str::FromStr });
+                predicates.push(parse_quote! {
+                    <#ty as ::core::str::FromStr>::Err: ::core::fmt::Display
+                });
+            }
+        }
     }
+    let mut generics = ast.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
 }
 
-fn fields_to_attributes(fields: &FieldsNamed) -> Result<Vec<FieldAttribute>, Vec<syn::Error>> {
+fn fields_to_attributes(fields: &FieldsNamed) -> Result<Vec<FieldAttribute<'_>>, Vec<syn::Error>> {
     let mut oks = vec![];
     let mut errs = vec![];
     fields.named.iter().for_each(|field| {
@@ -52,53 +213,1505 @@ fn handle_errors(errors: Vec<syn::Error>) -> TokenStream {
     output
 }
 
-fn impl_guzzle_named_fields(ast: &DeriveInput, fields: &FieldsNamed) -> TokenStream {
+fn impl_guzzle_named_fields(
+    ast: &DeriveInput,
+    fields: &FieldsNamed,
+    generator: Generator,
+) -> TokenStream {
+    let container = match ContainerAttribute::try_from(ast) {
+        Ok(container) => container,
+        Err(err) => return handle_errors(vec![err]),
+    };
     match fields_to_attributes(fields) {
-        Ok(attr) => attributes_to_generated_code(ast, attr),
+        Ok(mut attr) => {
+            apply_container_keys(&container, &mut attr);
+            generator(ast, &container, attr)
+        }
         Err(err) => handle_errors(err),
     }
 }
 
-fn attributes_to_generated_code(ast: &DeriveInput, attributes: Vec<FieldAttribute>) -> TokenStream {
+/// Applies the container's `rename_all` and `prefix` to the keys of each field
+fn apply_container_keys(container: &ContainerAttribute, attributes: &mut [FieldAttribute]) {
+    // Field names are renamed first so the prefix is left as it was written
+    if let Some(rule) = container.rename_all {
+        attributes
+            .iter_mut()
+            .for_each(|field_attribute| field_attribute.rename_field_keys(|key| rule.apply(key)));
+    }
+    if let Some(prefix) = &container.prefix {
+        attributes
+            .iter_mut()
+            .for_each(|field_attribute| field_attribute.add_prefix(prefix));
+    }
+}
+
+/// Everything about the struct as a whole that generating an individual field might need to know
+struct Context<'a> {
+    container: &'a ContainerAttribute,
+    /// The field holding the `GuzzleState`, if there is one
+    state: Option<&'a Ident>,
+    /// The field that receives everything nothing else consumed, if there is one
+    rest: Option<&'a Ident>,
+    /// Whether this is for `TryGuzzle`, where leftovers are `Ok` and failed parses are errors
+    fallible: bool,
+    /// Whether the fields belong to an enum variant, and so are bound by a match rather than
+    /// reached through `self`
+    in_variant: bool,
+    /// The perfect hash keys are matched by their slot in, for `#[guzzle(matcher = "phf")]`
+    perfect_hash: Option<&'a PerfectHash>,
+}
+
+impl<'a> Context<'a> {
+    /// How an arm refers to the field it's guzzling into
+    fn field(&self, field: &Ident) -> proc_macro2::TokenStream {
+        if self.in_variant {
+            quote! { (*#field) }
+        } else {
+            quote! { self.#field }
+        }
+    }
+
+    /// What the arms' patterns are matched against, which is the key `k` unless it's looked up in
+    /// the perfect hash first
+    fn scrutinee(&self) -> proc_macro2::TokenStream {
+        match self.perfect_hash {
+            Some(perfect_hash) => perfect_hash.lookup(),
+            None => quote! { k },
+        }
+    }
+
+    /// The pattern an arm matches the key with
+    fn pattern(&self, key: &LitStr) -> proc_macro2::TokenStream {
+        match self.perfect_hash {
+            Some(perfect_hash) => {
+                let slot = perfect_hash.slot(&key.value());
+                quote! { Some(#slot) }
+            }
+            None => literal(self.container, key),
+        }
+    }
+
+    /// How an arm consumes the key and value without guzzling them into anything
+    fn consumed(&self) -> proc_macro2::TokenStream {
+        if self.fallible {
+            quote! { Ok(None) }
+        } else {
+            quote! { None }
+        }
+    }
+
+    /// How an arm hands the key and value back without consuming them
+    fn leftover(&self) -> proc_macro2::TokenStream {
+        if self.fallible {
+            quote! { Ok(Some((key, value))) }
+        } else {
+            quote! { Some((key, value)) }
+        }
+    }
+
+    /// The final arm of the match, which either hands back everything that wasn't matched or puts
+    /// it in the rest field
+    fn fallthrough(&self) -> proc_macro2::TokenStream {
+        let leftover = self.leftover();
+        match self.rest {
+            Some(rest) if self.container.bytes => quote! {
+                _ => self.#rest.extend(::core::iter::once((key.as_ref().to_vec(), value))),
+            },
+            Some(rest) => quote! {
+                _ => self.#rest.extend(::core::iter::once((key.as_ref().to_string(), value))),
+            },
+            None => quote! { _ => return #leftover, },
+        }
+    }
+}
+
+fn attributes_to_generated_code(
+    ast: &DeriveInput,
+    container: &ContainerAttribute,
+    attributes: Vec<FieldAttribute>,
+) -> TokenStream {
+    let generics = infer_bounds(ast, container, &attributes, false);
+    let ast = &DeriveInput {
+        generics,
+        ..ast.clone()
+    };
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
-    let mut deep_guzzles = vec![];
-    let mut keys = vec![];
-    let mut matchers = vec![];
-    let mut parsers = vec![];
+    let mut errors = validate::validate_struct(container, name, &attributes);
+    let perfect_hash = match &container.perfect_hash {
+        Some(_) if container.bytes || container.toml => None,
+        Some(matcher) => {
+            let mut keys = vec![];
+            for (_, key, _) in attributes.iter().flat_map(FieldAttribute::get_arm_parts) {
+                if !keys.contains(&key.value()) {
+                    keys.push(key.value());
+                }
+            }
+            let perfect_hash = PerfectHash::new(&keys);
+            if perfect_hash.is_none() {
+                errors.push(syn::Error::new(
+                    matcher.span(),
+                    "Couldn't find a perfect hash for these keys",
+                ));
+            }
+            perfect_hash
+        }
+        None => None,
+    };
+    let context = Context {
+        container,
+        state: attributes.iter().find_map(FieldAttribute::get_state),
+        rest: attributes.iter().find_map(FieldAttribute::get_rest),
+        fallible: false,
+        in_variant: false,
+        perfect_hash: perfect_hash.as_ref(),
+    };
+
+    if let (Some(duplicates), None) = (&container.duplicates, context.state) {
+        if let Some(error) = duplicates_without_state(duplicates) {
+            errors.push(error);
+        }
+    }
+    let mut defaults = vec![];
+    let mut required = vec![];
+    let mut missing = vec![];
+    for (field, keyed_attr) in attributes.iter().flat_map(FieldAttribute::get_keyed) {
+        // A field with a default will always have a value once finalized
+        if let (Some(required_ident), None) = (&keyed_attr.required, &keyed_attr.default) {
+            match context.state {
+                Some(state) => {
+                    // Missing fields are reported under the key they'd be output as
+                    let field = field.to_string();
+                    let key = keyed_attr
+                        .keys
+                        .first()
+                        .map(LitStr::value)
+                        .unwrap_or_else(|| field.clone());
+                    required.push(quote! { usize::from(!self.#state.has_seen(#field)) });
+                    missing.push(quote! {
+                        if !self.#state.has_seen(#field) {
+                            missing.push(#key);
+                        }
+                    });
+                }
+                None => errors.push(syn::Error::new(
+                    required_ident.span(),
+                    "required needs a `guzzle::GuzzleState` field marked with #[guzzle(state)]",
+                )),
+            }
+        }
+        if let (Some(duplicates), None) = (&keyed_attr.duplicates, context.state) {
+            if let Some(error) = duplicates_without_state(duplicates) {
+                errors.push(error);
+            }
+        }
+        if let Some(default) = &keyed_attr.default_if_absent {
+            match generate_default(&context, field, keyed_attr, default) {
+                Ok(default) => defaults.push(default),
+                Err(error) => errors.push(error),
+            }
+        }
+        if let Some(default) = &keyed_attr.default {
+            match generate_default_expr(&context, field, keyed_attr, default) {
+                Ok(default) => defaults.push(default),
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+
+    let mut grouped_keys = vec![];
+    let mut group_arms = vec![];
+    for (group, keys) in &container.groups {
+        match generate_group_arm(&context, &attributes, group, keys) {
+            Ok(group_arm) => group_arms.push(group_arm),
+            Err(error) => errors.push(error),
+        }
+        grouped_keys.extend(keys.iter().map(LitStr::value));
+    }
+    errors.extend(find_unreachable_keys(&attributes, &grouped_keys));
+    if !errors.is_empty() {
+        return handle_errors(errors);
+    }
+
+    // TryGuzzle gets its own arms so that failed parses can be errors. Every struct with string
+    // keys and values gets it, so they can all be built with `TryFromMetadata`.
+    let generate_try = !container.bytes && !container.toml && container.value.is_none();
+    let value_type = container.value_type();
+    let try_context = Context {
+        fallible: true,
+        ..context
+    };
+
+    let mut deep_finalizes = vec![];
+    let mut deep_remaining = vec![];
+    let mut deep_missing = vec![];
+    let mut deep_forwards = vec![];
+    let mut deep_maps = vec![];
+    let mut deep_maps_by_key = true;
+    let mut deep_keys = vec![];
+    let mut bytes_deep_forwards = vec![];
+    let mut try_deep_forwards = vec![];
+    let mut deep_explanations = vec![];
+    let mut arms = vec![];
+    let mut try_arms = vec![];
+    let mut schema = vec![];
+    let mut schema_keys = vec![];
+    let mut explained_keys = vec![];
+    let mut explanations = vec![];
+    // Keys matched by a prefix or a pattern are only tried once no exact key has matched
+    let mut prefix_arms = vec![];
+    let mut prefix_try_arms = vec![];
+    let mut prefix_explanations = vec![];
+    let mut deep_checks = vec![];
+    let mut prefix_checks = vec![];
 
     for field_attribute in &attributes {
         // In the future we might have types of attributes so this might need opening up but it'll
         // do for now.
-        if let Some(expr) = field_attribute.get_recursion() {
-            deep_guzzles.push(expr);
+        if let Some(deep_guzzle) = field_attribute.get_recursion() {
+            let flatten = quote! { flatten().collect::<Vec<_>>() };
+            deep_finalizes.push(generate_deep_call(
+                deep_guzzle,
+                quote! { finalize() },
+                true,
+                flatten.clone(),
+            ));
+            deep_remaining.push(generate_deep_call(
+                deep_guzzle,
+                quote! { remaining_required() },
+                false,
+                quote! { sum::<usize>() },
+            ));
+            deep_missing.push(generate_deep_call(
+                deep_guzzle,
+                quote! { missing_keys() },
+                false,
+                flatten,
+            ));
+
+            deep_forwards.push(generate_deep_forward(
+                &context,
+                deep_guzzle,
+                quote! { _guzzle::Guzzle::guzzle },
+                quote! { None },
+            ));
+            bytes_deep_forwards.push(generate_deep_forward(
+                &context,
+                deep_guzzle,
+                quote! { _guzzle::BytesGuzzle::guzzle_bytes },
+                quote! { None },
+            ));
+            // Deep guzzle fields may not be fallible, so they're guzzled as normal
+            try_deep_forwards.push(generate_deep_forward(
+                &try_context,
+                deep_guzzle,
+                quote! { _guzzle::Guzzle::guzzle },
+                quote! { Ok(None) },
+            ));
+            deep_explanations.push(generate_deep_explanation(deep_guzzle));
+            deep_checks.push(generate_deep_check(deep_guzzle, &value_type));
+            // Prefixed keys can't be built in a const, and repeaters' keys aren't known at all
+            if let (None, false, Some(ty)) = (&deep_guzzle.prefix, deep_guzzle.vec, &deep_guzzle.ty)
+            {
+                deep_keys.push(quote! { <#ty as _guzzle::Guzzle<#value_type>>::KEYS });
+            }
+            // Only a plain deep guzzle field can look its own keys up in a map
+            if deep_guzzle.prefix.is_none() && !deep_guzzle.option && !deep_guzzle.vec {
+                let field = &deep_guzzle.field;
+                deep_maps.push(quote! { _guzzle::Guzzle::guzzle_map(&mut self.#field, map); });
+            } else {
+                deep_maps_by_key = false;
+            }
         } else {
-            for (key, matcher, parser) in field_attribute.get_arm_parts() {
-                keys.push(key);
-                matchers.push(matcher);
-                parsers.push(parser);
+            for (field, matcher, keyed_attr) in field_attribute.get_arm_parts() {
+                // Grouped keys are consumed by their group's arm instead
+                if !grouped_keys.contains(&matcher.value()) {
+                    arms.push(generate_arm(&context, field, matcher, keyed_attr));
+                    if generate_try {
+                        try_arms.push(generate_arm(&try_context, field, matcher, keyed_attr));
+                    }
+                }
+                schema.push(generate_schema_entry(
+                    field,
+                    matcher,
+                    field_attribute.get_doc(),
+                ));
+                let field = field.to_string();
+                schema_keys.push(quote! { (#matcher, #field) });
+                if !explained_keys.contains(&matcher.value()) {
+                    explained_keys.push(matcher.value());
+                    explanations
+                        .push(quote! { #matcher => _guzzle::KeyDisposition::Field(#field), });
+                }
+            }
+            for (field, key_prefix, keyed_attr) in field_attribute.get_prefixed() {
+                prefix_arms.push(generate_prefix_arm(&context, field, key_prefix, keyed_attr));
+                if generate_try {
+                    prefix_try_arms.push(generate_prefix_arm(
+                        &try_context,
+                        field,
+                        key_prefix,
+                        keyed_attr,
+                    ));
+                }
+                let field = field.to_string();
+                prefix_explanations.push(quote! {
+                    _ if key.starts_with(#key_prefix) => _guzzle::KeyDisposition::Field(#field),
+                });
+                prefix_checks.push(quote! { _ if key.starts_with(#key_prefix) => true, });
+            }
+            for (field, key_regex, keyed_attr) in field_attribute.get_regexes() {
+                prefix_arms.push(generate_regex_arm(&context, field, key_regex, keyed_attr));
+                if generate_try {
+                    prefix_try_arms.push(generate_regex_arm(
+                        &try_context,
+                        field,
+                        key_regex,
+                        keyed_attr,
+                    ));
+                }
+                let field = field.to_string();
+                let is_match = generate_regex_match(key_regex);
+                prefix_explanations.push(quote! {
+                    _ if { let k = key; #is_match } => _guzzle::KeyDisposition::Field(#field),
+                });
+                prefix_checks.push(quote! { _ if { let k = key; #is_match } => true, });
             }
         }
     }
 
+    // A key that matches the name of an ignored field is discarded, unless something else wants it,
+    // which the rest field always does
+    let unexplained = match context.rest {
+        Some(rest) => {
+            let rest = rest.to_string();
+            quote! { _guzzle::KeyDisposition::Field(#rest) }
+        }
+        None => {
+            for field in attributes.iter().filter_map(FieldAttribute::get_ignored) {
+                let key = container.field_key(&field.to_string());
+                let field = field.to_string();
+                if !explained_keys.contains(&key) {
+                    explanations
+                        .push(quote! { #key => _guzzle::KeyDisposition::Discarded(#field), });
+                }
+            }
+            quote! { _guzzle::KeyDisposition::Unknown }
+        }
+    };
+    let fallthrough = context.fallthrough();
+    let scrutinee = context.scrutinee();
+    let try_fallthrough = match container.deny_unknown_keys {
+        Some(_) => quote! {
+            _ => return Err(_guzzle::GuzzleError::UnknownKey(k.to_string())),
+        },
+        None => try_context.fallthrough(),
+    };
+    let key_check = if explained_keys.is_empty() {
+        None
+    } else {
+        Some(quote! { #( #explained_keys )|* => true, })
+    };
+    let rest_check = context.rest.is_some();
+    let may_consume = quote! {
+        fn may_consume(key: &str) -> bool {
+            #( #deep_checks )*
+            match key {
+                #key_check
+                #( #prefix_checks )*
+                _ => #rest_check,
+            }
+        }
+    };
+    // Most rows are left over, so checking the key first is cheaper than copying the value
+    let guzzle_ref = if container.value.is_some() {
+        None
+    } else {
+        Some(quote! {
+            fn guzzle_ref(&mut self, key: &str, value: &str) -> bool {
+                <Self as _guzzle::Guzzle>::may_consume(key)
+                    && _guzzle::Guzzle::guzzle(self, (key, value.to_string())).is_none()
+            }
+        })
+    };
+    // Nested consts can't refer to the type's generics, so generic types only list their own keys
+    let keys = if deep_keys.is_empty() || !ast.generics.params.is_empty() {
+        quote! { &[ #( #explained_keys ),* ] }
+    } else {
+        let own_len = explained_keys.len();
+        quote! {
+            {
+                const LEN: usize = #own_len #( + #deep_keys.len() )*;
+                const KEYS: [&str; LEN] = _guzzle::concatenate_keys(&[
+                    &[ #( #explained_keys ),* ],
+                    #( #deep_keys ),*
+                ]);
+                &KEYS
+            }
+        }
+    };
+    // When every arm hands the key back, as when all the fields are deep guzzled, the match is
+    // left to diverge rather than being followed by unreachable code
+    let diverges = group_arms.is_empty()
+        && arms.is_empty()
+        && prefix_arms.is_empty()
+        && context.rest.is_none();
+    let consumed = if diverges {
+        None
+    } else {
+        Some(quote! { ; None })
+    };
+    // Groups and unknown keys always return from `try_guzzle`, so only its own arms and a rest
+    // field can fall through to the end
+    let try_diverges = try_arms.is_empty()
+        && prefix_try_arms.is_empty()
+        && (context.rest.is_none() || container.deny_unknown_keys.is_some());
+    let try_consumed = if try_diverges {
+        None
+    } else {
+        Some(quote! { ; Ok(None) })
+    };
+    // Looking keys up only finds exact matches, anything else has to scan the whole map
+    let finish = container
+        .finish
+        .as_ref()
+        .map(|finish| quote! { self.#finish(); });
+    let guzzle_map = if deep_maps_by_key && prefix_arms.is_empty() && context.rest.is_none() {
+        Some(quote! {
+            fn guzzle_map<__M>(&mut self, map: &mut __M)
+            where __M: _guzzle::MetadataMap<#value_type>
+            {
+                #( #deep_maps )*
+                let keys: &[&str] = &[ #( #explained_keys ),* ];
+                for key in keys {
+                    if let Some(current) = map.take(key) {
+                        if let Some((key, value)) = _guzzle::Guzzle::guzzle(self, current) {
+                            map.put(key, value);
+                        }
+                    }
+                }
+            }
+        })
+    } else {
+        None
+    };
+    let state_leftovers = context
+        .state
+        .map(|state| quote! { .chain(self.#state.take_leftovers()) });
+
+    let guzzle_impl = if container.toml {
+        toml::generate_toml_impl(ast, &context, &attributes)
+    } else if container.bytes {
+        quote! {
+            impl #impl_generics _guzzle::BytesGuzzle for #name #ty_generics #where_clause {
+                fn guzzle_bytes<__K>(
+                    &mut self,
+                    (key, value): (__K, Vec<u8>),
+                ) -> Option<(__K, Vec<u8>)>
+                where __K: AsRef<[u8]>
+                {
+                    #( #bytes_deep_forwards )*
+                    // Convert the key once, ahead of the arms
+                    let k: &[u8] = key.as_ref();
+                    match k {
+                        #( #arms )*
+                        #fallthrough
+                    };
+                    None
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl #impl_generics _guzzle::Guzzle<#value_type> for #name #ty_generics #where_clause {
+                const KEYS: &'static [&'static str] = #keys;
+
+                fn guzzle<__K>(
+                    &mut self,
+                    (key, value): (__K, #value_type),
+                ) -> Option<(__K, #value_type)>
+                where __K: AsRef<str>
+                {
+                    #( #deep_forwards )*
+                    // Convert the key once, so the arms, guards and prefix checks all share it
+                    let k: &str = key.as_ref();
+                    match #scrutinee {
+                        #( #group_arms )*
+                        #( #arms )*
+                        #( #prefix_arms )*
+                        #fallthrough
+                    } #consumed
+                }
+
+                #may_consume
+
+                #guzzle_ref
+
+                #guzzle_map
+
+                fn explain_key(&self, key: &str) -> _guzzle::KeyDisposition {
+                    #( #deep_explanations )*
+                    match key {
+                        #( #explanations )*
+                        #( #prefix_explanations )*
+                        _ => #unexplained,
+                    }
+                }
+
+                fn finalize(&mut self) -> Vec<(String, String)> {
+                    #( #defaults )*
+                    let leftovers = ::core::iter::empty()
+                        #( .chain(#deep_finalizes) )*
+                        #state_leftovers
+                        .collect();
+                    #finish
+                    leftovers
+                }
+
+                fn remaining_required(&self) -> usize {
+                    0 #( + #required )* #( + #deep_remaining )*
+                }
+
+                fn missing_keys(&self) -> Vec<&'static str> {
+                    let mut missing = vec![];
+                    #( #missing )*
+                    #( missing.extend(#deep_missing); )*
+                    missing
+                }
+            }
+        }
+    };
+
+    let guzzle_one = match (container.inherent, container.bytes) {
+        (false, _) => None,
+        (true, false) => Some(quote! {
+            /// Guzzles a single key and value, handing back the value if it wasn't consumed. This
+            /// is the same as `Guzzle::guzzle` without the generic key.
+            pub fn guzzle_one(&mut self, key: &str, value: #value_type) -> Option<#value_type> {
+                _guzzle::Guzzle::guzzle(self, (key, value)).map(|(_, value)| value)
+            }
+        }),
+        (true, true) => Some(quote! {
+            /// Guzzles a single key and value, handing back the value if it wasn't consumed. This
+            /// is the same as `BytesGuzzle::guzzle_bytes` without the generic key.
+            pub fn guzzle_one(&mut self, key: &[u8], value: Vec<u8>) -> Option<Vec<u8>> {
+                _guzzle::BytesGuzzle::guzzle_bytes(self, (key, value)).map(|(_, value)| value)
+            }
+        }),
+    };
+
+    let try_guzzle_impl = if generate_try {
+        let strict_arm = container.strict_prefix.as_ref().map(|prefix| {
+            quote! {
+                _ if k.starts_with(#prefix) => {
+                    return Err(_guzzle::GuzzleError::UnknownKey(k.to_string()))
+                }
+            }
+        });
+        let group_arm = if grouped_keys.is_empty() {
+            None
+        } else {
+            let grouped_keys = grouped_keys
+                .iter()
+                .map(|key| try_context.pattern(&LitStr::new(key, proc_macro2::Span::call_site())));
+            Some(quote! {
+                // Groups are consumed as normal too
+                #( #grouped_keys )|* => return Ok(_guzzle::Guzzle::guzzle(self, (key, value))),
+            })
+        };
+        // Rather than stopping at the first unknown key, carry on so they can all be listed
+        let try_guzzle_all = container.deny_unknown_keys.as_ref().map(|_| {
+            quote! {
+                fn try_guzzle_all<__K, __I>(
+                    &mut self,
+                    iter: __I,
+                ) -> Result<Vec<(__K, String)>, Self::Error>
+                where
+                    __K: AsRef<str>,
+                    __I: IntoIterator<Item = (__K, String)>,
+                {
+                    let mut leftovers = vec![];
+                    let mut unknown_keys = vec![];
+                    for current in iter {
+                        match _guzzle::TryGuzzle::try_guzzle(self, current) {
+                            Ok(Some(leftover)) => leftovers.push(leftover),
+                            Ok(None) => {}
+                            Err(_guzzle::GuzzleError::UnknownKey(key)) => unknown_keys.push(key),
+                            Err(error) => return Err(error),
+                        }
+                    }
+                    if unknown_keys.is_empty() {
+                        Ok(leftovers)
+                    } else {
+                        Err(_guzzle::GuzzleError::UnknownKeys(unknown_keys))
+                    }
+                }
+            }
+        });
+        Some(quote! {
+            impl #impl_generics _guzzle::TryGuzzle for #name #ty_generics #where_clause {
+                type Error = _guzzle::GuzzleError;
+
+                fn try_guzzle<__K>(
+                    &mut self,
+                    (key, value): (__K, String),
+                ) -> Result<Option<(__K, String)>, Self::Error>
+                where __K: AsRef<str>
+                {
+                    #( #try_deep_forwards )*
+                    let k: &str = key.as_ref();
+                    match #scrutinee {
+                        #group_arm
+                        #( #try_arms )*
+                        #( #prefix_try_arms )*
+                        #strict_arm
+                        #try_fallthrough
+                    } #try_consumed
+                }
+
+                #try_guzzle_all
+            }
+        })
+    } else {
+        None
+    };
+
+    // Inherent items are only added when they're asked for, so they can't clash with the type's
+    // own
+    let schema_items = if container.schema {
+        Some(quote! {
+            /// Each key this type will consume directly and the field it is consumed into, for use
+            /// in const contexts. Like `guzzle_schema`, keys consumed by deep guzzle fields are not
+            /// included.
+            pub const SCHEMA: &'static [(&'static str, &'static str)] = &[ #( #schema_keys ),* ];
+
+            /// Each key this type will consume directly, along with the field it is consumed into
+            /// and that field's doc comment. Keys consumed by deep guzzle fields are not included.
+            pub fn guzzle_schema() -> &'static [(&'static str, &'static str, &'static str)] {
+                &[ #( #schema ),* ]
+            }
+        })
+    } else {
+        None
+    };
+    let inherent_impl = if container.inherent || container.schema {
+        Some(quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #guzzle_one
+
+                #schema_items
+            }
+        })
+    } else {
+        None
+    };
+
     let gen = quote! {
-        impl #impl_generics Guzzle for #name #ty_generics #where_clause {
-            fn guzzle<T>(&mut self, (key, value): (T, String)) -> Option<(T, String)>
-            where T: AsRef<str>
+        #guzzle_impl
+
+        #try_guzzle_impl
+
+        #inherent_impl
+    };
+    hygienic(container, gen)
+}
+
+/// The most rows a repeater will grow to, as an expression
+fn max_rows(deep_guzzle: &DeepGuzzle) -> proc_macro2::TokenStream {
+    match &deep_guzzle.max_rows {
+        Some(max_rows) => {
+            // Checked when the attribute was parsed, and unsuffixed so it's always a `usize`
+            let max_rows = max_rows.base10_parse::<usize>().unwrap_or_default();
+            let max_rows = proc_macro2::Literal::usize_unsuffixed(max_rows);
+            quote! { #max_rows }
+        }
+        None => quote! { _guzzle::DEFAULT_MAX_ROWS },
+    }
+}
+
+/// Generates the statement that offers the key and value to a deep guzzle field using
+/// `guzzle_fn`, returning `consumed` if it takes them and rebinding them otherwise. Fields with a
+/// prefix are only offered keys starting with it, and with it stripped off.
+pub(crate) fn generate_deep_forward(
+    context: &Context,
+    deep_guzzle: &DeepGuzzle,
+    guzzle_fn: proc_macro2::TokenStream,
+    consumed: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let field = context.field(&deep_guzzle.field);
+    if let (true, Some(prefix)) = (deep_guzzle.vec, &deep_guzzle.prefix) {
+        // Repeaters are offered the rest of the key by the row it names, growing to fit as long as
+        // that stays within `max_rows`. Keys naming rows past it are handed back.
+        let max_rows = max_rows(deep_guzzle);
+        return quote! {
+            let (key, value) = match _guzzle::split_indexed_key(key.as_ref(), #prefix) {
+                Some((index, rest)) if index.checked_add(1).map_or(false, |rows| rows <= #max_rows) => {
+                    let len = #field.len();
+                    if index >= len {
+                        #field.resize_with(index + 1, Default::default);
+                    }
+                    match #guzzle_fn(&mut #field[index], (rest, value)) {
+                        Some((_, value)) => {
+                            // Nothing was consumed, so don't leave new rows behind
+                            #field.truncate(len);
+                            (key, value)
+                        }
+                        None => return #consumed,
+                    }
+                }
+                _ => (key, value),
+            };
+        };
+    }
+    let offer = |pair: proc_macro2::TokenStream| {
+        if deep_guzzle.option {
+            quote! {
+                {
+                    let was_none = #field.is_none();
+                    let leftover = #guzzle_fn(
+                        #field.get_or_insert_with(Default::default),
+                        #pair,
+                    );
+                    // Nothing was consumed so there's still nothing there
+                    if was_none && leftover.is_some() {
+                        #field = None;
+                    }
+                    leftover
+                }
+            }
+        } else {
+            quote! { #guzzle_fn(&mut #field, #pair) }
+        }
+    };
+    match &deep_guzzle.prefix {
+        Some(prefix) => {
+            let prefix = literal(context.container, prefix);
+            let offer = offer(quote! { (stripped, value) });
+            quote! {
+                let (key, value) = match key.as_ref().strip_prefix(#prefix) {
+                    Some(stripped) => match #offer {
+                        Some((_, value)) => (key, value),
+                        None => return #consumed,
+                    },
+                    None => (key, value),
+                };
+            }
+        }
+        None => {
+            let offer = offer(quote! { (key, value) });
+            quote! {
+                let (key, value) = match #offer {
+                    Some(leftover) => leftover,
+                    None => return #consumed,
+                };
+            }
+        }
+    }
+}
+
+/// Generates a call to a `Guzzle` method on a deep guzzle field. Optional fields that are `None`
+/// evaluate to the method's default instead, while repeaters call it on every row and `combine`
+/// the results.
+fn generate_deep_call(
+    deep_guzzle: &DeepGuzzle,
+    call: proc_macro2::TokenStream,
+    mutable: bool,
+    combine: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let field = &deep_guzzle.field;
+    if deep_guzzle.vec {
+        let iter = if mutable {
+            quote! { iter_mut }
+        } else {
+            quote! { iter }
+        };
+        return quote! { self.#field.#iter().map(|inner| inner.#call).#combine };
+    }
+    match (deep_guzzle.option, mutable) {
+        (false, _) => quote! { self.#field.#call },
+        (true, false) => quote! {
+            self.#field.as_ref().map(|inner| inner.#call).unwrap_or_default()
+        },
+        (true, true) => quote! {
+            self.#field.as_mut().map(|inner| inner.#call).unwrap_or_default()
+        },
+    }
+}
+
+/// Generates the part of `may_consume` that asks a deep guzzle field's type about the key
+fn generate_deep_check(
+    deep_guzzle: &DeepGuzzle,
+    value_type: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let ty = &deep_guzzle.ty;
+    let check = quote! {
+        if <#ty as _guzzle::Guzzle<#value_type>>::may_consume(key) {
+            return true;
+        }
+    };
+    match &deep_guzzle.prefix {
+        Some(prefix) if deep_guzzle.vec => {
+            let max_rows = max_rows(deep_guzzle);
+            quote! {
+                if let Some((index, key)) = _guzzle::split_indexed_key(key, #prefix) {
+                    if index < #max_rows {
+                        #check
+                    }
+                }
+            }
+        }
+        Some(prefix) => quote! {
+            if let Some(key) = key.strip_prefix(#prefix) {
+                #check
+            }
+        },
+        None => check,
+    }
+}
+
+/// Generates the part of `explain_key` that asks a deep guzzle field about the key
+fn generate_deep_explanation(deep_guzzle: &DeepGuzzle) -> proc_macro2::TokenStream {
+    let field = &deep_guzzle.field;
+    let name = field.to_string();
+    // An empty optional field, or a row that doesn't exist yet, is explained by what its default
+    // would do with the key
+    let inner = if deep_guzzle.vec {
+        quote! {
             {
-                #(
-                    let result = self.#deep_guzzles.guzzle((key, value));
-                    if result.is_none() { return None };
-                    let (key, value) = result.unwrap();
-                )*
-                match key.as_ref() {
-                    #( #matchers => self.#keys = #parsers(value), )*
-                    _ => return Some((key, value)),
+                fn explain<G>(rows: &[G], index: usize, key: &str) -> _guzzle::KeyDisposition
+                where G: _guzzle::Guzzle + Default
+                {
+                    match rows.get(index) {
+                        Some(inner) => inner.explain_key(key),
+                        None => G::default().explain_key(key),
+                    }
+                }
+                explain(&self.#field, index, key)
+            }
+        }
+    } else if deep_guzzle.option {
+        quote! {
+            {
+                fn explain<G>(field: &Option<G>, key: &str) -> _guzzle::KeyDisposition
+                where G: _guzzle::Guzzle + Default
+                {
+                    match field {
+                        Some(inner) => inner.explain_key(key),
+                        None => G::default().explain_key(key),
+                    }
+                }
+                explain(&self.#field, key)
+            }
+        }
+    } else {
+        quote! { self.#field.explain_key(key) }
+    };
+    let explain = quote! {
+        match #inner {
+            _guzzle::KeyDisposition::Unknown
+            | _guzzle::KeyDisposition::Discarded(_) => {},
+            disposition => return _guzzle::KeyDisposition::DeepGuzzle(
+                #name,
+                Box::new(disposition),
+            ),
+        }
+    };
+    match &deep_guzzle.prefix {
+        Some(prefix) if deep_guzzle.vec => {
+            let max_rows = max_rows(deep_guzzle);
+            quote! {
+                if let Some((index, key)) = _guzzle::split_indexed_key(key, #prefix) {
+                    if index < #max_rows {
+                        #explain
+                    }
+                }
+            }
+        }
+        Some(prefix) => quote! {
+            if let Some(key) = key.strip_prefix(#prefix) {
+                #explain
+            }
+        },
+        None => explain,
+    }
+}
+
+/// Generates a single match arm that consumes the value into the field
+fn generate_arm(
+    context: &Context,
+    field: &Ident,
+    matcher: &LitStr,
+    keyed_attr: &GuzzleKeyedAttribute,
+) -> proc_macro2::TokenStream {
+    let container = context.container;
+    let (parser, parser_kind) = select_parser(container, keyed_attr, Some(matcher));
+    let matcher = context.pattern(matcher);
+    // Values that don't match the guard fall through to the remaining arms
+    let guard = keyed_attr.when_value.as_ref().map(|when_value| {
+        let when_value = literal(container, when_value);
+        quote! { if value == #when_value }
+    });
+    let body = generate_arm_body(context, field, keyed_attr, parser, parser_kind, false);
+
+    quote! {
+        #matcher #guard => { #body }
+    }
+}
+
+/// Generates a match arm that consumes the value into the field for any key starting with the
+/// prefix, handing the parser the rest of the key along with the value
+fn generate_prefix_arm(
+    context: &Context,
+    field: &Ident,
+    key_prefix: &LitStr,
+    keyed_attr: &GuzzleKeyedAttribute,
+) -> proc_macro2::TokenStream {
+    let container = context.container;
+    let (parser, parser_kind) = select_parser(container, keyed_attr, None);
+    let guard = keyed_attr.when_value.as_ref().map(|when_value| {
+        let when_value = literal(container, when_value);
+        quote! { && value == #when_value }
+    });
+    let body = generate_arm_body(context, field, keyed_attr, parser, parser_kind, true);
+
+    quote! {
+        _ if k.starts_with(#key_prefix) #guard => {
+            let key_part = k[#key_prefix.len()..].to_string();
+            #body
+        }
+    }
+}
+
+/// Generates a match arm that consumes the value into the field for any key the pattern matches,
+/// handing the parser the whole key along with the value
+fn generate_regex_arm(
+    context: &Context,
+    field: &Ident,
+    key_regex: &LitStr,
+    keyed_attr: &GuzzleKeyedAttribute,
+) -> proc_macro2::TokenStream {
+    let container = context.container;
+    let (parser, parser_kind) = select_parser(container, keyed_attr, None);
+    let guard = keyed_attr.when_value.as_ref().map(|when_value| {
+        let when_value = literal(container, when_value);
+        quote! { && value == #when_value }
+    });
+    let is_match = generate_regex_match(key_regex);
+    let body = generate_arm_body(context, field, keyed_attr, parser, parser_kind, true);
+
+    quote! {
+        _ if #is_match #guard => {
+            let key_part = k.to_string();
+            #body
+        }
+    }
+}
+
+/// Generates an expression that checks `k` against the pattern, compiling it the first time
+fn generate_regex_match(key_regex: &LitStr) -> proc_macro2::TokenStream {
+    quote! {
+        {
+            static PATTERN: ::std::sync::OnceLock<_guzzle::regex::Regex> =
+                ::std::sync::OnceLock::new();
+            PATTERN
+                .get_or_init(|| _guzzle::regex::Regex::new(#key_regex).unwrap())
+                .is_match(k)
+        }
+    }
+}
+
+/// Finds keys that more than one field claims, whose later arms could never be reached. A key can
+/// be shared when the earlier fields only take it `when_value` matches, as the value may still
+/// fall through to the next.
+fn find_unreachable_keys(
+    attributes: &[FieldAttribute],
+    grouped_keys: &[String],
+) -> Vec<syn::Error> {
+    let mut claimed: Vec<(String, &Ident)> = vec![];
+    let mut errors = vec![];
+    for field_attribute in attributes {
+        for (field, matcher, keyed_attr) in field_attribute.get_arm_parts() {
+            let key = matcher.value();
+            if grouped_keys.contains(&key) {
+                continue;
+            }
+            match claimed.iter().find(|(claimed_key, _)| *claimed_key == key) {
+                Some((_, claimed_by)) => errors.push(syn::Error::new(
+                    matcher.span(),
+                    format!(
+                        "key `{}` is already claimed by the field `{}`",
+                        key, claimed_by
+                    ),
+                )),
+                None if keyed_attr.when_value.is_none() => claimed.push((key, field)),
+                None => {}
+            }
+        }
+    }
+    errors
+}
+
+/// Generates the part of an arm that parses the value and consumes it into the field. When
+/// `with_key_part` is set, the parser is given the `key_part` bound by the arm along with the
+/// value.
+fn generate_arm_body(
+    context: &Context,
+    field: &Ident,
+    keyed_attr: &GuzzleKeyedAttribute,
+    parser: Option<proc_macro2::TokenStream>,
+    parser_kind: ParserKind,
+    with_key_part: bool,
+) -> proc_macro2::TokenStream {
+    let container = context.container;
+    let leftover = context.leftover();
+    // Transforms are resolved wherever the struct is, like parsers
+    let transforms = &keyed_attr.transforms;
+    let transform = if transforms.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            #( let value = (#transforms)(value); )*
+        })
+    };
+    // The field is left as it is and not marked as seen, so defaults still apply
+    let empty_is_missing = keyed_attr.empty_is_missing.is_some();
+    let skip_empty = if empty_is_missing || container.empty_is_missing.is_some() {
+        let consumed = context.consumed();
+        Some(quote! {
+            if value.is_empty() {
+                return #consumed;
+            }
+        })
+    } else {
+        None
+    };
+    let duplicate = generate_duplicate_check(context, field, keyed_attr);
+    // A parser with the key is given the whole key rather than the part a pattern matched
+    let with_key_part = with_key_part && keyed_attr.parser_with_key.is_none();
+    let input = |value: proc_macro2::TokenStream| {
+        if with_key_part {
+            quote! { (key_part.clone(), #value) }
+        } else {
+            value
+        }
+    };
+
+    // Opt and try parsers may turn the value down, so it needs to be kept around to hand back
+    let collection = match parser_kind {
+        ParserKind::Infallible => quote! { Vec<_> },
+        ParserKind::Opt => quote! { Option<Vec<_>> },
+        ParserKind::Try => quote! { Result<Vec<_>, _> },
+    };
+    let parse = match (&keyed_attr.split, parser_kind) {
+        (Some(split), _) => {
+            let input = input(quote! { value.to_string() });
+            quote! {
+                value
+                    .split(#split)
+                    .map(|value| #parser(#input))
+                    .collect::<#collection>()
+            }
+        }
+        (None, ParserKind::Infallible) => {
+            let input = input(quote! { value });
+            quote! { #parser(#input) }
+        }
+        (None, _) => {
+            let input = input(quote! { value.clone() });
+            quote! { #parser(#input) }
+        }
+    };
+
+    let parsed = match parser_kind {
+        ParserKind::Infallible => parse,
+        ParserKind::Opt => {
+            let rejected = if keyed_attr.drop_rejected.is_some() {
+                context.consumed()
+            } else {
+                leftover.clone()
+            };
+            quote! {
+                match #parse {
+                    Some(parsed) => parsed,
+                    None => return #rejected,
+                }
+            }
+        }
+        // Guzzle can't fail, so a failed parse is just a leftover there
+        ParserKind::Try if context.fallible => quote! {
+            match #parse {
+                Ok(parsed) => parsed,
+                Err(error) => return Err(_guzzle::GuzzleError::Parse {
+                    key: key.as_ref().to_string(),
+                    message: error.to_string(),
+                }),
+            }
+        },
+        ParserKind::Try => quote! {
+            match #parse {
+                Ok(parsed) => parsed,
+                Err(_) => return #leftover,
+            }
+        },
+    };
+
+    // Splitting a value only makes sense when collecting, so it implies `collect`
+    let collects = keyed_attr.collects(container.bytes || container.value.is_some());
+    let target = context.field(field);
+    let consume = match (&keyed_attr.split, collects, &keyed_attr.slot) {
+        (Some(_), _, _) if keyed_attr.setter.is_some() => {
+            let setter = &keyed_attr.setter;
+            quote! {
+                for parsed in parsed {
+                    self.#setter(parsed);
+                }
+            }
+        }
+        (None, _, _) if keyed_attr.setter.is_some() => {
+            let setter = &keyed_attr.setter;
+            quote! { self.#setter(parsed); }
+        }
+        (Some(_), _, _) => quote! { #target.extend(parsed); },
+        (None, true, _) if keyed_attr.set => quote! { #target.insert(parsed); },
+        (None, true, _) if keyed_attr.map => quote! { #target.extend(::core::iter::once(parsed)); },
+        (None, true, _) => quote! { #target.push(parsed); },
+        (None, false, Some(slot)) => quote! { #target[#slot] = Some(parsed); },
+        (None, false, None) if keyed_attr.concatenates() => {
+            generate_concatenate(&target, keyed_attr)
+        }
+        (None, false, None) if keyed_attr.option => quote! { #target = Some(parsed); },
+        (None, false, None) => quote! { #target = parsed; },
+    };
+    let mark_seen = generate_mark_seen(context, field);
+
+    quote! {
+        #transform
+        #skip_empty
+        #duplicate
+        let parsed = #parsed;
+        #consume
+        #mark_seen
+    }
+}
+
+/// Generates the check for whether the field already has a value, for fields that keep their
+/// first value or only allow one
+fn generate_duplicate_check(
+    context: &Context,
+    field: &Ident,
+    keyed_attr: &GuzzleKeyedAttribute,
+) -> Option<proc_macro2::TokenStream> {
+    let duplicates = keyed_attr
+        .duplicates
+        .as_ref()
+        .or(context.container.duplicates.as_ref());
+    let field_name = field.to_string();
+    match (duplicates.map(LitStr::value).as_deref(), context.state) {
+        (Some("first"), Some(state)) => {
+            let consumed = context.consumed();
+            Some(quote! {
+                if self.#state.has_seen(#field_name) {
+                    return #consumed;
+                }
+            })
+        }
+        (Some("error"), Some(state)) => {
+            let rejected = if context.fallible {
+                quote! { Err(_guzzle::GuzzleError::Duplicate(key.as_ref().to_string())) }
+            } else {
+                context.leftover()
+            };
+            Some(quote! {
+                if self.#state.has_seen(#field_name) {
+                    return #rejected;
+                }
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Generates the statements that append the parsed value onto a `String` field
+fn generate_concatenate(
+    target: &proc_macro2::TokenStream,
+    keyed_attr: &GuzzleKeyedAttribute,
+) -> proc_macro2::TokenStream {
+    match &keyed_attr.append_separator {
+        Some(separator) => quote! {
+            if !#target.is_empty() {
+                #target.push_str(#separator);
+            }
+            #target.push_str(&parsed);
+        },
+        None => quote! { #target.push_str(&parsed); },
+    }
+}
+
+/// Picks the parser for values under the key, or for the field if there's no key as with
+/// `default_if_absent`, along with how it reports failure. Fields that don't hold strings and
+/// have no parser of their own are parsed with `FromStr`, unless the values aren't strings.
+fn select_parser(
+    container: &ContainerAttribute,
+    keyed_attr: &GuzzleKeyedAttribute,
+    matcher: Option<&LitStr>,
+) -> (Option<proc_macro2::TokenStream>, ParserKind) {
+    match (
+        &keyed_attr.try_parser,
+        &keyed_attr.opt_parser,
+        infallible_parser(keyed_attr, matcher),
+    ) {
+        (Some(try_parser), _, _) => (Some(callable(try_parser)), ParserKind::Try),
+        (None, Some(opt_parser), _) => (Some(callable(opt_parser)), ParserKind::Opt),
+        (None, None, Some(parser)) => (Some(parser), ParserKind::Infallible),
+        // Keys matched by a pattern are parsed along with the key, so `FromStr` doesn't apply
+        (None, None, None)
+            if keyed_attr.from_str
+                && !container.bytes
+                && container.value.is_none()
+                && !keyed_attr.matches_pattern() =>
+        {
+            (Some(quote! { _guzzle::parsers::from_str }), ParserKind::Try)
+        }
+        (None, None, None) if container.borrowed.is_some() => borrowed_parser(keyed_attr),
+        (None, None, None) => (None, ParserKind::Infallible),
+    }
+}
+
+/// Borrowed values are converted to whatever the field holds: `&str` fields take them as they
+/// are, `Cow`s borrow them, `String`s copy them and anything else is parsed with `FromStr`
+fn borrowed_parser(
+    keyed_attr: &GuzzleKeyedAttribute,
+) -> (Option<proc_macro2::TokenStream>, ParserKind) {
+    let ty = match &keyed_attr.value_ty {
+        Some(ty) if !keyed_attr.matches_pattern() => ty,
+        _ => return (None, ParserKind::Infallible),
+    };
+    if let Type::Reference(_) = ty {
+        (None, ParserKind::Infallible)
+    } else if is_type(ty, &["Cow"]) {
+        (
+            Some(quote! { _guzzle::__private::Cow::Borrowed }),
+            ParserKind::Infallible,
+        )
+    } else if is_type(ty, &["String"]) {
+        (Some(quote! { String::from }), ParserKind::Infallible)
+    } else {
+        (
+            Some(quote! { _guzzle::parsers::from_str_ref }),
+            ParserKind::Try,
+        )
+    }
+}
+
+/// The `parser` for values under the key, preferring one paired with the key itself, or a
+/// `parser_with_key` wrapped up so it can be called with just the value. That needs the key as
+/// `k`, which every arm has.
+fn infallible_parser(
+    keyed_attr: &GuzzleKeyedAttribute,
+    matcher: Option<&LitStr>,
+) -> Option<proc_macro2::TokenStream> {
+    let parser = match matcher {
+        Some(matcher) => keyed_attr.parser_for(matcher),
+        None => keyed_attr.parser.as_ref(),
+    };
+    match (parser, &keyed_attr.parser_with_key) {
+        (Some(parser), _) => Some(callable(parser)),
+        (None, Some(parser_with_key)) => Some(quote! { (|value| (#parser_with_key)(k, value)) }),
+        (None, None) => None,
+    }
+}
+
+/// Wraps a parser so it can be called however it was written, such as a closure
+/// `|s: String| s.len()` that would otherwise swallow the arguments into its body. Paths are left
+/// alone so that a missing parser is still reported as a missing function.
+pub(crate) fn callable(parser: &Expr) -> proc_macro2::TokenStream {
+    match parser {
+        Expr::Path(_) => quote! { #parser },
+        _ => quote_spanned! { parser.span()=> (#parser) },
+    }
+}
+
+/// How a parser tells us it couldn't parse the value
+#[derive(Clone, Copy)]
+enum ParserKind {
+    /// `parser`, which can only panic
+    Infallible,
+    /// `opt_parser`, which returns an `Option`
+    Opt,
+    /// `try_parser`, which returns a `Result`
+    Try,
+}
+
+/// Generates an arm that buffers members of a group until they have all arrived, then consumes
+/// them together. Members that their field turns down are released to be left over by `finalize`.
+fn generate_group_arm(
+    context: &Context,
+    attributes: &[FieldAttribute],
+    group: &Ident,
+    keys: &Keys,
+) -> SynResult<proc_macro2::TokenStream> {
+    let state = context.state.ok_or_else(|| {
+        syn::Error::new(
+            group.span(),
+            "groups need a `guzzle::GuzzleState` field marked with #[guzzle(state)]",
+        )
+    })?;
+
+    let mut member_arms = vec![];
+    for key in keys.iter() {
+        let parts: Vec<_> = attributes
+            .iter()
+            .flat_map(FieldAttribute::get_arm_parts)
+            .filter(|(_, matcher, _)| matcher.value() == key.value())
+            .collect();
+        if parts.is_empty() {
+            return Err(syn::Error::new(
+                key.span(),
+                format!("No field is guzzled from `{}`", key.value()),
+            ));
+        }
+        for (field, matcher, keyed_attr) in parts {
+            member_arms.push(generate_arm(context, field, matcher, keyed_attr));
+        }
+    }
+
+    let group = group.to_string();
+    let all_keys = keys.iter();
+    let keys = keys.iter().map(|key| context.pattern(key));
+    let scrutinee = context.scrutinee();
+    Ok(quote! {
+        #( #keys )|* => {
+            if let Some(members) = self.#state.buffer_group(#group, &[ #( #all_keys ),* ], k, value) {
+                let apply = |(key, value): (String, String)| -> Option<(String, String)> {
+                    let k: &str = key.as_ref();
+                    match #scrutinee {
+                        #( #member_arms )*
+                        _ => return Some((key, value)),
+                    };
+                    None
                 };
-                None
+                let rejected: Vec<_> = members.into_iter().filter_map(apply).collect();
+                rejected.into_iter().for_each(|pair| self.#state.release(pair));
+            }
+        }
+    })
+}
+
+/// Records that the field has been guzzled, if there's somewhere to record it
+fn generate_mark_seen(context: &Context, field: &Ident) -> Option<proc_macro2::TokenStream> {
+    let field = field.to_string();
+    context
+        .state
+        .map(|state| quote! { self.#state.mark_seen(#field); })
+}
+
+/// Keeping the first value or rejecting the rest means knowing what's been guzzled already
+fn duplicates_without_state(duplicates: &LitStr) -> Option<syn::Error> {
+    if duplicates.value() == "last" {
+        None
+    } else {
+        Some(syn::Error::new(
+            duplicates.span(),
+            "duplicates needs a `guzzle::GuzzleState` field marked with #[guzzle(state)]",
+        ))
+    }
+}
+
+/// Generates the part of `finalize` that fills in a field whose keys never turned up
+fn generate_default(
+    context: &Context,
+    field: &Ident,
+    keyed_attr: &GuzzleKeyedAttribute,
+    default: &LitStr,
+) -> SynResult<proc_macro2::TokenStream> {
+    let state = context.state.ok_or_else(|| {
+        syn::Error::new(
+            default.span(),
+            "default_if_absent needs a `guzzle::GuzzleState` field marked with #[guzzle(state)]",
+        )
+    })?;
+    let field_name = field.to_string();
+    let parsed = if keyed_attr.option {
+        quote! { Some(parsed) }
+    } else {
+        quote! { parsed }
+    };
+    let set = match &keyed_attr.setter {
+        Some(setter) => quote! { self.#setter(parsed); },
+        None => quote! { self.#field = #parsed; },
+    };
+    let assign = match select_parser(context.container, keyed_attr, None) {
+        (parser, ParserKind::Try) => quote! {
+            if let Ok(parsed) = #parser(value) {
+                #set
             }
+        },
+        (parser, ParserKind::Opt) => quote! {
+            if let Some(parsed) = #parser(value) {
+                #set
+            }
+        },
+        (parser, ParserKind::Infallible) => quote! {
+            let parsed = #parser(value);
+            #set
+        },
+    };
+    // The default didn't arrive under any key, so a parser with the key sees the first one
+    let key = match keyed_attr.keys.first() {
+        Some(key) => quote! { #key },
+        None => quote! { #field_name },
+    };
+    Ok(quote! {
+        if !self.#state.has_seen(#field_name) {
+            #[allow(unused_variables)]
+            let k: &str = #key;
+            let value = String::from(#default);
+            #assign
         }
+    })
+}
+
+/// Generates the part of `finalize` that sets a field with a `default` expression whose keys
+/// never turned up. Unlike `default_if_absent` there's nothing to parse, so the value type can be
+/// anything.
+fn generate_default_expr(
+    context: &Context,
+    field: &Ident,
+    keyed_attr: &GuzzleKeyedAttribute,
+    default: &Expr,
+) -> SynResult<proc_macro2::TokenStream> {
+    let state = context.state.ok_or_else(|| {
+        syn::Error::new_spanned(
+            default,
+            "default needs a `guzzle::GuzzleState` field marked with #[guzzle(state)]",
+        )
+    })?;
+    let field_name = field.to_string();
+    let set = match &keyed_attr.setter {
+        Some(setter) => quote! { self.#setter(#default); },
+        None => quote! { self.#field = #default; },
     };
-    gen.into()
+    Ok(quote! {
+        if !self.#state.has_seen(#field_name) {
+            #set
+        }
+    })
+}
+
+/// In bytes mode keys and values are compared as byte strings rather than strings
+fn literal(container: &ContainerAttribute, lit: &LitStr) -> proc_macro2::TokenStream {
+    if container.bytes {
+        let lit = LitByteStr::new(lit.value().as_bytes(), lit.span());
+        quote! { #lit }
+    } else {
+        quote! { #lit }
+    }
+}
+
+/// Generates a `(key, field, doc)` tuple for the schema
+fn generate_schema_entry(field: &Ident, matcher: &LitStr, doc: &str) -> proc_macro2::TokenStream {
+    let field = field.to_string();
+    quote! { (#matcher, #field, #doc) }
 }
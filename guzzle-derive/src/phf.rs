@@ -0,0 +1,94 @@
+use quote::quote;
+
+/// A perfect hash of a struct's keys for `#[guzzle(matcher = "phf")]`, so finding the arm for a
+/// key is one hash and one string comparison however many keys there are. Each key hashes to its
+/// own slot in a table, and the seed is searched for at compile time.
+pub struct PerfectHash {
+    seed: u64,
+    /// The key in each slot, with empty strings in the slots no key hashes to
+    table: Vec<String>,
+}
+
+impl PerfectHash {
+    /// Looks for a seed that gives each key its own slot, trying bigger tables if that fails
+    pub fn new(keys: &[String]) -> Option<Self> {
+        let mut size = keys.len().next_power_of_two().max(1);
+        while size <= keys.len().max(1) * 16 {
+            for seed in 0..10_000 {
+                let mut table = vec![String::new(); size];
+                let mut taken = vec![false; size];
+                let fits = keys.iter().all(|key| {
+                    let slot = slot_for(seed, key, size);
+                    let free = !taken[slot];
+                    taken[slot] = true;
+                    table[slot] = key.clone();
+                    free
+                });
+                if fits {
+                    return Some(PerfectHash { seed, table });
+                }
+            }
+            size *= 2;
+        }
+        None
+    }
+
+    /// The slot the key is in, as matched by the arm for it
+    pub fn slot(&self, key: &str) -> usize {
+        slot_for(self.seed, key, self.table.len())
+    }
+
+    /// An expression for the slot of `k`, if `k` is one of the keys at all
+    pub fn lookup(&self) -> proc_macro2::TokenStream {
+        let seed = self.seed;
+        let size = self.table.len();
+        let modulus = size as u64;
+        let table = &self.table;
+        quote! {
+            {
+                const TABLE: [&str; #size] = [ #( #table ),* ];
+                let slot = (_guzzle::key_hash(#seed, k) % #modulus) as usize;
+                if TABLE[slot] == k {
+                    Some(slot)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn slot_for(seed: u64, key: &str, size: usize) -> usize {
+    (key_hash(seed, key) % size as u64) as usize
+}
+
+/// Seeded FNV-1a, which has to match `guzzle::key_hash` exactly
+fn key_hash(seed: u64, key: &str) -> u64 {
+    key.bytes()
+        .fold(0xcbf2_9ce4_8422_2325 ^ seed, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_has_its_own_slot() {
+        let keys: Vec<String> = (0..50).map(|index| format!("key_{}", index)).collect();
+        let perfect_hash = PerfectHash::new(&keys).unwrap();
+        let mut slots: Vec<_> = keys.iter().map(|key| perfect_hash.slot(key)).collect();
+        slots.sort_unstable();
+        slots.dedup();
+        assert_eq!(slots.len(), keys.len());
+        for key in &keys {
+            assert_eq!(&perfect_hash.table[perfect_hash.slot(key)], key);
+        }
+    }
+
+    #[test]
+    fn no_keys() {
+        assert!(PerfectHash::new(&[]).is_some());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::attr::{FieldAttribute, GuzzleKeyedAttribute};
+use crate::{
+    callable, generate_concatenate, generate_deep_forward, generate_duplicate_check,
+    generate_mark_seen, infallible_parser, Context,
+};
+use quote::quote;
+use syn::{DeriveInput, Ident, LitStr};
+
+/// Generates `TomlGuzzle` in place of `Guzzle` for `#[guzzle(toml)]`, which consumes
+/// `toml::Value`s directly rather than strings
+pub fn generate_toml_impl(
+    ast: &DeriveInput,
+    context: &Context,
+    attributes: &[FieldAttribute],
+) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let deep_guzzles: Vec<_> = attributes
+        .iter()
+        .filter_map(FieldAttribute::get_recursion)
+        .map(|deep_guzzle| {
+            generate_deep_forward(
+                context,
+                deep_guzzle,
+                quote! { _guzzle::TomlGuzzle::guzzle_toml },
+                quote! { None },
+            )
+        })
+        .collect();
+    let arms: Vec<_> = attributes
+        .iter()
+        .flat_map(FieldAttribute::get_arm_parts)
+        .map(|(field, matcher, keyed_attr)| generate_toml_arm(context, field, matcher, keyed_attr))
+        .collect();
+
+    let fallthrough = context.fallthrough();
+
+    quote! {
+        impl #impl_generics _guzzle::TomlGuzzle for #name #ty_generics #where_clause {
+            fn guzzle_toml<__K>(
+                &mut self,
+                (key, value): (__K, _guzzle::toml::Value),
+            ) -> Option<(__K, _guzzle::toml::Value)>
+            where __K: AsRef<str>
+            {
+                #( #deep_guzzles )*
+                let k: &str = key.as_ref();
+                match k {
+                    #( #arms )*
+                    #fallthrough
+                };
+                None
+            }
+        }
+    }
+}
+
+/// Generates a match arm that converts the value into the field, handing the pair back if the
+/// value is the wrong type
+fn generate_toml_arm(
+    context: &Context,
+    field: &Ident,
+    matcher: &LitStr,
+    keyed_attr: &GuzzleKeyedAttribute,
+) -> proc_macro2::TokenStream {
+    let guard = keyed_attr
+        .when_value
+        .as_ref()
+        .map(|when_value| quote! { if value.as_str() == Some(#when_value) });
+
+    let convert = match (
+        keyed_attr.toml_parser.as_ref().map(callable),
+        keyed_attr.try_parser.as_ref().map(callable),
+        keyed_attr.opt_parser.as_ref().map(callable),
+        infallible_parser(keyed_attr, Some(matcher)),
+    ) {
+        (Some(toml_parser), _, _, _) => quote! {
+            |value: &_guzzle::toml::Value| Some(#toml_parser(value.clone()))
+        },
+        (None, Some(try_parser), _, _) => quote! {
+            |value: &_guzzle::toml::Value| #try_parser(_guzzle::toml_to_string(value)).ok()
+        },
+        (None, None, Some(opt_parser), _) => quote! {
+            |value: &_guzzle::toml::Value| #opt_parser(_guzzle::toml_to_string(value))
+        },
+        (None, None, None, Some(parser)) => quote! {
+            |value: &_guzzle::toml::Value| Some(#parser(_guzzle::toml_to_string(value)))
+        },
+        (None, None, None, None) => quote! {
+            |value: &_guzzle::toml::Value| _guzzle::FromToml::from_toml(value)
+        },
+    };
+
+    // Collections take each item of an array, or a single value on its own. Split fields will
+    // also split a string as they would when guzzling strings.
+    let split = keyed_attr.split.as_ref().map(|split| {
+        quote! {
+            _guzzle::toml::Value::String(string) => string
+                .split(#split)
+                .map(|part| convert(&_guzzle::toml::Value::String(part.to_string())))
+                .collect::<Option<Vec<_>>>(),
+        }
+    });
+    // Only an `opt_parser` can reject a value that way, see `drop_rejected`
+    let rejected = if keyed_attr.drop_rejected.is_some() {
+        quote! { None }
+    } else {
+        quote! { Some((key, value)) }
+    };
+    let consume = if keyed_attr.collects(false) || keyed_attr.split.is_some() {
+        let method = if keyed_attr.set {
+            quote!(insert)
+        } else {
+            quote!(push)
+        };
+        let add = match &keyed_attr.setter {
+            Some(setter) => quote! { self.#setter(parsed); },
+            None => quote! { self.#field.#method(parsed); },
+        };
+        quote! {
+            let parsed = match &value {
+                _guzzle::toml::Value::Array(values) => {
+                    values.iter().map(convert).collect::<Option<Vec<_>>>()
+                }
+                #split
+                value => convert(value).map(|parsed| vec![parsed]),
+            };
+            match parsed {
+                Some(parsed) => for parsed in parsed {
+                    #add
+                },
+                None => return #rejected,
+            }
+        }
+    } else {
+        let assign = match (&keyed_attr.setter, &keyed_attr.slot) {
+            (Some(setter), _) => quote! { self.#setter(parsed); },
+            (None, Some(slot)) => quote! { self.#field[#slot] = Some(parsed); },
+            (None, None) if keyed_attr.concatenates() => {
+                generate_concatenate(&quote! { self.#field }, keyed_attr)
+            }
+            (None, None) if keyed_attr.option => quote! { self.#field = Some(parsed); },
+            (None, None) => quote! { self.#field = parsed; },
+        };
+        quote! {
+            match convert(&value) {
+                Some(parsed) => { #assign }
+                None => return #rejected,
+            }
+        }
+    };
+    let mark_seen = generate_mark_seen(context, field);
+    let duplicate = generate_duplicate_check(context, field, keyed_attr);
+
+    quote! {
+        #matcher #guard => {
+            #duplicate
+            let convert = #convert;
+            #consume
+            #mark_seen
+        }
+    }
+}
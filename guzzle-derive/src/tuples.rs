@@ -0,0 +1,178 @@
+use crate::container::ContainerAttribute;
+use crate::proc_macro::TokenStream;
+use crate::validate::validate_fields;
+use crate::{
+    apply_container_keys, fields_to_attributes, generate_arm, generate_deep_forward,
+    generate_prefix_arm, generate_regex_arm, handle_errors, hygienic, Context,
+};
+use quote::quote;
+use std::convert::TryFrom;
+use syn::{spanned::Spanned, DeriveInput, FieldsNamed, FieldsUnnamed, Ident};
+
+/// Generates `Guzzle` for a tuple struct. Its fields have no names to use as keys, so each one
+/// needs `#[guzzle(keys = [...])]`, `#[deep_guzzle]` or `#[no_guzzle]`, which makes a newtype
+/// either a single keyed value or a wrapper that delegates to the type inside it. Fields are
+/// bound by a pattern rather than reached through `self`, so like enum variants they can't hold
+/// state or catch the rest.
+pub fn generate_tuple_impl(ast: &DeriveInput, fields: &FieldsUnnamed) -> TokenStream {
+    let container = match ContainerAttribute::try_from(ast) {
+        Ok(container) => container,
+        Err(error) => return handle_errors(vec![error]),
+    };
+    let unsupported = container.bytes
+        || container.toml
+        || container.inherent
+        || container.schema
+        || container.tag.is_some()
+        || container.strict_prefix.is_some()
+        || container.deny_unknown_keys.is_some()
+        || container.duplicates.is_some()
+        || container.finish.is_some()
+        || !container.groups.is_empty();
+    if unsupported {
+        return handle_errors(vec![syn::Error::new(
+            ast.ident.span(),
+            "Tuple structs only support the prefix, value and never_panic container attributes",
+        )]);
+    }
+
+    // Each field is given a name to bind it to, which is also what errors point at
+    let named = FieldsNamed {
+        brace_token: Default::default(),
+        named: fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let mut field = field.clone();
+                field.ident = Some(Ident::new(&format!("field_{}", index), field.ty.span()));
+                field
+            })
+            .collect(),
+    };
+    let mut attributes = match fields_to_attributes(&named) {
+        Ok(attributes) => attributes,
+        Err(errors) => return handle_errors(errors),
+    };
+    apply_container_keys(&container, &mut attributes);
+
+    let context = Context {
+        container: &container,
+        state: None,
+        rest: None,
+        fallible: false,
+        in_variant: true,
+        perfect_hash: None,
+    };
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let mut errors = validate_fields(&container, &attributes);
+    let mut bindings = vec![];
+    let mut deep_forwards = vec![];
+    let mut arms = vec![];
+    for field_attribute in &attributes {
+        if let Some(field) = field_attribute
+            .get_state()
+            .or_else(|| field_attribute.get_rest())
+        {
+            errors.push(syn::Error::new(
+                field.span(),
+                "state and rest fields are not supported in tuple structs",
+            ));
+        }
+        for (field, keyed_attr) in field_attribute.get_keyed() {
+            if keyed_attr.named_after_field && !keyed_attr.matches_pattern() {
+                errors.push(syn::Error::new(
+                    field.span(),
+                    "Tuple struct fields have no name to use as a key, give them \
+                     #[guzzle(keys = [...])], #[deep_guzzle] or #[no_guzzle]",
+                ));
+            }
+            let defaults = keyed_attr.default_if_absent.is_some() || keyed_attr.default.is_some();
+            if defaults || keyed_attr.required.is_some() {
+                errors.push(syn::Error::new(
+                    field.span(),
+                    "default, default_if_absent and required are not supported in tuple structs",
+                ));
+            }
+            // The fields are borrowed from `self`, so its methods can't be called
+            if let Some(setter) = &keyed_attr.setter {
+                errors.push(syn::Error::new(
+                    setter.span(),
+                    "setter is not supported in tuple structs",
+                ));
+            }
+            if let Some(duplicates) = &keyed_attr.duplicates {
+                errors.push(syn::Error::new(
+                    duplicates.span(),
+                    "duplicates is not supported in tuple structs",
+                ));
+            }
+        }
+
+        let mut bound = false;
+        if let Some(deep_guzzle) = field_attribute.get_recursion() {
+            deep_forwards.push(generate_deep_forward(
+                &context,
+                deep_guzzle,
+                quote! { _guzzle::Guzzle::guzzle },
+                quote! { None },
+            ));
+            bound = true;
+        }
+        for (field, matcher, keyed_attr) in field_attribute.get_arm_parts() {
+            arms.push(generate_arm(&context, field, matcher, keyed_attr));
+            bound = true;
+        }
+        for (field, key_prefix, keyed_attr) in field_attribute.get_prefixed() {
+            arms.push(generate_prefix_arm(&context, field, key_prefix, keyed_attr));
+            bound = true;
+        }
+        for (field, key_regex, keyed_attr) in field_attribute.get_regexes() {
+            arms.push(generate_regex_arm(&context, field, key_regex, keyed_attr));
+            bound = true;
+        }
+        // Fields without anything to guzzle are still part of the pattern, just not bound
+        let field = field_attribute.get_field();
+        bindings.push(if bound {
+            quote! { #field }
+        } else {
+            quote! { _ }
+        });
+    }
+    if !errors.is_empty() {
+        return handle_errors(errors);
+    }
+
+    // A wrapper that only delegates has nothing left to match
+    let fields = if arms.is_empty() {
+        quote! { Some((key, value)) }
+    } else {
+        quote! {
+            let k: &str = key.as_ref();
+            match k {
+                #( #arms )*
+                _ => return Some((key, value)),
+            };
+            None
+        }
+    };
+
+    let value_type = container.value_type();
+    let gen = quote! {
+        impl #impl_generics _guzzle::Guzzle<#value_type> for #name #ty_generics #where_clause {
+            fn guzzle<__K>(
+                &mut self,
+                (key, value): (__K, #value_type),
+            ) -> Option<(__K, #value_type)>
+            where __K: AsRef<str>
+            {
+                let #name( #( #bindings ),* ) = self;
+                #( #deep_forwards )*
+                #fields
+            }
+        }
+    };
+    hygienic(&container, gen)
+}
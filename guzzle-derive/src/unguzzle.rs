@@ -0,0 +1,156 @@
+use crate::attr::{FieldAttribute, GuzzleKeyedAttribute};
+use crate::container::ContainerAttribute;
+use crate::proc_macro::TokenStream;
+use crate::{hygienic, infer_bounds};
+use quote::quote;
+use syn::DeriveInput;
+
+/// Generates `Unguzzle`, which turns the struct back into keys and values. Each field is output
+/// under its first key, using its `unparser` if it has one and `Display` otherwise, and deep
+/// guzzle fields output their own keys and values. Fields marked `skip_unguzzle` are guzzled but
+/// never output.
+pub fn attributes_to_generated_code(
+    ast: &DeriveInput,
+    container: &ContainerAttribute,
+    attributes: Vec<FieldAttribute>,
+) -> TokenStream {
+    let generics = infer_bounds(ast, container, &attributes, true);
+    let ast = &DeriveInput {
+        generics,
+        ..ast.clone()
+    };
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let mut pairs = vec![];
+
+    for field_attribute in &attributes {
+        if let Some(deep_guzzle) = field_attribute.get_recursion() {
+            let field = &deep_guzzle.field;
+            // Optional fields that were never filled in have nothing to output
+            let inner = if deep_guzzle.option {
+                quote! { self.#field.iter().flat_map(_guzzle::Unguzzle::to_metadata) }
+            } else {
+                quote! { _guzzle::Unguzzle::to_metadata(&self.#field) }
+            };
+            match &deep_guzzle.prefix {
+                // Each row's keys get their index back as well as the prefix
+                Some(prefix) if deep_guzzle.vec => pairs.push(quote! {
+                    for (index, row) in self.#field.iter().enumerate() {
+                        pairs.extend(
+                            _guzzle::Unguzzle::to_metadata(row)
+                                .into_iter()
+                                .map(|(key, value)| {
+                                    (format!("{}{}_{}", #prefix, index, key), value)
+                                }),
+                        );
+                    }
+                }),
+                // The prefix was stripped on the way in, so it goes back on the way out
+                Some(prefix) => pairs.push(quote! {
+                    pairs.extend(
+                        #inner
+                            .into_iter()
+                            .map(|(key, value)| (format!("{}{}", #prefix, key), value)),
+                    );
+                }),
+                None => pairs.push(quote! {
+                    pairs.extend(#inner);
+                }),
+            }
+        }
+        if let Some(field) = field_attribute.get_rest() {
+            pairs.push(quote! {
+                pairs.extend(self.#field.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+            });
+        }
+        for (field, key_prefix, keyed_attr) in field_attribute.get_prefixed() {
+            if keyed_attr.skip_unguzzle {
+                continue;
+            }
+            // Prefixed fields hold each suffix along with its value, so the keys can be rebuilt
+            let unparse = unparser(keyed_attr);
+            pairs.push(quote! {
+                for (suffix, value) in self.#field.iter() {
+                    pairs.push((format!("{}{}", #key_prefix, suffix), #unparse(value)));
+                }
+            });
+        }
+        for (field, _, keyed_attr) in field_attribute.get_regexes() {
+            if keyed_attr.skip_unguzzle {
+                continue;
+            }
+            // While fields matched with a regex hold each whole key
+            let unparse = unparser(keyed_attr);
+            pairs.push(quote! {
+                for (key, value) in self.#field.iter() {
+                    pairs.push((key.to_string(), #unparse(value)));
+                }
+            });
+        }
+        for (field, keyed_attr) in field_attribute.get_keyed() {
+            if keyed_attr.skip_unguzzle {
+                continue;
+            }
+            // Fields that only match keys by a pattern were output above
+            let key = match keyed_attr.keys.first() {
+                Some(key) => key,
+                None => continue,
+            };
+            let unparse = unparser(keyed_attr);
+            if let Some(slot) = &keyed_attr.slot {
+                pairs.push(quote! {
+                    if let Some(value) = &self.#field[#slot] {
+                        pairs.push((#key.to_string(), #unparse(value)));
+                    }
+                });
+            } else if let Some(split) = &keyed_attr.split {
+                // Split collections become a single value, joined with the same delimiter they
+                // are split on so they survive the round trip
+                let delimiter = split.value();
+                pairs.push(quote! {
+                    if !self.#field.is_empty() {
+                        let values: Vec<String> = self.#field.iter().map(#unparse).collect();
+                        pairs.push((#key.to_string(), values.join(#delimiter)));
+                    }
+                });
+            } else if keyed_attr.collects(false) {
+                // While other collections were built up from a key each, so they get one back
+                pairs.push(quote! {
+                    for value in self.#field.iter() {
+                        pairs.push((#key.to_string(), #unparse(value)));
+                    }
+                });
+            } else if keyed_attr.option {
+                pairs.push(quote! {
+                    if let Some(value) = &self.#field {
+                        pairs.push((#key.to_string(), #unparse(value)));
+                    }
+                });
+            } else {
+                pairs.push(quote! {
+                    pairs.push((#key.to_string(), #unparse(&self.#field)));
+                });
+            }
+        }
+    }
+
+    let gen = quote! {
+        impl #impl_generics _guzzle::Unguzzle for #name #ty_generics #where_clause {
+            fn to_metadata(&self) -> Vec<(String, String)> {
+                let mut pairs = vec![];
+                #( #pairs )*
+                pairs
+            }
+        }
+    };
+    hygienic(container, gen)
+}
+
+/// What turns a reference to each value back into a string, its `unparser` or `Display`
+fn unparser(keyed_attr: &GuzzleKeyedAttribute) -> proc_macro2::TokenStream {
+    match &keyed_attr.unparser {
+        Some(unparser) => quote! { (#unparser) },
+        None => quote! { ToString::to_string },
+    }
+}
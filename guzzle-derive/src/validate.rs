@@ -0,0 +1,434 @@
+use crate::attr::FieldAttribute;
+use crate::container::ContainerAttribute;
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::Ident;
+
+/// What's being guzzled when it isn't strings, which a lot of attributes depend on
+#[derive(Clone, Copy, PartialEq)]
+enum Values {
+    Bytes,
+    Toml,
+    /// Whatever `#[guzzle(value = ...)]` says
+    Other,
+}
+
+const ANY: &[Values] = &[Values::Bytes, Values::Toml, Values::Other];
+
+/// The errors found so far, along with what the struct is guzzling
+struct Validation<'a> {
+    container: &'a ContainerAttribute,
+    values: Option<Values>,
+    errors: Vec<syn::Error>,
+}
+
+impl<'a> Validation<'a> {
+    fn new(container: &'a ContainerAttribute) -> Self {
+        let values = if container.bytes {
+            Some(Values::Bytes)
+        } else if container.toml {
+            Some(Values::Toml)
+        } else if container.value.is_some() {
+            Some(Values::Other)
+        } else {
+            None
+        };
+        Validation {
+            container,
+            values,
+            errors: vec![],
+        }
+    }
+
+    /// The attribute that chose what's being guzzled, as it would have been written
+    fn values_attribute(&self, values: Values) -> &'static str {
+        match values {
+            Values::Bytes => "#[guzzle(bytes)]",
+            Values::Toml => "#[guzzle(toml)]",
+            // Borrowing is how the value type was chosen, even though it's also set
+            Values::Other if self.container.borrowed.is_some() => "#[guzzle(borrowed)]",
+            Values::Other => "#[guzzle(value = ...)]",
+        }
+    }
+
+    fn error(&mut self, tokens: impl ToTokens, message: &str) {
+        self.errors.push(syn::Error::new_spanned(tokens, message));
+    }
+
+    /// Reports `attribute` if the struct is guzzling any of the `unsupported` kinds of values
+    fn supported(&mut self, tokens: impl ToTokens, attribute: &str, unsupported: &[Values]) {
+        if let Some(values) = self.values.filter(|values| unsupported.contains(values)) {
+            let message = format!(
+                "{} is not supported with {}",
+                attribute,
+                self.values_attribute(values)
+            );
+            self.error(tokens, &message);
+        }
+    }
+
+    fn container(&mut self, ident: &Ident, attributes: &[FieldAttribute]) {
+        let container = self.container;
+        if container.bytes && container.toml {
+            self.error(ident, "#[guzzle(toml)] can't be used with #[guzzle(bytes)]");
+        }
+        if let (Some(value), true) = (&container.value, container.bytes || container.toml) {
+            let message = format!(
+                "{} can't be used with bytes or toml",
+                self.values_attribute(Values::Other)
+            );
+            self.error(value, &message);
+        }
+        if let Some(matcher) = &container.perfect_hash {
+            self.supported(matcher, "matcher = \"phf\"", &[Values::Bytes, Values::Toml]);
+        }
+        if let Some(empty_is_missing) = &container.empty_is_missing {
+            self.supported(
+                empty_is_missing,
+                "empty_is_missing",
+                &[Values::Toml, Values::Other],
+            );
+        }
+        if let Some(prefix) = &container.strict_prefix {
+            self.supported(prefix, "strict_prefix", ANY);
+        }
+        if let Some(deny_unknown_keys) = &container.deny_unknown_keys {
+            self.supported(deny_unknown_keys, "deny_unknown_keys", ANY);
+            if attributes
+                .iter()
+                .any(|attribute| attribute.get_rest().is_some())
+            {
+                self.error(
+                    deny_unknown_keys,
+                    "deny_unknown_keys can't be used with a #[guzzle(rest)] field, which \
+                     consumes every key",
+                );
+            }
+        }
+        for (group, _) in &container.groups {
+            self.supported(group, "groups", ANY);
+        }
+        if let Some(finish) = &container.finish {
+            self.supported(finish, "finish", &[Values::Bytes, Values::Toml]);
+        }
+        if container.inherent {
+            self.supported(ident, "#[guzzle(inherent)]", &[Values::Toml]);
+        }
+        for rest in attributes
+            .iter()
+            .filter_map(FieldAttribute::get_rest)
+            .skip(1)
+        {
+            self.error(rest, "Only one field can be marked with #[guzzle(rest)]");
+        }
+    }
+
+    fn fields(&mut self, attributes: &[FieldAttribute]) {
+        for deep_guzzle in attributes.iter().filter_map(FieldAttribute::get_recursion) {
+            if deep_guzzle.vec {
+                self.supported(
+                    &deep_guzzle.field,
+                    "#[deep_guzzle] into a Vec",
+                    &[Values::Bytes],
+                );
+            } else if let Some(max_rows) = &deep_guzzle.max_rows {
+                self.error(
+                    max_rows,
+                    "max_rows can only be used on a #[deep_guzzle] Vec",
+                );
+            }
+        }
+        for (_, keyed_attr) in attributes.iter().flat_map(FieldAttribute::get_keyed) {
+            if let Some(split) = &keyed_attr.split {
+                self.supported(split, "split", &[Values::Bytes, Values::Other]);
+            }
+            if let Some(empty_is_missing) = &keyed_attr.empty_is_missing {
+                let unsupported = &[Values::Toml, Values::Other];
+                self.supported(empty_is_missing, "empty_is_missing", unsupported);
+            }
+            if let Some(transform) = keyed_attr.transforms.first() {
+                self.supported(transform, "map", ANY);
+            }
+            if let Some(default) = &keyed_attr.default_if_absent {
+                self.supported(default, "default_if_absent", ANY);
+            }
+            if let Some(default) = &keyed_attr.default {
+                self.supported(default, "default", &[Values::Bytes, Values::Toml]);
+                if keyed_attr.default_if_absent.is_some() {
+                    self.error(
+                        default,
+                        "default and default_if_absent can't both be used on the same field",
+                    );
+                }
+            }
+            if let Some(key_prefix) = &keyed_attr.key_prefix {
+                self.supported(key_prefix, "key_prefix", &[Values::Bytes, Values::Toml]);
+            }
+            if let Some(key_regex) = &keyed_attr.key_regex {
+                self.supported(key_regex, "key_regex", &[Values::Bytes, Values::Toml]);
+                // Catch bad patterns now rather than the first time a key is guzzled
+                if let Err(error) = regex::Regex::new(&key_regex.value()) {
+                    self.error(key_regex, &error.to_string());
+                }
+            }
+            // Only one parser would be used, so having more than one is a mistake
+            let parsers = [
+                ("parser", &keyed_attr.parser),
+                ("parser_with_key", &keyed_attr.parser_with_key),
+                ("opt_parser", &keyed_attr.opt_parser),
+                ("try_parser", &keyed_attr.try_parser),
+            ];
+            for (index, (name, parser)) in parsers.iter().enumerate() {
+                let parser = match parser {
+                    Some(parser) => parser,
+                    None => continue,
+                };
+                match parsers[..index].iter().find(|(_, other)| other.is_some()) {
+                    Some((other, _)) => {
+                        let message = format!(
+                            "{} and {} can't both be used on the same field",
+                            other, name
+                        );
+                        self.error(parser, &message);
+                    }
+                    None if *name == "parser_with_key" => {
+                        self.supported(parser, "parser_with_key", &[Values::Bytes]);
+                    }
+                    None => {}
+                }
+            }
+            // Fallible parsers are chosen ahead of key_parsers, which would never be used
+            if !keyed_attr.key_parsers.is_empty() {
+                let fallible = [
+                    ("opt_parser", &keyed_attr.opt_parser),
+                    ("try_parser", &keyed_attr.try_parser),
+                ];
+                for (name, parser) in fallible.iter() {
+                    if let Some(parser) = parser {
+                        let message = format!(
+                            "key_parsers and {} can't both be used on the same field",
+                            name
+                        );
+                        self.error(parser, &message);
+                    }
+                }
+            }
+            if let (Some(drop_rejected), true) = (
+                &keyed_attr.drop_rejected,
+                keyed_attr.opt_parser.is_none() || keyed_attr.try_parser.is_some(),
+            ) {
+                self.error(
+                    drop_rejected,
+                    "drop_rejected needs an opt_parser to reject values",
+                );
+            }
+            if let Some(append) = &keyed_attr.append {
+                let collection = keyed_attr.vec || keyed_attr.set || keyed_attr.map;
+                if !keyed_attr.string && !collection {
+                    self.error(append, "append needs a String or a collection field");
+                }
+                if let (Some(separator), false) = (&keyed_attr.append_separator, keyed_attr.string)
+                {
+                    self.error(
+                        separator,
+                        "append can only have a separator on a String field",
+                    );
+                }
+            }
+        }
+        self.never_panic(attributes);
+    }
+
+    /// Under `never_panic` every value has to be parsed in a way that can turn it down, as a
+    /// `parser` can only panic on a value it doesn't understand. Transforms, setters, defaults and
+    /// `finish` are left alone, as there's nothing fallible to ask for in their place.
+    fn never_panic(&mut self, attributes: &[FieldAttribute]) {
+        if !self.container.never_panic {
+            return;
+        }
+        for (_, keyed_attr) in attributes.iter().flat_map(FieldAttribute::get_keyed) {
+            // A toml_parser is used ahead of the others, and can't fail either
+            let toml_parser = keyed_attr
+                .toml_parser
+                .as_ref()
+                .filter(|_| self.container.toml);
+            let fallible = keyed_attr.try_parser.is_some() || keyed_attr.opt_parser.is_some();
+            let infallible = keyed_attr
+                .parser
+                .iter()
+                .chain(keyed_attr.key_parsers.iter().map(|(_, parser)| parser))
+                .chain(keyed_attr.parser_with_key.iter())
+                .filter(|_| !fallible)
+                .chain(toml_parser);
+            for parser in infallible {
+                self.errors.push(syn::Error::new(
+                    parser.span(),
+                    "#[guzzle(never_panic)] needs parsers that can fail, use opt_parser or \
+                     try_parser",
+                ));
+            }
+        }
+    }
+}
+
+/// Checks that a struct's attributes make sense together before any code is generated for it
+pub fn validate_struct(
+    container: &ContainerAttribute,
+    ident: &Ident,
+    attributes: &[FieldAttribute],
+) -> Vec<syn::Error> {
+    let mut validation = Validation::new(container);
+    validation.container(ident, attributes);
+    validation.fields(attributes);
+    validation.errors
+}
+
+/// Checks the fields of a tuple struct or an enum variant, whose containers check themselves
+pub fn validate_fields(
+    container: &ContainerAttribute,
+    attributes: &[FieldAttribute],
+) -> Vec<syn::Error> {
+    let mut validation = Validation::new(container);
+    validation.fields(attributes);
+    validation.errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fields_to_attributes;
+    use quote::quote;
+    use std::convert::TryFrom;
+    use syn::{parse2, Data, DeriveInput};
+
+    fn messages(input: proc_macro2::TokenStream) -> Result<Vec<String>, syn::Error> {
+        let input: DeriveInput = parse2(input)?;
+        let container = ContainerAttribute::try_from(&input)?;
+        let fields = match &input.data {
+            Data::Struct(data) => match &data.fields {
+                syn::Fields::Named(fields) => fields.clone(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        let attributes = fields_to_attributes(&fields).map_err(|mut errors| errors.remove(0))?;
+        Ok(validate_struct(&container, &input.ident, &attributes)
+            .iter()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    #[test]
+    fn unsupported_attributes_name_the_mode() -> Result<(), syn::Error> {
+        let messages = messages(quote! {
+            #[guzzle(bytes, strict_prefix = "wp_")]
+            struct Example {
+                #[guzzle(split = ",", default_if_absent = "a")]
+                tags: Vec<u8>,
+            }
+        })?;
+        assert_eq!(
+            messages,
+            vec![
+                "strict_prefix is not supported with #[guzzle(bytes)]",
+                "split is not supported with #[guzzle(bytes)]",
+                "default_if_absent is not supported with #[guzzle(bytes)]",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn borrowed_structs_are_named_as_borrowed() -> Result<(), syn::Error> {
+        let messages = messages(quote! {
+            #[guzzle(borrowed)]
+            struct Example<'a> {
+                #[guzzle(default_if_absent = "none")]
+                name: &'a str,
+            }
+        })?;
+        assert_eq!(
+            messages,
+            vec!["default_if_absent is not supported with #[guzzle(borrowed)]"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn only_one_parser_per_field() -> Result<(), syn::Error> {
+        let messages = messages(quote! {
+            struct Example {
+                #[guzzle(parser = a, opt_parser = b)]
+                one: u64,
+                #[guzzle(parser = a, try_parser = b)]
+                two: u64,
+                #[guzzle(opt_parser = a, try_parser = b)]
+                three: u64,
+                #[guzzle(parser_with_key = a, opt_parser = b)]
+                four: u64,
+            }
+        })?;
+        assert_eq!(
+            messages,
+            vec![
+                "parser and opt_parser can't both be used on the same field",
+                "parser and try_parser can't both be used on the same field",
+                "opt_parser and try_parser can't both be used on the same field",
+                "parser_with_key and opt_parser can't both be used on the same field",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn key_parsers_need_infallible_parsers() -> Result<(), syn::Error> {
+        let messages = messages(quote! {
+            struct Example {
+                #[guzzle(key_parsers = [("meters", m_parser)], opt_parser = a)]
+                one: f64,
+                #[guzzle(key_parsers = [("meters", m_parser)], try_parser = a)]
+                two: f64,
+                #[guzzle(key_parsers = [("meters", m_parser)], parser = a)]
+                three: f64,
+            }
+        })?;
+        assert_eq!(
+            messages,
+            vec![
+                "key_parsers and opt_parser can't both be used on the same field",
+                "key_parsers and try_parser can't both be used on the same field",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn max_rows_needs_a_repeater() -> Result<(), syn::Error> {
+        let messages = messages(quote! {
+            struct Example {
+                #[deep_guzzle(prefix = "loc_", max_rows = 10)]
+                location: Location,
+                #[deep_guzzle(max_rows = 10)]
+                rows: Vec<Row>,
+            }
+        })?;
+        assert_eq!(
+            messages,
+            vec!["max_rows can only be used on a #[deep_guzzle] Vec"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn attributes_are_only_checked_against_their_own_modes() -> Result<(), syn::Error> {
+        let messages = messages(quote! {
+            #[guzzle(toml)]
+            struct Example {
+                #[guzzle(split = ",")]
+                tags: Vec<String>,
+            }
+        })?;
+        assert!(messages.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+//! A serde `Deserializer` over keys and values, for types that derive `Deserialize` rather than
+//! `Guzzle`.
+//!
+//! Each key is a field, and each value is parsed into whatever type that field asks for. Numbers
+//! and `bool`s are parsed with `FromStr`, sequences are split on `,` in the same way `Unguzzle`
+//! joins them, and unit enum variants are matched by name. Keys the type doesn't know about are
+//! ignored, unless it is `#[serde(deny_unknown_fields)]`.
+//!
+//! ```rust
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Post {
+//!     title: String,
+//!     views: u64,
+//!     tags: Vec<String>,
+//!     subtitle: Option<String>,
+//! }
+//!
+//! let post: Post = guzzle::de::from_metadata(vec![
+//!     ("title".to_string(), "Hello".to_string()),
+//!     ("views".to_string(), "20".to_string()),
+//!     ("tags".to_string(), "rust,serde".to_string()),
+//!     ("_edit_lock".to_string(), "1573492331:1".to_string()),
+//! ])
+//! .unwrap();
+//! assert_eq!(post.title, "Hello".to_string());
+//! assert_eq!(post.views, 20);
+//! assert_eq!(post.tags, vec!["rust".to_string(), "serde".to_string()]);
+//! assert_eq!(post.subtitle, None);
+//! ```
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Unexpected, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt;
+
+/// Deserializes a `T` from keys and values
+pub fn from_metadata<T, I>(pairs: I) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+    I: IntoIterator<Item = (String, String)>,
+{
+    T::deserialize(MetadataDeserializer::new(pairs))
+}
+
+/// Why the keys and values couldn't be deserialized, such as a value that couldn't be parsed
+/// into its field's type or a required field that had no key, or why a value couldn't be
+/// serialized into them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error(message.to_string())
+    }
+}
+
+/// Deserializes a map or struct from an iterator of keys and values
+pub struct MetadataDeserializer<I> {
+    pairs: I,
+}
+
+impl<I> MetadataDeserializer<I>
+where
+    I: Iterator<Item = (String, String)>,
+{
+    pub fn new<P>(pairs: P) -> Self
+    where
+        P: IntoIterator<IntoIter = I, Item = (String, String)>,
+    {
+        MetadataDeserializer {
+            pairs: pairs.into_iter(),
+        }
+    }
+}
+
+impl<'de, I> de::Deserializer<'de> for MetadataDeserializer<I>
+where
+    I: Iterator<Item = (String, String)>,
+{
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut map = MapDeserializer::new(
+            self.pairs
+                .map(|(key, value)| (key, ValueDeserializer(value))),
+        );
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// Deserializes a single value, parsing it into whatever type is asked for
+struct ValueDeserializer(String);
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&self.0), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    // A key being there at all means there is a value
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let values: Vec<_> = if self.0.is_empty() {
+            vec![]
+        } else {
+            self.0
+                .split(',')
+                .map(|value| ValueDeserializer(value.to_string()))
+                .collect()
+        };
+        let mut seq = SeqDeserializer::new(values.into_iter());
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(self.0))
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
@@ -36,8 +36,8 @@
 //!     #[guzzle(keys = ["one", "two"])]
 //!     listed_keys: String,
 //!
-//!     /// This field is not a string, you must provider a parser that will transform it into
-//!     /// the correct type
+//!     /// This field is not a string, so it is parsed with `FromStr` unless you provide a parser
+//!     /// that will transform it into the correct type
 //!     #[guzzle(parser = u64_parser)]
 //!     other_types: u64,
 //!
@@ -85,10 +85,7 @@
 //! let mut guzzle_example = GuzzleExample::default();
 //!
 //! // Feed our keys and values to our object, capturing any that weren't consumed
-//! let remaining_data: Vec<(&str, String)> = example_data
-//!     .into_iter()
-//!     .filter_map(|v| guzzle_example.guzzle(v))
-//!     .collect();
+//! let remaining_data = guzzle_example.guzzle_all(example_data);
 //!
 //! // All appropriate fields are now set
 //! assert_eq!(guzzle_example.basic, "basic info".to_string());
@@ -105,6 +102,55 @@
 //! assert_eq!(remaining_data, vec![("ignored", "ignored data".to_string())]);
 //! ```
 //!
+//! Never Panic
+//! -----------
+//!
+//! Parsers are free to panic on data they don't understand, which is fine for trusted data but
+//! not so good for production ingestion. Annotating the struct with `#[guzzle(never_panic)]`
+//! makes sure every value goes through a parser that can turn it down: fields parsed with
+//! `FromStr` already do, and a `parser` is refused at compile time in favour of an `opt_parser`
+//! or `try_parser`. A value that's turned down is handed back as if it hadn't been consumed.
+//! Fields that are guzzled deeply must opt in to this themselves. The code guzzle generates
+//! doesn't panic either, so a repeater's keys can't ask for more rows than its `max_rows`.
+//! Only parsing is checked: `map` transforms, `setter` methods, `default` expressions and `finish`
+//! methods are your own code, and guzzle trusts them not to panic.
+//!
+//! ```rust
+//! use guzzle::Guzzle;
+//!
+//! #[derive(Default, Guzzle)]
+//! #[guzzle(never_panic)]
+//! struct NeverPanics {
+//!     number: u64,
+//!     #[guzzle(opt_parser = guzzle::parsers::lenient_u64)]
+//!     lenient: u64,
+//! }
+//!
+//! let mut never_panics = NeverPanics::default();
+//! assert_eq!(
+//!     never_panics.guzzle(("number", "twenty".to_string())),
+//!     Some(("number", "twenty".to_string()))
+//! );
+//! assert_eq!(
+//!     never_panics.guzzle(("lenient", "twenty".to_string())),
+//!     Some(("lenient", "twenty".to_string()))
+//! );
+//! assert_eq!(never_panics.number, 0);
+//! ```
+//!
+//! No Std
+//! ------
+//!
+//! Guzzle only needs `alloc`, so turning off the default `std` feature makes it `no_std` and the
+//! derived code works the same way as it does with it. What is lost are the parts that need `std`
+//! itself: `Router`, `guzzle_dynamic`, the `HashMap` and `Arc<Mutex<_>>`
+//! implementations, and the optional features, all of which turn `std` back on.
+//!
+//! ```toml
+//! [dependencies]
+//! guzzle = { version = "1", default-features = false }
+//! ```
+//!
 //! Example Use Case
 //! ----------------
 //!
@@ -112,223 +158,5939 @@
 //! structs.
 //!
 
-pub use guzzle_derive::*;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub use crate::router::Router;
+#[cfg(feature = "async")]
+pub use crate::stream::{AsyncGuzzleExt, GuzzleStream, GuzzleTryStream};
+#[cfg(feature = "toml")]
+pub use crate::toml_guzzle::{toml_to_string, FromToml, TomlGuzzle};
+pub use guzzle_derive::*;
+// So generated code can name `toml::Value` without the user depending on toml themselves
+#[cfg(feature = "toml")]
+#[doc(hidden)]
+pub use toml;
+// Likewise for the patterns generated for `key_regex`
+#[cfg(feature = "regex")]
+#[doc(hidden)]
+pub use regex;
+
+use crate::__private::*;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    error::Error,
+    hash::BuildHasher,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+extern crate alloc;
+// Generated code refers to `::guzzle`, this lets that work inside this crate too
+extern crate self as guzzle;
+
+/// What generated code needs from `alloc`, which isn't in the prelude without `std`
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::borrow::{Cow, ToOwned};
+    pub use alloc::boxed::Box;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec::Vec;
+    pub use alloc::{format, vec};
+}
+
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "mysql")]
+#[path = "mysql_guzzle.rs"]
+pub mod mysql;
+#[cfg(feature = "mysql_async")]
+#[path = "mysql_async_guzzle.rs"]
+pub mod mysql_async;
+pub mod parsers;
+#[cfg(feature = "php")]
+pub mod php;
+#[cfg(feature = "std")]
+mod router;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod sources;
+pub mod sql;
+#[cfg(feature = "sqlx")]
+#[path = "sqlx_guzzle.rs"]
+pub mod sqlx;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "toml")]
+mod toml_guzzle;
+pub mod transforms;
+#[cfg(feature = "wordpress")]
+pub mod wordpress;
+
+/// Consumes keys and values into the fields they belong to. Values are `String`s unless the
+/// struct is derived with `#[guzzle(value = ...)]`, eg `#[guzzle(value = serde_json::Value)]`, in
+/// which case fields of that type are set directly and other fields need a parser taking it.
+/// `#[guzzle(borrowed)]` structs take `&'a str` values instead, which their `&'a str` and
+/// `Cow<'a, str>` fields borrow without copying.
+pub trait Guzzle<V = String> {
+    /// Every key this type can consume, for pre-filtering queries or finding keys that two
+    /// guzzlers would fight over. Derived implementations list their own keys followed by those of
+    /// their deep guzzle fields, except for fields with a prefix, repeaters and fields that match
+    /// keys by a pattern, whose keys can't be known in advance. Generic types only list their own.
+    const KEYS: &'static [&'static str] = &[];
+
+    fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+    where
+        T: AsRef<str>;
+
+    /// Whether `guzzle` might consume the key, worked out from the type alone so it can be asked
+    /// without an instance and without allocating. Derived implementations answer from their keys,
+    /// prefixes and patterns, and those of their deep guzzle fields. Otherwise every key might be
+    /// consumed.
+    fn may_consume(_key: &str) -> bool {
+        true
+    }
+
+    /// Guzzles a borrowed key and value, returning whether they were consumed. Derived
+    /// implementations with string values check `may_consume` first and only copy the value for
+    /// keys something might consume, which saves an allocation for every row that would be left
+    /// over anyway. Otherwise the value is always converted and given to `guzzle`.
+    fn guzzle_ref(&mut self, key: &str, value: &str) -> bool
+    where
+        V: for<'v> From<&'v str>,
+    {
+        self.guzzle((key, V::from(value))).is_none()
+    }
+
+    /// Guzzles every key and value from the iterator, returning those that weren't consumed in the
+    /// order they arrived.
+    fn guzzle_all<T, I>(&mut self, iter: I) -> Vec<(T, V)>
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, V)>,
+    {
+        let iter = iter.into_iter();
+        // Leftovers are common in large metadata tables, so avoid growing the vec as we go
+        let mut leftovers = Vec::with_capacity(iter.size_hint().0);
+        for current in iter {
+            if let Some(leftover) = self.guzzle(current) {
+                leftovers.push(leftover);
+            }
+        }
+        leftovers
+    }
+
+    /// Guzzles the keys and values in the buffer where they are, removing those that are consumed
+    /// and leaving the rest in the order they were in. Unlike `guzzle_all` no new `Vec` is built,
+    /// so several guzzlers can take turns over the same buffer without reallocating. Each value is
+    /// swapped for its default while it's offered, and put back if it isn't consumed.
+    fn guzzle_drain<T>(&mut self, buffer: &mut Vec<(T, V)>)
+    where
+        T: AsRef<str>,
+        V: Default,
+    {
+        buffer.retain_mut(|(key, value)| {
+            match self.guzzle((key.as_ref(), core::mem::take(value))) {
+                Some((_, leftover)) => {
+                    *value = leftover;
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+
+    /// Guzzles entries out of a map, removing those that are consumed. Derived implementations
+    /// that only match exact keys look each of their keys up rather than scanning the whole map,
+    /// which is much faster when the metadata is already keyed. Otherwise every entry is taken out,
+    /// guzzled, and the leftovers put back.
+    fn guzzle_map<M>(&mut self, map: &mut M)
+    where
+        M: MetadataMap<V>,
+    {
+        for (key, value) in self.guzzle_all(map.take_all()) {
+            map.put(key, value);
+        }
+    }
+
+    /// Creates a default `Self` and guzzles every key and value from the iterator into it,
+    /// returning it along with those that weren't consumed. Like `guzzle_all` this doesn't
+    /// `finalize`, so use `from_metadata_finalized` if the type has anything held back or filled in
+    /// at the end.
+    ///
+    /// ```rust
+    /// use guzzle::Guzzle;
+    ///
+    /// #[derive(Default, Guzzle)]
+    /// struct Post {
+    ///     title: String,
+    /// }
+    ///
+    /// let (post, remaining_data) = Post::from_metadata(vec![
+    ///     ("title", "Hello".to_string()),
+    ///     ("colour", "red".to_string()),
+    /// ]);
+    /// assert_eq!(post.title, "Hello");
+    /// assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+    /// ```
+    fn from_metadata<T, I>(iter: I) -> (Self, Vec<(T, V)>)
+    where
+        Self: Default + Sized,
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, V)>,
+    {
+        let mut guzzled = Self::default();
+        let leftovers = guzzled.guzzle_all(iter);
+        (guzzled, leftovers)
+    }
+
+    /// Like `from_metadata`, but once the input is exhausted `Self` is also finalized, so defaults
+    /// are filled in and any `#[guzzle(finish = method)]` is called. Anything `finalize` hands back,
+    /// such as the members of an incomplete group, joins the leftovers, which is why their keys
+    /// are owned.
+    ///
+    /// ```rust
+    /// use guzzle::{Guzzle, GuzzleState};
+    ///
+    /// #[derive(Default, Guzzle)]
+    /// struct Post {
+    ///     title: String,
+    ///     #[guzzle(default_if_absent = "draft")]
+    ///     status: String,
+    ///     #[guzzle(state)]
+    ///     state: GuzzleState,
+    /// }
+    ///
+    /// let (post, remaining_data) = Post::from_metadata_finalized(vec![
+    ///     ("title", "Hello".to_string()),
+    ///     ("colour", "red".to_string()),
+    /// ]);
+    /// assert_eq!(post.title, "Hello");
+    /// assert_eq!(post.status, "draft");
+    /// assert_eq!(remaining_data, vec![("colour".to_string(), "red".to_string())]);
+    /// ```
+    fn from_metadata_finalized<T, I>(iter: I) -> (Self, Vec<(String, V)>)
+    where
+        Self: Default + Sized,
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, V)>,
+        V: From<String>,
+    {
+        let (mut guzzled, leftovers) = Self::from_metadata(iter);
+        let mut leftovers: Vec<_> = leftovers
+            .into_iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value))
+            .collect();
+        leftovers.extend(
+            guzzled
+                .finalize()
+                .into_iter()
+                .map(|(key, value)| (key, V::from(value))),
+        );
+        (guzzled, leftovers)
+    }
+
+    /// Guzzles one layer of a layered configuration over whatever has already been guzzled,
+    /// returning anything that wasn't consumed. Call it once per source in increasing order of
+    /// priority, eg defaults, then a config file, then the environment, then the command line.
+    ///
+    /// Ordinary fields take the value from the last layer that provided one, while fields that
+    /// `collect` (or `split`) keep the values from every layer, so sets end up with the union of
+    /// all layers, as does a `rest` field. Fields that no layer mentions are left as they were.
+    ///
+    /// That's exactly what guzzling into the same struct again already does, so this is
+    /// `guzzle_all` under a name that says how it's being used.
+    fn guzzle_layer<T, I>(&mut self, iter: I) -> Vec<(T, V)>
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, V)>,
+    {
+        self.guzzle_all(iter)
+    }
+
+    /// Like `guzzle_all`, but stops guzzling once `max` pairs have been consumed. Everything after
+    /// that point is returned as leftovers without being offered to `guzzle`.
+    fn guzzle_all_limited<T, I>(&mut self, iter: I, max: usize) -> Vec<(T, V)>
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, V)>,
+    {
+        let mut iter = iter.into_iter();
+        let mut leftovers = Vec::with_capacity(iter.size_hint().0);
+        let mut consumed = 0;
+        while consumed < max {
+            match iter.next() {
+                Some(current) => match self.guzzle(current) {
+                    Some(leftover) => leftovers.push(leftover),
+                    None => consumed += 1,
+                },
+                None => break,
+            }
+        }
+        leftovers.extend(iter);
+        leftovers
+    }
+
+    /// Called once everything has been guzzled, this fills in values such as
+    /// `#[guzzle(default_if_absent = "...")]` and `#[guzzle(default = ...)]` that depend on what
+    /// wasn't seen. Deep guzzle fields are finalized too, and then derived implementations with
+    /// `#[guzzle(finish = method)]` call the method.
+    ///
+    /// Anything that was held back and never consumed, such as the members of a
+    /// `#[guzzle(group(...))]` that never completed, is returned as leftovers.
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// How many fields marked `#[guzzle(required)]` have not been guzzled yet, including those in
+    /// deep guzzle fields. Useful for reporting progress on long running ingestion.
+    fn remaining_required(&self) -> usize {
+        0
+    }
+
+    /// The key for each field marked `#[guzzle(required)]` that has not been guzzled yet,
+    /// including those in deep guzzle fields, so callers can tell a struct that was fully
+    /// populated from one silently left at its defaults. Fields are listed under their first key,
+    /// which for deep guzzle fields with a prefix, and repeaters, is the key without the prefix.
+    fn missing_keys(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// Describes what `guzzle` would do with the given key. This is a debugging aid for finding
+    /// out why a key ended up in the leftovers. Implementations that don't provide this will
+    /// always say the key is unknown.
+    fn explain_key(&self, _key: &str) -> KeyDisposition {
+        KeyDisposition::Unknown
+    }
+}
+
+/// An optional section is only created once it consumes something, so it stays `None` if none of
+/// its keys turn up. Until then it explains keys as its default would.
+impl<V, G> Guzzle<V> for Option<G>
+where
+    G: Guzzle<V> + Default,
+{
+    const KEYS: &'static [&'static str] = G::KEYS;
+
+    fn may_consume(key: &str) -> bool {
+        G::may_consume(key)
+    }
+
+    fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+    where
+        T: AsRef<str>,
+    {
+        match self {
+            Some(inner) => inner.guzzle(current),
+            None => {
+                let mut inner = G::default();
+                let leftover = inner.guzzle(current);
+                if leftover.is_none() {
+                    *self = Some(inner);
+                }
+                leftover
+            }
+        }
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        self.as_mut().map(G::finalize).unwrap_or_default()
+    }
+
+    fn remaining_required(&self) -> usize {
+        self.as_ref().map(G::remaining_required).unwrap_or_default()
+    }
+
+    fn missing_keys(&self) -> Vec<&'static str> {
+        self.as_ref().map(G::missing_keys).unwrap_or_default()
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        match self {
+            Some(inner) => inner.explain_key(key),
+            None => G::default().explain_key(key),
+        }
+    }
+}
+
+/// Guzzlers behind a pointer are fed through it, so boxed and borrowed guzzlers can be used
+/// wherever a `Guzzle` is expected.
+impl<V, G> Guzzle<V> for Box<G>
+where
+    G: Guzzle<V> + ?Sized,
+{
+    const KEYS: &'static [&'static str] = G::KEYS;
+
+    fn may_consume(key: &str) -> bool {
+        G::may_consume(key)
+    }
+
+    fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+    where
+        T: AsRef<str>,
+    {
+        (**self).guzzle(current)
+    }
+
+    fn guzzle_ref(&mut self, key: &str, value: &str) -> bool
+    where
+        V: for<'v> From<&'v str>,
+    {
+        (**self).guzzle_ref(key, value)
+    }
+
+    fn guzzle_map<M>(&mut self, map: &mut M)
+    where
+        M: MetadataMap<V>,
+    {
+        (**self).guzzle_map(map)
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        (**self).finalize()
+    }
+
+    fn remaining_required(&self) -> usize {
+        (**self).remaining_required()
+    }
+
+    fn missing_keys(&self) -> Vec<&'static str> {
+        (**self).missing_keys()
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        (**self).explain_key(key)
+    }
+}
+
+impl<V, G> Guzzle<V> for &mut G
+where
+    G: Guzzle<V> + ?Sized,
+{
+    const KEYS: &'static [&'static str] = G::KEYS;
+
+    fn may_consume(key: &str) -> bool {
+        G::may_consume(key)
+    }
+
+    fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+    where
+        T: AsRef<str>,
+    {
+        (**self).guzzle(current)
+    }
+
+    fn guzzle_ref(&mut self, key: &str, value: &str) -> bool
+    where
+        V: for<'v> From<&'v str>,
+    {
+        (**self).guzzle_ref(key, value)
+    }
+
+    fn guzzle_map<M>(&mut self, map: &mut M)
+    where
+        M: MetadataMap<V>,
+    {
+        (**self).guzzle_map(map)
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        (**self).finalize()
+    }
+
+    fn remaining_required(&self) -> usize {
+        (**self).remaining_required()
+    }
+
+    fn missing_keys(&self) -> Vec<&'static str> {
+        (**self).missing_keys()
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        (**self).explain_key(key)
+    }
+}
+
+/// Shared guzzlers are borrowed for each call, so this panics if the guzzler is already borrowed,
+/// as `RefCell` always does.
+impl<V, G> Guzzle<V> for Rc<RefCell<G>>
+where
+    G: Guzzle<V> + ?Sized,
+{
+    const KEYS: &'static [&'static str] = G::KEYS;
+
+    fn may_consume(key: &str) -> bool {
+        G::may_consume(key)
+    }
+
+    fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+    where
+        T: AsRef<str>,
+    {
+        self.borrow_mut().guzzle(current)
+    }
+
+    fn guzzle_ref(&mut self, key: &str, value: &str) -> bool
+    where
+        V: for<'v> From<&'v str>,
+    {
+        self.borrow_mut().guzzle_ref(key, value)
+    }
+
+    fn guzzle_map<M>(&mut self, map: &mut M)
+    where
+        M: MetadataMap<V>,
+    {
+        self.borrow_mut().guzzle_map(map)
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        self.borrow_mut().finalize()
+    }
+
+    fn remaining_required(&self) -> usize {
+        self.borrow().remaining_required()
+    }
+
+    fn missing_keys(&self) -> Vec<&'static str> {
+        self.borrow().missing_keys()
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        self.borrow().explain_key(key)
+    }
+}
+
+/// Shared guzzlers are locked for each call. A guzzler whose lock was poisoned is still fed, as
+/// leaving it half guzzled is no worse than the panic that poisoned it.
+#[cfg(feature = "std")]
+impl<V, G> Guzzle<V> for Arc<Mutex<G>>
+where
+    G: Guzzle<V> + ?Sized,
+{
+    const KEYS: &'static [&'static str] = G::KEYS;
+
+    fn may_consume(key: &str) -> bool {
+        G::may_consume(key)
+    }
+
+    fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+    where
+        T: AsRef<str>,
+    {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .guzzle(current)
+    }
+
+    fn guzzle_ref(&mut self, key: &str, value: &str) -> bool
+    where
+        V: for<'v> From<&'v str>,
+    {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .guzzle_ref(key, value)
+    }
+
+    fn guzzle_map<M>(&mut self, map: &mut M)
+    where
+        M: MetadataMap<V>,
+    {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .guzzle_map(map)
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .finalize()
+    }
+
+    fn remaining_required(&self) -> usize {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remaining_required()
+    }
+
+    fn missing_keys(&self) -> Vec<&'static str> {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .missing_keys()
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .explain_key(key)
+    }
+}
+
+/// Tuples of guzzlers offer each key and value to every element in turn until one consumes it, so
+/// several independent structs can be filled in a single pass without a wrapper struct
+macro_rules! tuple_guzzle {
+    ($($guzzler:ident $index:tt),+) => {
+        impl<V, $($guzzler),+> Guzzle<V> for ($($guzzler,)+)
+        where
+            $($guzzler: Guzzle<V>,)+
+        {
+            fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+            where
+                T: AsRef<str>,
+            {
+                $( let current = self.$index.guzzle(current)?; )+
+                Some(current)
+            }
+
+            fn guzzle_ref(&mut self, key: &str, value: &str) -> bool
+            where
+                V: for<'v> From<&'v str>,
+            {
+                $( self.$index.guzzle_ref(key, value) )||+
+            }
+
+            fn guzzle_map<M>(&mut self, map: &mut M)
+            where
+                M: MetadataMap<V>,
+            {
+                $( self.$index.guzzle_map(map); )+
+            }
+
+            fn finalize(&mut self) -> Vec<(String, String)> {
+                let mut leftovers = vec![];
+                $( leftovers.extend(self.$index.finalize()); )+
+                leftovers
+            }
+
+            fn remaining_required(&self) -> usize {
+                0 $( + self.$index.remaining_required() )+
+            }
+
+            fn missing_keys(&self) -> Vec<&'static str> {
+                let mut missing = vec![];
+                $( missing.extend(self.$index.missing_keys()); )+
+                missing
+            }
+
+            fn explain_key(&self, key: &str) -> KeyDisposition {
+                $(
+                    match self.$index.explain_key(key) {
+                        KeyDisposition::Unknown | KeyDisposition::Discarded(_) => {}
+                        disposition => return disposition,
+                    }
+                )+
+                KeyDisposition::Unknown
+            }
+        }
+    };
+}
+
+tuple_guzzle!(A 0, B 1);
+tuple_guzzle!(A 0, B 1, C 2);
+tuple_guzzle!(A 0, B 1, C 2, D 3);
+tuple_guzzle!(A 0, B 1, C 2, D 3, E 4);
+tuple_guzzle!(A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_guzzle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_guzzle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Composes two guzzlers that don't know about each other into one, which offers each key and
+/// value to `first`, then to `second` if `first` didn't consume it. Chains can be chained too.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Post {
+///     title: String,
+/// }
+///
+/// #[derive(Default, Guzzle)]
+/// struct Author {
+///     name: String,
+/// }
+///
+/// let mut chain = guzzle::chain(Post::default(), Author::default());
+/// let remaining_data = chain.guzzle_all(vec![
+///     ("name", "Daniel".to_string()),
+///     ("title", "Hello".to_string()),
+///     ("colour", "red".to_string()),
+/// ]);
+/// assert_eq!(chain.first.title, "Hello");
+/// assert_eq!(chain.second.name, "Daniel");
+/// assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+/// ```
+pub fn chain<A, B>(first: A, second: B) -> Chain<A, B> {
+    Chain { first, second }
+}
+
+/// Two guzzlers composed with `chain`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Chain<A, B> {
+    pub first: A,
+    pub second: B,
+}
+
+impl<V, A, B> Guzzle<V> for Chain<A, B>
+where
+    A: Guzzle<V>,
+    B: Guzzle<V>,
+{
+    fn guzzle<T>(&mut self, current: (T, V)) -> Option<(T, V)>
+    where
+        T: AsRef<str>,
+    {
+        let current = self.first.guzzle(current)?;
+        self.second.guzzle(current)
+    }
+
+    fn may_consume(key: &str) -> bool {
+        A::may_consume(key) || B::may_consume(key)
+    }
+
+    fn guzzle_ref(&mut self, key: &str, value: &str) -> bool
+    where
+        V: for<'v> From<&'v str>,
+    {
+        self.first.guzzle_ref(key, value) || self.second.guzzle_ref(key, value)
+    }
+
+    fn guzzle_map<M>(&mut self, map: &mut M)
+    where
+        M: MetadataMap<V>,
+    {
+        self.first.guzzle_map(map);
+        self.second.guzzle_map(map);
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        let mut leftovers = self.first.finalize();
+        leftovers.extend(self.second.finalize());
+        leftovers
+    }
+
+    fn remaining_required(&self) -> usize {
+        self.first.remaining_required() + self.second.remaining_required()
+    }
+
+    fn missing_keys(&self) -> Vec<&'static str> {
+        let mut missing = self.first.missing_keys();
+        missing.extend(self.second.missing_keys());
+        missing
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        match self.first.explain_key(key) {
+            KeyDisposition::Unknown | KeyDisposition::Discarded(_) => self.second.explain_key(key),
+            disposition => disposition,
+        }
+    }
+}
+
+/// Guzzling straight from an iterator of keys and values, for any iterator.
+///
+/// ```rust
+/// use guzzle::{Guzzle, GuzzleIteratorExt};
+///
+/// #[derive(Default, Guzzle)]
+/// struct Post {
+///     title: String,
+/// }
+///
+/// let example_data = vec![("title", "Hello".to_string()), ("colour", "red".to_string())];
+///
+/// let mut post = Post::default();
+/// let remaining_data: Vec<_> = example_data.clone().into_iter().guzzle_into(&mut post).collect();
+/// assert_eq!(post.title, "Hello");
+/// assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+///
+/// let mut post = Post::default();
+/// example_data.into_iter().guzzle_exhaust(&mut post);
+/// assert_eq!(post.title, "Hello");
+/// ```
+pub trait GuzzleIteratorExt: Iterator + Sized {
+    /// Lazily guzzles each key and value into the target as the leftovers are iterated
+    fn guzzle_into<G: ?Sized>(self, target: &mut G) -> GuzzleInto<'_, Self, G> {
+        GuzzleInto { iter: self, target }
+    }
+
+    /// Guzzles every key and value into the target, dropping the leftovers
+    fn guzzle_exhaust<G, T, V>(self, target: &mut G)
+    where
+        Self: Iterator<Item = (T, V)>,
+        T: AsRef<str>,
+        G: Guzzle<V> + ?Sized,
+    {
+        self.guzzle_into(target).for_each(drop)
+    }
+}
+
+impl<I: Iterator> GuzzleIteratorExt for I {}
+
+/// The leftovers from guzzling an iterator with `GuzzleIteratorExt::guzzle_into`
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct GuzzleInto<'g, I, G: ?Sized> {
+    iter: I,
+    target: &'g mut G,
+}
+
+impl<I, G, T, V> Iterator for GuzzleInto<'_, I, G>
+where
+    I: Iterator<Item = (T, V)>,
+    T: AsRef<str>,
+    G: Guzzle<V> + ?Sized,
+{
+    type Item = (T, V);
+
+    fn next(&mut self) -> Option<(T, V)> {
+        let target = &mut *self.target;
+        self.iter
+            .by_ref()
+            .find_map(|current| target.guzzle(current))
+    }
+}
+
+/// Metadata that's already keyed, which `Guzzle::guzzle_map` can look keys up in
+pub trait MetadataMap<V> {
+    /// Removes the entry for the key, if there is one
+    fn take(&mut self, key: &str) -> Option<(String, V)>;
+
+    /// Removes every entry
+    fn take_all(&mut self) -> Vec<(String, V)>;
+
+    /// Puts back an entry that wasn't consumed
+    fn put(&mut self, key: String, value: V);
+}
+
+#[cfg(feature = "std")]
+impl<V, S: BuildHasher> MetadataMap<V> for HashMap<String, V, S> {
+    fn take(&mut self, key: &str) -> Option<(String, V)> {
+        self.remove_entry(key)
+    }
+
+    fn take_all(&mut self) -> Vec<(String, V)> {
+        self.drain().collect()
+    }
+
+    fn put(&mut self, key: String, value: V) {
+        self.insert(key, value);
+    }
+}
+
+impl<V> MetadataMap<V> for BTreeMap<String, V> {
+    fn take(&mut self, key: &str) -> Option<(String, V)> {
+        self.remove_entry(key)
+    }
+
+    fn take_all(&mut self) -> Vec<(String, V)> {
+        core::mem::take(self).into_iter().collect()
+    }
+
+    fn put(&mut self, key: String, value: V) {
+        self.insert(key, value);
+    }
+}
+
+/// A fallible `Guzzle`, for when some keys and values should be rejected outright rather than
+/// left over. Deriving `Guzzle` implements this for every struct with string keys, where:
+///
+/// - a field with a `#[guzzle(try_parser = ...)]`, a parser returning a `Result` whose error
+///   implements `Display`, or one parsed with `FromStr`, reports a failed parse as a
+///   `GuzzleError::Parse`, where `Guzzle` would hand the key and value back instead.
+/// - `#[guzzle(strict_prefix = "...")]` on the struct makes unmatched keys that start with the
+///   prefix a `GuzzleError::UnknownKey`, and `#[guzzle(deny_unknown_keys)]` does the same for
+///   every unmatched key.
+///
+/// Deep guzzle fields and groups are guzzled as normal, so they can't cause errors.
+///
+/// ```rust
+/// use guzzle::{Guzzle, GuzzleError, TryGuzzle};
+///
+/// #[derive(Default, Guzzle)]
+/// #[guzzle(strict_prefix = "wp_")]
+/// struct Post {
+///     #[guzzle(keys = ["wp_title"])]
+///     title: String,
+/// }
+///
+/// let mut post = Post::default();
+/// assert_eq!(post.try_guzzle(("wp_title", "Hello".to_string())), Ok(None));
+/// assert_eq!(
+///     post.try_guzzle(("wp_unknown", "?".to_string())),
+///     Err(GuzzleError::UnknownKey("wp_unknown".to_string()))
+/// );
+/// assert_eq!(
+///     post.try_guzzle(("other", "thing".to_string())),
+///     Ok(Some(("other", "thing".to_string())))
+/// );
+/// ```
+pub trait TryGuzzle {
+    type Error;
+
+    fn try_guzzle<T>(&mut self, current: (T, String)) -> Result<Option<(T, String)>, Self::Error>
+    where
+        T: AsRef<str>;
+
+    /// Guzzles every key and value from the iterator, returning those that weren't consumed, or
+    /// stopping at the first error
+    fn try_guzzle_all<T, I>(&mut self, iter: I) -> Result<Vec<(T, String)>, Self::Error>
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, String)>,
+    {
+        let mut leftovers = vec![];
+        for current in iter {
+            if let Some(leftover) = self.try_guzzle(current)? {
+                leftovers.push(leftover);
+            }
+        }
+        Ok(leftovers)
+    }
+}
+
+/// Builds a struct from keys and values, failing rather than leaving fields at their defaults.
+/// This is implemented for every type that derives `Guzzle` with string keys, which is to say
+/// anything that is `Default` and implements both `Guzzle` and `TryGuzzle`.
+///
+/// Every pair is guzzled before giving up, so the error lists every parse failure and unknown key
+/// along with the first key of every `#[guzzle(required)]` field that never arrived. Pairs that
+/// nothing consumed are otherwise discarded.
+///
+/// ```rust
+/// use guzzle::{Guzzle, GuzzleError, GuzzleState, TryFromMetadata};
+///
+/// #[derive(Debug, Default, Guzzle)]
+/// struct Post {
+///     #[guzzle(required)]
+///     title: String,
+///     views: u32,
+///     #[guzzle(state)]
+///     state: GuzzleState,
+/// }
+///
+/// let post = Post::try_from_metadata(vec![("title", "Hello".to_string())]).unwrap();
+/// assert_eq!(post.title, "Hello");
+///
+/// let error = Post::try_from_metadata(vec![("views", "lots".to_string())]).unwrap_err();
+/// assert_eq!(
+///     error,
+///     GuzzleError::Invalid {
+///         missing: vec!["title".to_string()],
+///         errors: vec![GuzzleError::Parse {
+///             key: "views".to_string(),
+///             message: "invalid digit found in string".to_string(),
+///         }],
+///     }
+/// );
+/// ```
+pub trait TryFromMetadata: Sized {
+    fn try_from_metadata<T, I>(iter: I) -> Result<Self, GuzzleError>
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, String)>;
+}
+
+impl<G> TryFromMetadata for G
+where
+    G: Default + Guzzle + TryGuzzle<Error = GuzzleError>,
+{
+    fn try_from_metadata<T, I>(iter: I) -> Result<Self, GuzzleError>
+    where
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, String)>,
+    {
+        let mut guzzled = G::default();
+        let mut errors = vec![];
+        for current in iter {
+            if let Err(error) = guzzled.try_guzzle(current) {
+                errors.push(error);
+            }
+        }
+        guzzled.finalize();
+        let missing: Vec<_> = guzzled
+            .missing_keys()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        if missing.is_empty() && errors.is_empty() {
+            Ok(guzzled)
+        } else {
+            Err(GuzzleError::Invalid { missing, errors })
+        }
+    }
+}
+
+/// The errors a derived `TryGuzzle` and `TryFromMetadata` can produce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuzzleError {
+    /// The key was in a strict prefix, or the struct denies unknown keys, but nothing consumed it
+    UnknownKey(String),
+    /// Every key `try_guzzle_all` was given that nothing consumed, for structs that deny unknown
+    /// keys
+    UnknownKeys(Vec<String>),
+    /// A `try_parser` couldn't parse the value for the key
+    Parse { key: String, message: String },
+    /// The key's field had already been guzzled, and it's marked `duplicates = "error"`
+    Duplicate(String),
+    /// `TryFromMetadata` couldn't build the struct, because required keys were missing or there
+    /// were errors guzzling the keys that were there
+    Invalid {
+        missing: Vec<String>,
+        errors: Vec<GuzzleError>,
+    },
+}
+
+impl fmt::Display for GuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuzzleError::UnknownKey(key) => write!(f, "Unknown key: {}", key),
+            GuzzleError::UnknownKeys(keys) => write!(f, "Unknown keys: {}", keys.join(", ")),
+            GuzzleError::Parse { key, message } => {
+                write!(f, "Could not parse the value for {}: {}", key, message)
+            }
+            GuzzleError::Duplicate(key) => write!(f, "Duplicate key: {}", key),
+            GuzzleError::Invalid { missing, errors } => {
+                write!(f, "Invalid metadata")?;
+                if !missing.is_empty() {
+                    write!(f, ", missing keys: {}", missing.join(", "))?;
+                }
+                for error in errors {
+                    write!(f, "; {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for GuzzleError {}
+
+/// The reverse of `Guzzle`, turning a struct back into keys and values. Derive it alongside
+/// `Guzzle` and it will use the same attributes. Each field is output under its first key using
+/// its `Display` implementation. Fields with a `split` delimiter are joined back into a single
+/// value with it, and other collected fields get a pair per value. Empty collections are left
+/// out. A field whose `parser` isn't the reverse of `Display` can give
+/// `#[guzzle(unparser = ...)]` a function from a reference to each value to its string.
+pub trait Unguzzle {
+    fn to_metadata(&self) -> Vec<(String, String)>;
+}
+
+/// The keys whose values changed between two instances of an `Unguzzle` type, so only those need
+/// to be written back. Each changed or added key comes with its new value, in the order
+/// `to_metadata` outputs them. Keys with several values, such as collected fields, are changed if
+/// any of their values are, and then come with every one of their new values. These are followed
+/// by any keys that are no longer output at all, such as an `Option` that has been emptied, with
+/// `None`. Derive it alongside `Unguzzle`.
+///
+/// ```rust
+/// use guzzle::{Guzzle, GuzzleDiff, Unguzzle};
+///
+/// #[derive(Clone, Default, Guzzle, Unguzzle, GuzzleDiff)]
+/// struct Post {
+///     title: String,
+///     views: u64,
+///     subtitle: Option<String>,
+/// }
+///
+/// let old = Post { title: "Hi".to_string(), views: 1, subtitle: Some("There".to_string()) };
+/// let mut new = old.clone();
+/// new.views = 2;
+/// new.subtitle = None;
+/// assert_eq!(
+///     new.guzzle_diff(&old),
+///     vec![("views".to_string(), Some("2".to_string())), ("subtitle".to_string(), None)]
+/// );
+/// ```
+pub trait GuzzleDiff: Unguzzle {
+    fn guzzle_diff(&self, old: &Self) -> Vec<(String, Option<String>)> {
+        // Keys can have several values, such as those of collected fields, so each key's whole
+        // list of values is compared
+        fn by_key(pairs: &[(String, String)]) -> BTreeMap<&str, Vec<&str>> {
+            let mut values: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            for (key, value) in pairs {
+                values.entry(key.as_str()).or_default().push(value.as_str());
+            }
+            values
+        }
+        let old_pairs = old.to_metadata();
+        let new_pairs = self.to_metadata();
+        let mut unseen = by_key(&old_pairs);
+        let new_values = by_key(&new_pairs);
+        let changed: Vec<&str> = new_values
+            .iter()
+            .filter(|(key, values)| unseen.remove(*key).as_ref() != Some(*values))
+            .map(|(key, _)| *key)
+            .collect();
+        let mut changes: Vec<_> = new_pairs
+            .iter()
+            .filter(|(key, _)| changed.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), Some(value.clone())))
+            .collect();
+        // Whatever wasn't output again has been removed
+        changes.extend(
+            old_pairs
+                .iter()
+                .filter(|(key, _)| unseen.remove(key.as_str()).is_some())
+                .map(|(key, _)| (key.clone(), None)),
+        );
+        changes
+    }
+}
+
+/// An object safe companion to `Guzzle`, whose generic methods mean there can't be a
+/// `dyn Guzzle`. Every `Guzzle` implements it, so different types can be boxed up together, and a
+/// `Vec<Box<dyn GuzzleDyn>>` is itself a `Guzzle` that offers each key and value to every stage
+/// in turn until one consumes it.
+///
+/// ```rust
+/// use guzzle::{Guzzle, GuzzleDyn};
+///
+/// #[derive(Default, Guzzle)]
+/// struct Post {
+///     title: String,
+/// }
+///
+/// #[derive(Default, Guzzle)]
+/// struct Author {
+///     name: String,
+/// }
+///
+/// let mut pipeline: Vec<Box<dyn GuzzleDyn>> =
+///     vec![Box::new(Post::default()), Box::new(Author::default())];
+/// let remaining_data = pipeline.guzzle_all(vec![
+///     ("title", "Hello".to_string()),
+///     ("name", "Daniel".to_string()),
+///     ("colour", "red".to_string()),
+/// ]);
+/// assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+/// ```
+pub trait GuzzleDyn {
+    /// `Guzzle::guzzle` with an owned key
+    fn guzzle_dyn(&mut self, current: (String, String)) -> Option<(String, String)>;
+
+    /// `Guzzle::finalize`
+    fn finalize_dyn(&mut self) -> Vec<(String, String)>;
+
+    /// `Guzzle::explain_key`
+    fn explain_key_dyn(&self, key: &str) -> KeyDisposition;
+}
+
+impl<G: Guzzle> GuzzleDyn for G {
+    fn guzzle_dyn(&mut self, current: (String, String)) -> Option<(String, String)> {
+        self.guzzle(current)
+    }
+
+    fn finalize_dyn(&mut self) -> Vec<(String, String)> {
+        self.finalize()
+    }
+
+    fn explain_key_dyn(&self, key: &str) -> KeyDisposition {
+        self.explain_key(key)
+    }
+}
+
+impl Guzzle for Vec<Box<dyn GuzzleDyn>> {
+    fn guzzle<T>(&mut self, (key, value): (T, String)) -> Option<(T, String)>
+    where
+        T: AsRef<str>,
+    {
+        // Each stage hands back what it didn't consume, the original key goes back to the caller
+        let mut current = (key.as_ref().to_string(), value);
+        for stage in self.iter_mut() {
+            current = stage.guzzle_dyn(current)?;
+        }
+        Some((key, current.1))
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        self.iter_mut()
+            .flat_map(|stage| stage.finalize_dyn())
+            .collect()
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        // A key one stage discards may still be consumed by a later one
+        self.iter()
+            .map(|stage| stage.explain_key_dyn(key))
+            .find(|disposition| {
+                !matches!(
+                    disposition,
+                    KeyDisposition::Unknown | KeyDisposition::Discarded(_)
+                )
+            })
+            .unwrap_or(KeyDisposition::Unknown)
+    }
+}
+
+/// The byte equivalent of `Guzzle` for protocols where keys and values can't be assumed to be
+/// UTF-8. Derive it by annotating a struct deriving `Guzzle` with `#[guzzle(bytes)]`, keys are
+/// then compared as byte strings and parsers take a `Vec<u8>`.
+pub trait BytesGuzzle {
+    fn guzzle_bytes<T>(&mut self, current: (T, Vec<u8>)) -> Option<(T, Vec<u8>)>
+    where
+        T: AsRef<[u8]>;
+}
+
+/// Guzzles into a map rather than a struct, for when the fields aren't known until runtime. Only
+/// keys found in `allowed` are consumed, anything else is handed back.
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// let mut target = HashMap::new();
+/// let allowed = ["title"];
+/// assert_eq!(guzzle::guzzle_dynamic(&mut target, &allowed, ("title".into(), "Hi".into())), None);
+/// assert_eq!(target.get("title"), Some(&"Hi".to_string()));
+/// ```
+#[cfg(feature = "std")]
+pub fn guzzle_dynamic(
+    target: &mut HashMap<String, String>,
+    allowed: &[&str],
+    (key, value): (String, String),
+) -> Option<(String, String)> {
+    if allowed.contains(&key.as_str()) {
+        target.insert(key, value);
+        None
+    } else {
+        Some((key, value))
+    }
+}
+
+/// Guzzles newline delimited `key=value` text, returning any pairs that weren't consumed.
+///
+/// Each line is split on its first `=` and both sides are trimmed, so values may themselves contain
+/// `=`. Blank lines and lines starting with `#` are skipped. A line with no `=` at all is treated
+/// as a key with an empty value.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Config {
+///     name: String,
+/// }
+///
+/// let mut config = Config::default();
+/// let remaining = guzzle::guzzle_kv_lines(&mut config, "# A comment\nname = guzzle\nother=thing");
+/// assert_eq!(config.name, "guzzle".to_string());
+/// assert_eq!(remaining, vec![("other".to_string(), "thing".to_string())]);
+/// ```
+pub fn guzzle_kv_lines<G: Guzzle>(g: &mut G, text: &str) -> Vec<(String, String)> {
+    let pairs = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or_default().trim();
+            let value = parts.next().unwrap_or_default().trim();
+            (key.to_string(), value.to_string())
+        });
+    g.guzzle_all(pairs)
+}
+
+/// Seeded FNV-1a over the key, which `#[guzzle(matcher = "phf")]` uses to find the slot a key is
+/// in. It has to match the hash the derive searched for a seed with.
+#[doc(hidden)]
+pub fn key_hash(seed: u64, key: &str) -> u64 {
+    key.bytes()
+        .fold(0xcbf2_9ce4_8422_2325 ^ seed, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Joins lists of keys into one, for `Guzzle::KEYS`. `N` has to be their total length.
+#[doc(hidden)]
+pub const fn concatenate_keys<const N: usize>(
+    lists: &[&'static [&'static str]],
+) -> [&'static str; N] {
+    let mut keys = [""; N];
+    let mut index = 0;
+    let mut list = 0;
+    while list < lists.len() {
+        let mut key = 0;
+        while key < lists[list].len() {
+            keys[index] = lists[list][key];
+            index += 1;
+            key += 1;
+        }
+        list += 1;
+    }
+    keys
+}
+
+/// The most rows a `#[deep_guzzle]` `Vec` will grow to unless it's given its own
+/// `#[deep_guzzle(max_rows = ...)]`. Keys naming a row past the limit are handed back rather than
+/// consumed, so a single pair can't allocate an unbounded number of rows.
+pub const DEFAULT_MAX_ROWS: usize = 1024;
+
+/// Splits a repeater key such as `rows_0_title` into the row's index and the rest of the key, eg
+/// `(0, "title")`, given the prefix `rows_`. Used by `#[deep_guzzle]` on `Vec` fields.
+#[doc(hidden)]
+pub fn split_indexed_key<'k>(key: &'k str, prefix: &str) -> Option<(usize, &'k str)> {
+    let (index, rest) = key.strip_prefix(prefix)?.split_once('_')?;
+    // `parse` would also allow a leading `+`
+    if index.is_empty() || !index.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some((index.parse().ok()?, rest))
+}
+
+/// Keeps track of what has been guzzled for features that need to know about it after the fact,
+/// such as `default_if_absent` and groups. Add a field of this type marked with `#[guzzle(state)]`
+/// and the derive will keep it up to date.
+///
+/// ```rust
+/// use guzzle::{Guzzle, GuzzleState};
+///
+/// #[derive(Default, Guzzle)]
+/// struct Post {
+///     #[guzzle(default_if_absent = "draft")]
+///     status: String,
+///     #[guzzle(state)]
+///     state: GuzzleState,
+/// }
+///
+/// let mut post = Post::default();
+/// post.finalize();
+/// assert_eq!(post.status, "draft".to_string());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GuzzleState {
+    seen: BTreeSet<&'static str>,
+    /// Members of groups that haven't completed yet, along with the group they belong to
+    pending: Vec<(&'static str, String, String)>,
+    /// Pairs that were consumed into a group but then rejected by their field
+    released: Vec<(String, String)>,
+}
+
+impl GuzzleState {
+    /// Records that the named field has been guzzled
+    pub fn mark_seen(&mut self, field: &'static str) {
+        self.seen.insert(field);
+    }
+
+    /// Whether the named field has been guzzled
+    pub fn has_seen(&self, field: &str) -> bool {
+        self.seen.contains(field)
+    }
+
+    /// Holds on to a member of a group. Once every key in the group has been buffered they are all
+    /// handed back, in the order they arrived, to be guzzled together. A key that arrives twice
+    /// before the group completes replaces its earlier value.
+    pub fn buffer_group(
+        &mut self,
+        group: &'static str,
+        keys: &[&str],
+        key: &str,
+        value: String,
+    ) -> Option<Vec<(String, String)>> {
+        self.pending.retain(|(pending_group, pending_key, _)| {
+            *pending_group != group || pending_key != key
+        });
+        self.pending.push((group, key.to_string(), value));
+
+        let complete = keys.iter().all(|key| {
+            self.pending.iter().any(|(pending_group, pending_key, _)| {
+                *pending_group == group && pending_key == key
+            })
+        });
+        if !complete {
+            return None;
+        }
+
+        let (members, pending) = self
+            .pending
+            .drain(..)
+            .partition(|(pending_group, _, _)| *pending_group == group);
+        self.pending = pending;
+        Some(
+            members
+                .into_iter()
+                .map(|(_, key, value)| (key, value))
+                .collect(),
+        )
+    }
+
+    /// Hands a pair back to be returned as a leftover by `Guzzle::finalize`
+    pub fn release(&mut self, pair: (String, String)) {
+        self.released.push(pair);
+    }
+
+    /// Everything that was held back and never consumed, including the members of any group that
+    /// didn't complete
+    pub fn take_leftovers(&mut self) -> Vec<(String, String)> {
+        let pending = self.pending.drain(..).map(|(_, key, value)| (key, value));
+        self.released.drain(..).chain(pending).collect()
+    }
+}
+
+/// What a guzzler would do with a key, as returned by `Guzzle::explain_key`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyDisposition {
+    /// The key will be consumed into the named field
+    Field(&'static str),
+    /// The key matches a field marked with `no_guzzle`, so it will be left over
+    Discarded(&'static str),
+    /// The key will be consumed by the named deep guzzle field, as described by the inner
+    /// disposition
+    DeepGuzzle(&'static str, Box<KeyDisposition>),
+    /// Nothing will consume the key, so it will be left over
+    Unknown,
+}
+
+// The tests lean on std throughout, as do most users
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    mod guzzle_trait {
+        use crate::Guzzle;
+
+        #[derive(Default)]
+        struct Tester {
+            pub one: String,
+            pub two: String,
+        }
+
+        impl Guzzle for Tester {
+            fn guzzle<T>(&mut self, (key, value): (T, String)) -> Option<(T, String)>
+            where
+                T: AsRef<str>,
+            {
+                match key.as_ref() {
+                    "one" => self.one = value,
+                    "two" => self.two = value,
+                    _ => return Some((key, value)),
+                }
+                None
+            }
+        }
+
+        #[test]
+        fn guzzle_with_vec_string_string() {
+            let test_data = vec![
+                ("one".to_string(), "1".to_string()),
+                ("two".to_string(), "2".to_string()),
+                ("three".to_string(), "3".to_string()),
+            ];
+
+            let mut tester = Tester::default();
+
+            let remaining_data: Vec<(String, String)> = test_data
+                .into_iter()
+                .filter_map(|v| tester.guzzle(v))
+                .collect();
+
+            assert_eq!(tester.one, "1".to_string());
+            assert_eq!(tester.two, "2".to_string());
+
+            assert_eq!(remaining_data.len(), 1);
+            assert_eq!(
+                remaining_data.into_iter().next(),
+                Some(("three".to_string(), "3".to_string()))
+            );
+        }
+
+        #[test]
+        fn guzzle_with_vec_str_string() {
+            let test_data = vec![
+                ("one", "1".to_string()),
+                ("two", "2".to_string()),
+                ("three", "3".to_string()),
+            ];
+
+            let mut tester = Tester::default();
+
+            let remaining_data: Vec<(&str, String)> = test_data
+                .into_iter()
+                .filter_map(|v| tester.guzzle(v))
+                .collect();
+
+            assert_eq!(tester.one, "1".to_string());
+            assert_eq!(tester.two, "2".to_string());
+
+            assert_eq!(remaining_data.len(), 1);
+            assert_eq!(
+                remaining_data.into_iter().next(),
+                Some(("three", "3".to_string()))
+            );
+        }
+
+        #[test]
+        fn guzzle_all_returns_leftovers() {
+            let test_data = vec![
+                ("one", "1".to_string()),
+                ("two", "2".to_string()),
+                ("three", "3".to_string()),
+            ];
+
+            let mut tester = Tester::default();
+
+            let remaining_data = tester.guzzle_all(test_data);
+
+            assert_eq!(tester.one, "1".to_string());
+            assert_eq!(tester.two, "2".to_string());
+            assert_eq!(remaining_data, vec![("three", "3".to_string())]);
+        }
+
+        #[test]
+        fn guzzle_all_limited_stops_consuming() {
+            let test_data = vec![
+                ("one", "1".to_string()),
+                ("three", "3".to_string()),
+                ("two", "2".to_string()),
+                ("one", "uno".to_string()),
+                ("two", "dos".to_string()),
+            ];
+
+            let mut tester = Tester::default();
+
+            let remaining_data = tester.guzzle_all_limited(test_data, 2);
+
+            assert_eq!(tester.one, "1".to_string());
+            assert_eq!(tester.two, "2".to_string());
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("three", "3".to_string()),
+                    ("one", "uno".to_string()),
+                    ("two", "dos".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn guzzle_all_limited_to_nothing() {
+            let test_data = vec![("one", "1".to_string())];
+
+            let mut tester = Tester::default();
+
+            assert_eq!(tester.guzzle_all_limited(test_data.clone(), 0), test_data);
+            assert!(tester.one.is_empty());
+        }
+
+        #[test]
+        fn from_metadata_builds_from_default() {
+            let (tester, remaining_data) = Tester::from_metadata(vec![
+                ("one".to_string(), "1".to_string()),
+                ("three".to_string(), "3".to_string()),
+            ]);
+
+            assert_eq!(tester.one, "1".to_string());
+            assert!(tester.two.is_empty());
+            assert_eq!(remaining_data, vec![("three".to_string(), "3".to_string())]);
+        }
+
+        #[test]
+        fn guzzle_all_reserves_leftovers_up_front() {
+            let test_data: Vec<(String, String)> = (0..10_001)
+                .map(|index| (format!("key_{}", index), index.to_string()))
+                .collect();
+
+            let mut tester = Tester::default();
+
+            let remaining_data = tester.guzzle_all(test_data);
+
+            // Growing one push at a time would have left spare capacity behind
+            assert_eq!(remaining_data.len(), 10_001);
+            assert_eq!(remaining_data.capacity(), 10_001);
+        }
+
+        #[test]
+        fn guzzle_with_hash_str_string() {
+            use std::collections::HashMap;
+
+            let test_data: HashMap<String, String> = vec![
+                ("one".to_string(), "1".to_string()),
+                ("two".to_string(), "2".to_string()),
+                ("three".to_string(), "3".to_string()),
+            ]
+            .into_iter()
+            .collect();
+
+            let mut tester = Tester::default();
+
+            let remaining_data: Vec<(String, String)> = test_data
+                .into_iter()
+                .filter_map(|v| tester.guzzle(v))
+                .collect();
+
+            assert_eq!(tester.one, "1".to_string());
+            assert_eq!(tester.two, "2".to_string());
+
+            assert_eq!(remaining_data.len(), 1);
+            assert_eq!(
+                remaining_data.into_iter().next(),
+                Some(("three".to_string(), "3".to_string()))
+            );
+        }
+    }
+
+    mod guzzle_meta_data_derive {
+        use crate::Guzzle;
+
+        fn u64_parser(s: String) -> u64 {
+            s.parse().unwrap()
+        }
+        fn bool_parser(s: String) -> bool {
+            s.parse().unwrap()
+        }
+
+        #[derive(Default, Guzzle)]
+        struct TypeThatAlsoImplementsGuzzle {
+            /// This struct represents some deeply nested data
+            #[guzzle(keys = ["deep_data"], parser = bool_parser)]
+            deeply_nested_data: bool,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct GuzzleExample {
+            /// This field is annotated with no_guzzle, therefore it will not be parsed by guzzle
+            #[no_guzzle]
+            ignored: String,
+
+            /// This field is not annotated, so if a key matches the field name, it will set the
+            /// value
+            basic: String,
+
+            /// This field is annotated, but with no keys, so it uses the field name as a key
+            /// Currently `#[guzzle]` doesn't do anything different to the default behavior (see
+            /// above), but in the future the default behaviour may be toggleable.
+            #[guzzle]
+            basic_too: String,
+
+            /// This field may be filled from multiple keys
+            #[guzzle(keys = ["one", "two"])]
+            listed_keys: String,
+
+            /// This field is not a string, so it is parsed with `FromStr` unless you provide a parser
+            /// that will transform it into the correct type
+            #[guzzle(parser = u64_parser)]
+            other_types: u64,
+
+            /// This field isn't a string and has multiple keys
+            #[guzzle(parser = u64_parser, keys = ["three", "four"])]
+            other_types_with_listed_keys: u64,
+
+            /// Guzzle will wire up this field so that data being guzzled by the `GuzzleExample`
+            /// will first be sent to the `TypeThatAlsoImplementsGuzzle`. If the
+            /// `TypeThatAlsoImplementsGuzzle` consumes the value, `GuzzleExample` will not.
+            #[deep_guzzle]
+            recurse_guzzle_to_populate_this_field: TypeThatAlsoImplementsGuzzle,
+        }
+
+        #[test]
+        fn everything() {
+            let test_data: Vec<(&str, String)> = vec![
+                ("basic", "basic info".to_string()),
+                ("basic_too", "more basic info".to_string()),
+                ("one", "1".to_string()),
+                ("two", "2".to_string()),
+                ("other_types", "20".to_string()),
+                ("three", "3".to_string()),
+                ("four", "4".to_string()),
+                ("ignored", "ignored data".to_string()),
+                ("deep_data", "true".to_string()),
+            ];
+
+            let mut guzzle_example = GuzzleExample::default();
+
+            // This just makes sure deeply_nested_data isn't accidentally set to true
+            assert!(
+                !guzzle_example
+                    .recurse_guzzle_to_populate_this_field
+                    .deeply_nested_data
+            );
+
+            let remaining_data: Vec<(&str, String)> = test_data
+                .into_iter()
+                .filter_map(|v| guzzle_example.guzzle(v))
+                .collect();
+
+            // All appropriate fields are now set
+            assert_eq!(guzzle_example.basic, "basic info".to_string());
+            assert_eq!(guzzle_example.basic_too, "more basic info".to_string());
+            assert_eq!(guzzle_example.listed_keys, "2".to_string());
+            assert_eq!(guzzle_example.other_types, 20);
+            assert_eq!(guzzle_example.other_types_with_listed_keys, 4);
+
+            // Including the deeply nested field
+            assert!(
+                guzzle_example
+                    .recurse_guzzle_to_populate_this_field
+                    .deeply_nested_data
+            );
+
+            // Ignored data is left over
+            assert!(guzzle_example.ignored.is_empty());
+            assert_eq!(
+                remaining_data,
+                vec![("ignored", "ignored data".to_string())]
+            );
+        }
+    }
+
+    mod never_panic {
+        use crate::Guzzle;
+
+        fn bool_parser(s: String) -> Option<bool> {
+            s.parse().ok()
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(never_panic)]
+        struct Row {
+            title: String,
+            #[guzzle(opt_parser = crate::parsers::lenient_u64)]
+            number: u64,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(never_panic)]
+        struct NeverPanics {
+            basic: String,
+            number: u64,
+            #[guzzle(keys = ["flag", "other_flag"], opt_parser = bool_parser)]
+            flag: bool,
+            #[guzzle(try_parser = crate::parsers::from_str)]
+            ratio: f64,
+            #[guzzle(keys = ["tag"], collect)]
+            tags: Vec<u64>,
+            #[deep_guzzle(prefix = "loc_")]
+            location: Row,
+            #[deep_guzzle]
+            rows: Vec<Row>,
+        }
+
+        /// A tiny xorshift generator so we can throw a lot of junk at the guzzler without
+        /// pulling in a dependency
+        struct Junk(u64);
+
+        impl Junk {
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+
+            fn pick<'a>(&mut self, options: &[&'a str]) -> &'a str {
+                options[self.next() as usize % options.len()]
+            }
+
+            fn string(&mut self) -> String {
+                let length = self.next() % 8;
+                (0..length)
+                    .map(|_| self.pick(&["1", "0", "-", "true", "false", " ", "x", "é"]))
+                    .collect()
+            }
+        }
+
+        #[test]
+        fn bad_values_are_left_over() {
+            let test_data: Vec<(&str, String)> = vec![
+                ("number", "not a number".to_string()),
+                ("flag", "not a bool".to_string()),
+                ("number", "20".to_string()),
+            ];
+
+            let mut never_panics = NeverPanics::default();
+
+            let remaining_data: Vec<(&str, String)> = test_data
+                .into_iter()
+                .filter_map(|v| never_panics.guzzle(v))
+                .collect();
+
+            assert_eq!(never_panics.number, 20);
+            assert!(!never_panics.flag);
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("number", "not a number".to_string()),
+                    ("flag", "not a bool".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn random_data_never_panics() {
+            let keys = [
+                "basic",
+                "number",
+                "flag",
+                "other_flag",
+                "ratio",
+                "tag",
+                "loc_title",
+                "loc_number",
+                "rows_0_title",
+                "rows_3_number",
+                "rows_18446744073709551615_title",
+                "rows_18446744073709551616_title",
+                "rows__title",
+                "rows_",
+                "unknown",
+                "",
+            ];
+            let mut junk = Junk(0x2545_f491_4f6c_dd1d);
+            let mut never_panics = NeverPanics::default();
+
+            for _ in 0..2_000 {
+                // Also name rows anywhere an index can reach
+                let key = match junk.next() % 4 {
+                    0 => format!("rows_{}_number", junk.next() >> (junk.next() % 64)),
+                    _ => junk.pick(&keys).to_string(),
+                };
+                let value = junk.string();
+                let expected_value = value.clone();
+
+                match never_panics.guzzle((key.as_str(), value)) {
+                    // Anything handed back must be exactly what we put in
+                    Some((left_key, left_value)) => {
+                        assert_eq!(left_key, key);
+                        assert_eq!(left_value, expected_value);
+                    }
+                    None => assert_ne!(key, "unknown"),
+                }
+                assert!(never_panics.rows.len() <= crate::DEFAULT_MAX_ROWS);
+            }
+        }
+    }
+
+    mod guzzle_schema {
+        use crate::Guzzle;
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(schema)]
+        struct Documented {
+            /// The title of the post
+            title: String,
+
+            #[guzzle(keys = ["one", "two"])]
+            undocumented: String,
+        }
+
+        #[test]
+        fn schema_includes_docs() {
+            assert_eq!(
+                Documented::guzzle_schema(),
+                &[
+                    ("title", "title", "The title of the post"),
+                    ("one", "undocumented", ""),
+                    ("two", "undocumented", ""),
+                ]
+            );
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(schema)]
+        struct Outer {
+            name: String,
+            #[deep_guzzle]
+            documented: Documented,
+        }
+
+        /// Without `schema` nothing is added that could clash with the type's own items
+        #[derive(Default, Guzzle)]
+        struct OwnSchema {
+            name: String,
+        }
+
+        impl OwnSchema {
+            const SCHEMA: &'static str = "own";
+
+            fn guzzle_schema() -> usize {
+                1
+            }
+        }
+
+        const DOCUMENTED_SCHEMA: &[(&str, &str)] = Documented::SCHEMA;
+        const FIRST_KEY: &str = Documented::SCHEMA[0].0;
+
+        #[test]
+        fn schema_is_const() {
+            assert_eq!(
+                DOCUMENTED_SCHEMA,
+                &[
+                    ("title", "title"),
+                    ("one", "undocumented"),
+                    ("two", "undocumented")
+                ]
+            );
+            assert_eq!(FIRST_KEY, "title");
+
+            // Deep guzzle keys aren't included
+            assert_eq!(Outer::SCHEMA, &[("name", "name")]);
+        }
+
+        #[test]
+        fn schema_is_only_generated_when_asked_for() {
+            assert_eq!(OwnSchema::SCHEMA, "own");
+            assert_eq!(OwnSchema::guzzle_schema(), 1);
+        }
+    }
+
+    mod single_key_conversion {
+        use crate::Guzzle;
+        use std::cell::Cell;
+
+        /// A key that counts how many times it has been converted
+        struct CountingKey<'a> {
+            key: &'static str,
+            conversions: &'a Cell<usize>,
+        }
+
+        impl<'a> AsRef<str> for CountingKey<'a> {
+            fn as_ref(&self) -> &str {
+                self.conversions.set(self.conversions.get() + 1);
+                self.key
+            }
+        }
+
+        #[derive(Default, Guzzle)]
+        struct ManyArms {
+            #[guzzle(keys = ["one", "two", "three", "four"])]
+            first: String,
+            #[guzzle(keys = ["five", "six", "seven", "eight"])]
+            second: String,
+        }
+
+        #[test]
+        fn key_is_converted_once() {
+            let conversions = Cell::new(0);
+            let mut many_arms = ManyArms::default();
+
+            let matched = CountingKey {
+                key: "eight",
+                conversions: &conversions,
+            };
+            assert!(many_arms.guzzle((matched, "8".to_string())).is_none());
+            assert_eq!(conversions.get(), 1);
+
+            let unmatched = CountingKey {
+                key: "nine",
+                conversions: &conversions,
+            };
+            assert!(many_arms.guzzle((unmatched, "9".to_string())).is_some());
+            assert_eq!(conversions.get(), 2);
+
+            assert_eq!(many_arms.second, "8".to_string());
+            assert!(many_arms.first.is_empty());
+        }
+    }
+
+    mod when_value {
+        use crate::Guzzle;
+
+        fn flag(_: String) -> bool {
+            true
+        }
+
+        fn flag_with_key((_, _): (String, String)) -> bool {
+            true
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Discriminated {
+            #[guzzle(keys = ["type"], when_value = "post", parser = flag)]
+            is_post: bool,
+            #[guzzle(key_prefix = "show_", when_value = "yes", parser = flag_with_key)]
+            shown: bool,
+        }
+
+        #[test]
+        fn only_matching_values_are_consumed() {
+            let test_data: Vec<(&str, String)> =
+                vec![("type", "page".to_string()), ("type", "post".to_string())];
+
+            let mut discriminated = Discriminated::default();
+
+            let remaining_data: Vec<(&str, String)> = test_data
+                .into_iter()
+                .filter_map(|v| discriminated.guzzle(v))
+                .collect();
+
+            assert!(discriminated.is_post);
+            assert_eq!(remaining_data, vec![("type", "page".to_string())]);
+        }
+
+        #[test]
+        fn prefixed_keys_check_the_value() {
+            let mut discriminated = Discriminated::default();
+            let remaining_data = discriminated.guzzle_all(vec![
+                ("show_title", "no".to_string()),
+                ("show_date", "yes".to_string()),
+            ]);
+
+            assert!(discriminated.shown);
+            assert_eq!(remaining_data, vec![("show_title", "no".to_string())]);
+        }
+    }
+
+    mod explain_key {
+        use crate::{Guzzle, KeyDisposition};
+
+        #[derive(Default, Guzzle)]
+        struct Nested {
+            #[guzzle(keys = ["deep_data"])]
+            deeply_nested_data: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Explained {
+            #[no_guzzle]
+            ignored: String,
+
+            #[guzzle(keys = ["one", "two"])]
+            listed_keys: String,
+
+            #[deep_guzzle]
+            nested: Nested,
+        }
+
+        #[test]
+        fn explain_dispositions() {
+            let explained = Explained::default();
+            assert_eq!(
+                explained.explain_key("two"),
+                KeyDisposition::Field("listed_keys")
+            );
+            assert_eq!(
+                explained.explain_key("ignored"),
+                KeyDisposition::Discarded("ignored")
+            );
+            assert_eq!(
+                explained.explain_key("deep_data"),
+                KeyDisposition::DeepGuzzle(
+                    "nested",
+                    Box::new(KeyDisposition::Field("deeply_nested_data"))
+                )
+            );
+            assert_eq!(
+                explained.explain_key("listed_keys"),
+                KeyDisposition::Unknown
+            );
+
+            // Make sure the explanations agree with what actually happens
+            let mut explained = explained;
+            assert!(explained.guzzle(("ignored", "no".to_string())).is_some());
+            assert!(explained
+                .guzzle(("listed_keys", "no".to_string()))
+                .is_some());
+            assert!(explained.ignored.is_empty());
+            assert!(explained.listed_keys.is_empty());
+            assert!(explained.nested.deeply_nested_data.is_empty());
+        }
+    }
+
+    mod bytes {
+        use crate::{BytesGuzzle, Guzzle};
+
+        fn u16_parser(bytes: Vec<u8>) -> u16 {
+            u16::from_be_bytes([bytes[0], bytes[1]])
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(bytes)]
+        struct Header {
+            #[guzzle(parser = u16_parser)]
+            port: u16,
+            #[guzzle(keys = ["payload", "body"])]
+            payload: Vec<u8>,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(bytes)]
+        struct Packet {
+            #[guzzle(keys = ["kind"], when_value = "ping")]
+            kind: Vec<u8>,
+            #[deep_guzzle]
+            header: Header,
+        }
+
+        #[test]
+        fn guzzle_byte_keys_and_values() {
+            let test_data: Vec<(&[u8], Vec<u8>)> = vec![
+                (b"port", vec![0x1f, 0x90]),
+                (b"body", vec![0xff, 0x00, 0xfe]),
+                (b"kind", b"pong".to_vec()),
+                (b"kind", b"ping".to_vec()),
+                (b"unknown", vec![0x01]),
+            ];
+
+            let mut packet = Packet::default();
+
+            let remaining_data: Vec<(&[u8], Vec<u8>)> = test_data
+                .into_iter()
+                .filter_map(|v| packet.guzzle_bytes(v))
+                .collect();
+
+            assert_eq!(packet.header.port, 8080);
+            assert_eq!(packet.header.payload, vec![0xff, 0x00, 0xfe]);
+            assert_eq!(packet.kind, b"ping".to_vec());
+            assert_eq!(
+                remaining_data,
+                vec![
+                    (&b"kind"[..], b"pong".to_vec()),
+                    (&b"unknown"[..], vec![0x01])
+                ]
+            );
+        }
+    }
+
+    mod guzzle_dynamic {
+        use crate::guzzle_dynamic;
+        use std::collections::HashMap;
+
+        #[test]
+        fn only_allowed_keys_are_consumed() {
+            let test_data = vec![
+                ("one".to_string(), "1".to_string()),
+                ("two".to_string(), "2".to_string()),
+                ("three".to_string(), "3".to_string()),
+                ("one".to_string(), "uno".to_string()),
+            ];
+            let allowed = ["one", "three"];
+
+            let mut target = HashMap::new();
+
+            let remaining_data: Vec<(String, String)> = test_data
+                .into_iter()
+                .filter_map(|v| guzzle_dynamic(&mut target, &allowed, v))
+                .collect();
+
+            assert_eq!(target.len(), 2);
+            assert_eq!(target.get("one"), Some(&"uno".to_string()));
+            assert_eq!(target.get("three"), Some(&"3".to_string()));
+            assert_eq!(remaining_data, vec![("two".to_string(), "2".to_string())]);
+        }
+
+        #[test]
+        fn nothing_allowed() {
+            let mut target = HashMap::new();
+            let pair = ("one".to_string(), "1".to_string());
+            assert_eq!(guzzle_dynamic(&mut target, &[], pair.clone()), Some(pair));
+            assert!(target.is_empty());
+        }
+    }
+
+    mod guzzle_kv_lines {
+        use crate::{guzzle_kv_lines, Guzzle};
+
+        #[derive(Default, Guzzle)]
+        struct Config {
+            name: String,
+            url: String,
+        }
+
+        #[test]
+        fn skips_comments_and_blank_lines() {
+            let text = "
+                # The name of the thing
+                name = guzzle
+
+                #url = ignored
+                other = value
+            ";
+            let mut config = Config::default();
+            let remaining = guzzle_kv_lines(&mut config, text);
+
+            assert_eq!(config.name, "guzzle".to_string());
+            assert_eq!(config.url, String::new());
+            assert_eq!(remaining, vec![("other".to_string(), "value".to_string())]);
+        }
+
+        #[test]
+        fn values_can_contain_equals() {
+            let mut config = Config::default();
+            let remaining = guzzle_kv_lines(&mut config, "url=https://example.com/?a=1&b=2");
+
+            assert_eq!(config.url, "https://example.com/?a=1&b=2".to_string());
+            assert!(remaining.is_empty());
+        }
+
+        #[test]
+        fn lines_without_equals_have_empty_values() {
+            let mut config = Config::default();
+            let remaining = guzzle_kv_lines(&mut config, "name\nflag");
+
+            assert_eq!(config.name, String::new());
+            assert_eq!(remaining, vec![("flag".to_string(), String::new())]);
+        }
+    }
+
+    mod default_if_absent {
+        use crate::{Guzzle, GuzzleState};
+
+        fn u64_parser(s: String) -> u64 {
+            s.parse().unwrap()
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Nested {
+            #[guzzle(keys = ["nested_count"], parser = u64_parser, default_if_absent = "10")]
+            count: u64,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(keys = ["status"], default_if_absent = "draft")]
+            status: String,
+            #[guzzle(keys = ["count"], parser = u64_parser, default_if_absent = "1")]
+            count: u64,
+            title: String,
+            #[deep_guzzle]
+            nested: Nested,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn absent_keys_get_defaults() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![("title", "Hi".to_string())]);
+            assert!(remaining_data.is_empty());
+
+            // Nothing is filled in until we're finished
+            assert!(post.status.is_empty());
+
+            post.finalize();
+            assert_eq!(post.title, "Hi".to_string());
+            assert_eq!(post.status, "draft".to_string());
+            assert_eq!(post.count, 1);
+            assert_eq!(post.nested.count, 10);
+        }
+
+        #[test]
+        fn present_keys_are_kept() {
+            let mut post = Post::default();
+            post.guzzle_all(vec![
+                ("status", "published".to_string()),
+                ("count", "5".to_string()),
+                ("nested_count", "0".to_string()),
+            ]);
+            post.finalize();
+            assert_eq!(post.status, "published".to_string());
+            assert_eq!(post.count, 5);
+            assert_eq!(post.nested.count, 0);
+            assert!(post.state.has_seen("status"));
+            assert!(!post.state.has_seen("title"));
+        }
+    }
+
+    mod collect {
+        use crate::{Guzzle, Unguzzle};
+        use std::collections::HashSet;
+
+        fn u64_parser(s: String) -> u64 {
+            s.parse().unwrap()
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Nested {
+            #[guzzle(keys = ["nested_ids"], split = ",", parser = u64_parser)]
+            ids: Vec<u64>,
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            title: String,
+            #[guzzle(keys = ["tag"], collect)]
+            tags: Vec<String>,
+            #[guzzle(keys = ["categories"], split = "|")]
+            categories: Vec<String>,
+            #[guzzle(collect)]
+            empty: Vec<String>,
+            #[no_guzzle]
+            ignored: String,
+            #[deep_guzzle]
+            nested: Nested,
+        }
+
+        #[test]
+        fn collect_repeated_and_split_values() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("tag", "rust".to_string()),
+                ("tag", "wordpress".to_string()),
+                ("categories", "news|tech".to_string()),
+                ("nested_ids", "1".to_string()),
+                ("nested_ids", "2".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(post.tags, vec!["rust".to_string(), "wordpress".to_string()]);
+            assert_eq!(
+                post.categories,
+                vec!["news".to_string(), "tech".to_string()]
+            );
+            assert_eq!(post.nested.ids, vec![1, 2]);
+        }
+
+        #[test]
+        fn round_trip_through_to_metadata() {
+            let mut post = Post::default();
+            post.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("tag", "rust".to_string()),
+                ("tag", "wordpress".to_string()),
+                ("categories", "news|tech".to_string()),
+                ("nested_ids", "1".to_string()),
+                ("nested_ids", "2".to_string()),
+            ]);
+            post.ignored = "not output".to_string();
+
+            let metadata = post.to_metadata();
+            assert_eq!(
+                metadata,
+                vec![
+                    ("title".to_string(), "Hello".to_string()),
+                    ("tag".to_string(), "rust".to_string()),
+                    ("tag".to_string(), "wordpress".to_string()),
+                    ("categories".to_string(), "news|tech".to_string()),
+                    ("nested_ids".to_string(), "1,2".to_string()),
+                ]
+            );
+
+            // Collected and split fields survive the round trip exactly
+            let (round_tripped, remaining_data) = Post::from_metadata(metadata);
+            assert!(remaining_data.is_empty());
+            assert_eq!(round_tripped.title, post.title);
+            assert_eq!(round_tripped.tags, post.tags);
+            assert_eq!(round_tripped.categories, post.categories);
+            assert_eq!(round_tripped.nested.ids, post.nested.ids);
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Meta {
+            #[guzzle(keys = ["_wp_attached_file"])]
+            files: Vec<String>,
+            #[guzzle(parser = u64_parser)]
+            ids: Vec<u64>,
+        }
+
+        #[test]
+        fn vec_fields_collect_repeated_keys() {
+            let mut meta = Meta::default();
+            let remaining_data = meta.guzzle_all(vec![
+                ("_wp_attached_file", "one.png".to_string()),
+                ("ids", "1".to_string()),
+                ("_wp_attached_file", "two.png".to_string()),
+                ("ids", "2".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(
+                meta.files,
+                vec!["one.png".to_string(), "two.png".to_string()]
+            );
+            assert_eq!(meta.ids, vec![1, 2]);
+            assert_eq!(
+                meta.to_metadata(),
+                vec![
+                    ("_wp_attached_file".to_string(), "one.png".to_string()),
+                    ("_wp_attached_file".to_string(), "two.png".to_string()),
+                    ("ids".to_string(), "1".to_string()),
+                    ("ids".to_string(), "2".to_string()),
+                ]
+            );
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Tagged {
+            #[guzzle(keys = ["tag"], collect)]
+            tags: HashSet<String>,
+            #[guzzle(keys = ["sorted_tag"], collect)]
+            sorted_tags: std::collections::BTreeSet<String>,
+            #[guzzle(keys = ["labels"], split = ",")]
+            labels: HashSet<String>,
+        }
+
+        #[test]
+        fn collect_into_sets() {
+            let mut tagged = Tagged::default();
+            let remaining_data = tagged.guzzle_all(vec![
+                ("tag", "rust".to_string()),
+                ("tag", "wordpress".to_string()),
+                ("tag", "rust".to_string()),
+                ("sorted_tag", "b".to_string()),
+                ("sorted_tag", "a".to_string()),
+                ("sorted_tag", "b".to_string()),
+                ("labels", "x,y,x".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+
+            let expected: HashSet<String> = vec!["rust".to_string(), "wordpress".to_string()]
+                .into_iter()
+                .collect();
+            assert_eq!(tagged.tags, expected);
+            assert_eq!(
+                tagged.sorted_tags.into_iter().collect::<Vec<_>>(),
+                vec!["a".to_string(), "b".to_string()]
+            );
+            assert_eq!(tagged.labels.len(), 2);
+        }
+    }
+
+    mod key_parsers {
+        use crate::Guzzle;
+
+        fn m_parser(s: String) -> f64 {
+            s.parse().unwrap()
+        }
+
+        fn f_parser(s: String) -> f64 {
+            s.parse::<f64>().unwrap() * 0.3048
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Measurement {
+            #[guzzle(key_parsers = [("meters", m_parser), ("feet", f_parser)])]
+            distance: f64,
+        }
+
+        #[test]
+        fn each_key_uses_its_own_parser() {
+            let mut measurement = Measurement::default();
+            assert_eq!(measurement.guzzle(("meters", "2.5".to_string())), None);
+            assert_eq!(measurement.distance, 2.5);
+
+            assert_eq!(measurement.guzzle(("feet", "10".to_string())), None);
+            assert!((measurement.distance - 3.048).abs() < f64::EPSILON);
+
+            // The field name isn't a key unless it's asked for
+            let distance = ("distance", "1".to_string());
+            assert_eq!(measurement.guzzle(distance.clone()), Some(distance));
+        }
+    }
+
+    mod inherent {
+        use crate::{BytesGuzzle, Guzzle};
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(inherent)]
+        struct Post {
+            #[guzzle(keys = ["title", "name"])]
+            title: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(bytes, inherent)]
+        struct Raw {
+            thumbnail: Vec<u8>,
+        }
+
+        #[test]
+        fn guzzle_one_matches_the_trait() {
+            let mut inherent = Post::default();
+            let mut generic = Post::default();
+
+            for (key, value) in &[("name", "Hello"), ("other", "thing")] {
+                assert_eq!(
+                    inherent.guzzle_one(key, value.to_string()),
+                    generic
+                        .guzzle((key, value.to_string()))
+                        .map(|(_, value)| value),
+                );
+                assert_eq!(inherent.title, generic.title);
+            }
+            assert_eq!(inherent.title, "Hello".to_string());
+        }
+
+        #[test]
+        fn guzzle_one_in_bytes_mode() {
+            let mut raw = Raw::default();
+            assert_eq!(raw.guzzle_one(b"thumbnail", vec![1, 2]), None);
+            assert_eq!(raw.guzzle_one(b"other", vec![3]), Some(vec![3]));
+            assert_eq!(raw.thumbnail, vec![1, 2]);
+            assert_eq!(raw.guzzle_bytes((b"thumbnail", vec![4])), None);
+        }
+    }
+
+    mod groups {
+        use crate::{Guzzle, GuzzleState};
+
+        fn f64_parser(s: String) -> f64 {
+            s.parse().unwrap()
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(group(location = ["lat", "lng"]))]
+        struct Place {
+            name: String,
+            #[guzzle(parser = f64_parser)]
+            lat: f64,
+            #[guzzle(parser = f64_parser)]
+            lng: f64,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn incomplete_group_is_left_over() {
+            let mut place = Place::default();
+            let remaining_data = place.guzzle_all(vec![
+                ("lat", "51.5".to_string()),
+                ("name", "London".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(place.name, "London".to_string());
+            assert_eq!(place.lat, 0.0);
+
+            assert_eq!(
+                place.finalize(),
+                vec![("lat".to_string(), "51.5".to_string())]
+            );
+            assert_eq!(place.lat, 0.0);
+            assert_eq!(place.lng, 0.0);
+        }
+
+        #[test]
+        fn from_metadata_finalized_leaves_incomplete_groups_over() {
+            let (place, remaining_data) = Place::from_metadata_finalized(vec![
+                ("lat", "51.5".to_string()),
+                ("colour", "red".to_string()),
+            ]);
+            assert_eq!(place.lat, 0.0);
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("colour".to_string(), "red".to_string()),
+                    ("lat".to_string(), "51.5".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn complete_group_is_consumed() {
+            let mut place = Place::default();
+            let remaining_data = place.guzzle_all(vec![
+                ("lat", "0".to_string()),
+                ("lat", "51.5".to_string()),
+                ("lng", "-0.1".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(place.lat, 51.5);
+            assert_eq!(place.lng, -0.1);
+            assert!(place.finalize().is_empty());
+        }
+    }
+
+    mod rename {
+        use crate::{Guzzle, Unguzzle};
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            #[guzzle(rename = "post_title")]
+            title: String,
+            #[guzzle(rename = "post_status", keys = ["status"])]
+            status: String,
+        }
+
+        #[test]
+        fn guzzle_and_round_trip_with_the_renamed_key() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("post_title", "Hello".to_string()),
+                ("title", "ignored".to_string()),
+                ("status", "draft".to_string()),
+            ]);
+            assert_eq!(post.title, "Hello".to_string());
+            assert_eq!(post.status, "draft".to_string());
+            assert_eq!(remaining_data, vec![("title", "ignored".to_string())]);
+
+            let metadata = post.to_metadata();
+            assert_eq!(
+                metadata,
+                vec![
+                    ("post_title".to_string(), "Hello".to_string()),
+                    ("post_status".to_string(), "draft".to_string()),
+                ]
+            );
+
+            let mut round_tripped = Post::default();
+            assert!(round_tripped.guzzle_all(metadata).is_empty());
+            assert_eq!(round_tripped.title, post.title);
+            assert_eq!(round_tripped.status, post.status);
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    mod toml_guzzle {
+        use crate::{Guzzle, GuzzleState, TomlGuzzle};
+        use std::collections::HashSet;
+        use toml::value::{Table, Value};
+
+        fn u16_parser(s: String) -> u16 {
+            s.parse().unwrap()
+        }
+
+        fn version_parser(value: Value) -> String {
+            match value {
+                Value::Float(float) => format!("v{}", float),
+                other => other.to_string(),
+            }
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(toml)]
+        struct Server {
+            #[guzzle(keys = ["host"])]
+            hostname: String,
+            #[guzzle(parser = u16_parser)]
+            port: u16,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(toml)]
+        struct Config {
+            name: String,
+            retries: u8,
+            ratio: f64,
+            debug: bool,
+            #[guzzle(toml_parser = version_parser)]
+            version: String,
+            #[guzzle(keys = ["tag"], collect)]
+            tags: Vec<String>,
+            #[guzzle(keys = ["label"], collect)]
+            labels: HashSet<String>,
+            #[guzzle(split = ",")]
+            aliases: Vec<String>,
+            #[guzzle(keys = ["column_0"], slot = 0)]
+            #[guzzle(keys = ["column_1"], slot = 1)]
+            columns: [Option<i64>; 2],
+            #[deep_guzzle]
+            server: Server,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        fn table(text: &str) -> Table {
+            toml::from_str(text).unwrap()
+        }
+
+        #[test]
+        fn guzzle_a_mixed_table() {
+            let mut config = Config::default();
+            let remaining = config.guzzle_table(table(
+                r#"
+                    name = "guzzle"
+                    retries = 3
+                    ratio = 0.5
+                    debug = true
+                    version = 1.5
+                    tag = ["rust", "toml"]
+                    label = "single"
+                    aliases = "g,gz"
+                    column_1 = 42
+                    host = "localhost"
+                    port = "8080"
+                    unknown = 1
+                "#,
+            ));
+
+            assert_eq!(config.name, "guzzle".to_string());
+            assert_eq!(config.retries, 3);
+            assert_eq!(config.ratio, 0.5);
+            assert!(config.debug);
+            assert_eq!(config.version, "v1.5".to_string());
+            assert_eq!(config.tags, vec!["rust".to_string(), "toml".to_string()]);
+            assert!(config.labels.contains("single"));
+            assert_eq!(config.aliases, vec!["g".to_string(), "gz".to_string()]);
+            assert_eq!(config.columns, [None, Some(42)]);
+            assert!(config.state.has_seen("columns"));
+            assert_eq!(config.server.hostname, "localhost".to_string());
+            assert_eq!(config.server.port, 8080);
+            assert_eq!(remaining, table("unknown = 1"));
+        }
+
+        #[test]
+        fn values_of_the_wrong_type_are_left_over() {
+            let mut config = Config::default();
+            let remaining = config.guzzle_table(table(
+                r#"
+                    name = 1
+                    retries = 300
+                    tag = ["rust", 2]
+                "#,
+            ));
+
+            assert!(config.name.is_empty());
+            assert_eq!(config.retries, 0);
+            assert!(config.tags.is_empty());
+            assert_eq!(remaining.len(), 3);
+        }
+    }
+
+    mod strict_prefix {
+        use crate::{Guzzle, GuzzleError, TryGuzzle};
+
+        #[derive(Default, Guzzle)]
+        struct Nested {
+            wp_nested: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(strict_prefix = "wp_")]
+        struct Post {
+            #[guzzle(keys = ["wp_title"])]
+            title: String,
+            #[deep_guzzle]
+            nested: Nested,
+        }
+
+        #[test]
+        fn unknown_prefixed_keys_are_errors() {
+            let mut post = Post::default();
+            assert_eq!(post.try_guzzle(("wp_title", "Hello".to_string())), Ok(None));
+            assert_eq!(post.try_guzzle(("wp_nested", "deep".to_string())), Ok(None));
+            assert_eq!(
+                post.try_guzzle(("wp_unknown", "?".to_string())),
+                Err(GuzzleError::UnknownKey("wp_unknown".to_string()))
+            );
+            assert_eq!(
+                post.try_guzzle(("other", "thing".to_string())),
+                Ok(Some(("other", "thing".to_string())))
+            );
+            assert_eq!(post.title, "Hello".to_string());
+            assert_eq!(post.nested.wp_nested, "deep".to_string());
+
+            // Guzzle itself still can't fail, so the key is just left over
+            let unknown = ("wp_unknown", "?".to_string());
+            assert_eq!(post.guzzle(unknown.clone()), Some(unknown));
+        }
+
+        #[test]
+        fn try_guzzle_all_stops_at_the_first_error() {
+            let mut post = Post::default();
+            let result = post.try_guzzle_all(vec![
+                ("other", "thing".to_string()),
+                ("wp_unknown", "?".to_string()),
+                ("wp_title", "Hello".to_string()),
+            ]);
+            assert_eq!(
+                result,
+                Err(GuzzleError::UnknownKey("wp_unknown".to_string()))
+            );
+            assert!(post.title.is_empty());
+
+            let result = post.try_guzzle_all(vec![
+                ("other", "thing".to_string()),
+                ("wp_title", "Hello".to_string()),
+            ]);
+            assert_eq!(result, Ok(vec![("other", "thing".to_string())]));
+        }
+    }
+
+    mod opt_parser {
+        use crate::{Guzzle, GuzzleState};
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(never_panic)]
+        struct Product {
+            #[guzzle(opt_parser = crate::parsers::lenient_f64)]
+            price: f64,
+            #[guzzle(keys = ["stock"], opt_parser = crate::parsers::lenient_u64, collect)]
+            #[guzzle(keys = ["sizes"], opt_parser = crate::parsers::lenient_u64, split = "|")]
+            quantities: Vec<u64>,
+            #[guzzle(opt_parser = crate::parsers::lenient_u64, default_if_absent = "1")]
+            minimum: u64,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn rejected_values_are_left_over() {
+            let mut product = Product::default();
+            let remaining_data = product.guzzle_all(vec![
+                ("price", "$1,234.50".to_string()),
+                ("price", "free".to_string()),
+                ("stock", "1,000".to_string()),
+                ("sizes", "1|2|3".to_string()),
+                ("sizes", "1|two".to_string()),
+            ]);
+            product.finalize();
+
+            assert_eq!(product.price, 1234.5);
+            assert_eq!(product.quantities, vec![1000, 1, 2, 3]);
+            assert_eq!(product.minimum, 1);
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("price", "free".to_string()),
+                    ("sizes", "1|two".to_string())
+                ]
+            );
+        }
+    }
+
+    mod layers {
+        use crate::Guzzle;
+        use std::collections::HashSet;
+
+        #[derive(Default, Guzzle)]
+        struct Config {
+            host: String,
+            port: String,
+            user: String,
+            #[guzzle(keys = ["feature"], collect)]
+            features: HashSet<String>,
+            #[guzzle(keys = ["paths"], split = ":")]
+            paths: Vec<String>,
+            #[guzzle(rest)]
+            rest: Vec<(String, String)>,
+        }
+
+        #[test]
+        fn later_layers_take_precedence() {
+            let defaults = vec![
+                ("host", "localhost".to_string()),
+                ("port", "80".to_string()),
+                ("user", "nobody".to_string()),
+                ("feature", "logging".to_string()),
+            ];
+            let file = vec![
+                ("port", "8080".to_string()),
+                ("feature", "metrics".to_string()),
+                ("paths", "/etc".to_string()),
+                ("unknown", "file".to_string()),
+            ];
+            let env = vec![
+                ("user", "admin".to_string()),
+                ("feature", "logging".to_string()),
+                ("paths", "/home:/tmp".to_string()),
+                ("unknown", "env".to_string()),
+                ("other", "?".to_string()),
+            ];
+
+            let mut config = Config::default();
+            assert!(config.guzzle_layer(defaults).is_empty());
+            assert!(config.guzzle_layer(file).is_empty());
+            assert!(config.guzzle_layer(env).is_empty());
+
+            assert_eq!(config.host, "localhost".to_string());
+            assert_eq!(config.port, "8080".to_string());
+            assert_eq!(config.user, "admin".to_string());
+            let features: HashSet<String> = vec!["logging".to_string(), "metrics".to_string()]
+                .into_iter()
+                .collect();
+            assert_eq!(config.features, features);
+            assert_eq!(
+                config.paths,
+                vec!["/etc".to_string(), "/home".to_string(), "/tmp".to_string()]
+            );
+            // Every layer's unknown keys are kept, even when they share a key
+            assert_eq!(
+                config.rest,
+                vec![
+                    ("unknown".to_string(), "file".to_string()),
+                    ("unknown".to_string(), "env".to_string()),
+                    ("other".to_string(), "?".to_string()),
+                ]
+            );
+        }
+    }
+
+    mod try_parser {
+        use crate::{Guzzle, GuzzleError, TryGuzzle};
+        use std::num::ParseIntError;
+
+        fn u64_parser(s: String) -> Result<u64, ParseIntError> {
+            s.parse()
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+            #[guzzle(try_parser = u64_parser)]
+            views: u64,
+            #[guzzle(keys = ["ids"], try_parser = u64_parser, split = ",")]
+            ids: Vec<u64>,
+        }
+
+        #[test]
+        fn failed_parses_are_errors() {
+            let mut post = Post::default();
+            assert_eq!(post.try_guzzle(("views", "12".to_string())), Ok(None));
+            assert_eq!(post.try_guzzle(("ids", "1,2".to_string())), Ok(None));
+            assert_eq!(
+                post.try_guzzle(("views", "lots".to_string())),
+                Err(GuzzleError::Parse {
+                    key: "views".to_string(),
+                    message: "invalid digit found in string".to_string(),
+                })
+            );
+            assert!(post.try_guzzle(("ids", "3,x".to_string())).is_err());
+            assert_eq!(
+                post.try_guzzle(("other", "thing".to_string())),
+                Ok(Some(("other", "thing".to_string())))
+            );
+            assert_eq!(post.views, 12);
+            assert_eq!(post.ids, vec![1, 2]);
+        }
+
+        #[test]
+        fn guzzle_leaves_failed_parses_over() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("views", "lots".to_string()),
+                ("views", "7".to_string()),
+            ]);
+            assert_eq!(post.title, "Hello".to_string());
+            assert_eq!(post.views, 7);
+            assert_eq!(remaining_data, vec![("views", "lots".to_string())]);
+        }
+    }
+
+    mod option_fields {
+        use crate::{Guzzle, GuzzleState, Unguzzle};
+
+        fn u64_parser(s: String) -> u64 {
+            s.parse().unwrap()
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            title: Option<String>,
+            #[guzzle(parser = u64_parser)]
+            views: Option<u64>,
+            #[guzzle(opt_parser = crate::parsers::lenient_f64)]
+            price: std::option::Option<f64>,
+            #[guzzle(default_if_absent = "draft")]
+            status: Option<String>,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn present_keys_are_some() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("views", "12".to_string()),
+                ("price", "not a price".to_string()),
+            ]);
+            post.finalize();
+
+            assert_eq!(post.title, None);
+            assert_eq!(post.views, Some(12));
+            assert_eq!(post.price, None);
+            assert_eq!(post.status, Some("draft".to_string()));
+            assert_eq!(remaining_data, vec![("price", "not a price".to_string())]);
+
+            // Absent values are left out of the metadata
+            assert_eq!(
+                post.to_metadata(),
+                vec![
+                    ("views".to_string(), "12".to_string()),
+                    ("status".to_string(), "draft".to_string()),
+                ]
+            );
+        }
+    }
+
+    mod rest {
+        use crate::{BytesGuzzle, Guzzle, KeyDisposition, Unguzzle};
+        use std::collections::HashMap;
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            title: String,
+            #[no_guzzle]
+            ignored: String,
+            #[guzzle(rest)]
+            rest: Vec<(String, String)>,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Mapped {
+            title: String,
+            #[guzzle(rest)]
+            rest: HashMap<String, String>,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(bytes)]
+        struct Raw {
+            payload: Vec<u8>,
+            #[guzzle(rest)]
+            rest: Vec<(Vec<u8>, Vec<u8>)>,
+        }
+
+        #[test]
+        fn rest_receives_everything_else() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("ignored", "nope".to_string()),
+                ("other", "thing".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(post.title, "Hello".to_string());
+            assert!(post.ignored.is_empty());
+            assert_eq!(
+                post.rest,
+                vec![
+                    ("ignored".to_string(), "nope".to_string()),
+                    ("other".to_string(), "thing".to_string()),
+                ]
+            );
+            assert_eq!(post.explain_key("other"), KeyDisposition::Field("rest"));
+            assert_eq!(post.explain_key("ignored"), KeyDisposition::Field("rest"));
+
+            // The rest is written back out after the fields
+            assert_eq!(
+                post.to_metadata(),
+                vec![
+                    ("title".to_string(), "Hello".to_string()),
+                    ("ignored".to_string(), "nope".to_string()),
+                    ("other".to_string(), "thing".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn rest_can_be_a_map_or_bytes() {
+            let mut mapped = Mapped::default();
+            assert_eq!(mapped.guzzle(("other", "thing".to_string())), None);
+            assert_eq!(mapped.rest.get("other"), Some(&"thing".to_string()));
+
+            let mut raw = Raw::default();
+            assert_eq!(raw.guzzle_bytes((b"other", vec![1])), None);
+            assert_eq!(raw.rest, vec![(b"other".to_vec(), vec![1])]);
+        }
+    }
+
+    mod from_str {
+        use crate::{Guzzle, GuzzleError, GuzzleState, TryGuzzle};
+
+        fn doubled(s: String) -> u64 {
+            s.parse::<u64>().unwrap() * 2
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Listing {
+            name: String,
+            bedrooms: u8,
+            furnished: bool,
+            rent: f64,
+            #[guzzle(parser = doubled)]
+            deposit: u64,
+            tags: Vec<u32>,
+            floor: Option<i8>,
+            #[guzzle(default_if_absent = "3")]
+            minimum_months: u16,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Strict {
+            #[guzzle(try_parser = crate::parsers::from_str)]
+            count: u32,
+            other: u32,
+        }
+
+        #[test]
+        fn fields_without_parsers_use_from_str() {
+            let mut listing = Listing::default();
+            let remaining_data = listing.guzzle_all(vec![
+                ("name", "Flat".to_string()),
+                ("bedrooms", "2".to_string()),
+                ("furnished", "true".to_string()),
+                ("rent", "950.5".to_string()),
+                ("deposit", "500".to_string()),
+                ("tags", "4".to_string()),
+                ("tags", "7".to_string()),
+                ("floor", "-1".to_string()),
+            ]);
+            listing.finalize();
+
+            assert!(remaining_data.is_empty());
+            assert_eq!(listing.name, "Flat");
+            assert_eq!(listing.bedrooms, 2);
+            assert!(listing.furnished);
+            assert_eq!(listing.rent, 950.5);
+            assert_eq!(listing.deposit, 1000);
+            assert_eq!(listing.tags, vec![4, 7]);
+            assert_eq!(listing.floor, Some(-1));
+            assert_eq!(listing.minimum_months, 3);
+        }
+
+        #[test]
+        fn values_that_dont_parse_are_left_over() {
+            let mut listing = Listing::default();
+            let remaining_data = listing.guzzle_all(vec![
+                ("bedrooms", "lots".to_string()),
+                ("furnished", "yes".to_string()),
+            ]);
+
+            assert_eq!(listing.bedrooms, 0);
+            assert!(!listing.furnished);
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("bedrooms", "lots".to_string()),
+                    ("furnished", "yes".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn try_guzzle_reports_the_from_str_error() {
+            let mut strict = Strict::default();
+            assert_eq!(strict.try_guzzle(("other", "4".to_string())), Ok(None));
+            assert_eq!(strict.other, 4);
+            assert_eq!(
+                strict.try_guzzle(("other", "x".to_string())),
+                Err(GuzzleError::Parse {
+                    key: "other".to_string(),
+                    message: "invalid digit found in string".to_string(),
+                })
+            );
+            assert_eq!(
+                strict.try_guzzle(("count", "".to_string())),
+                Err(GuzzleError::Parse {
+                    key: "count".to_string(),
+                    message: "cannot parse integer from empty string".to_string(),
+                })
+            );
+        }
+    }
+
+    mod prefix {
+        use crate::{Guzzle, KeyDisposition, Unguzzle};
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        #[guzzle(prefix = "location_")]
+        struct Location {
+            lat: f64,
+            lng: f64,
+            #[guzzle(keys = ["name", "title"])]
+            name: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Venue {
+            name: String,
+            #[deep_guzzle]
+            location: Location,
+        }
+
+        #[test]
+        fn every_key_is_prefixed() {
+            let mut venue = Venue::default();
+            let remaining_data = venue.guzzle_all(vec![
+                ("name", "The Hall".to_string()),
+                ("location_lat", "51.5".to_string()),
+                ("location_lng", "-0.1".to_string()),
+                ("location_title", "London".to_string()),
+                ("lat", "0".to_string()),
+            ]);
+
+            assert_eq!(venue.name, "The Hall");
+            assert_eq!(venue.location.lat, 51.5);
+            assert_eq!(venue.location.lng, -0.1);
+            assert_eq!(venue.location.name, "London");
+            assert_eq!(remaining_data, vec![("lat", "0".to_string())]);
+        }
+
+        #[test]
+        fn unguzzle_uses_prefixed_keys() {
+            let location = Location {
+                lat: 1.5,
+                lng: 2.0,
+                name: "Here".to_string(),
+            };
+            assert_eq!(
+                location.to_metadata(),
+                vec![
+                    ("location_lat".to_string(), "1.5".to_string()),
+                    ("location_lng".to_string(), "2".to_string()),
+                    ("location_name".to_string(), "Here".to_string()),
+                ]
+            );
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Inner {
+            a: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(prefix = "p_")]
+        struct Outer {
+            b: String,
+            #[deep_guzzle]
+            inner: Inner,
+            #[deep_guzzle(prefix = "other_")]
+            other: Inner,
+            #[no_guzzle]
+            skipped: String,
+        }
+
+        #[test]
+        fn deep_fields_are_offered_prefixed_keys() {
+            let mut outer = Outer::default();
+            let remaining_data = outer.guzzle_all(vec![
+                ("a", "bare".to_string()),
+                ("p_a", "inner".to_string()),
+                ("p_other_a", "other".to_string()),
+                ("p_b", "b".to_string()),
+                ("p_skipped", "skipped".to_string()),
+            ]);
+
+            assert_eq!(outer.inner.a, "inner");
+            assert_eq!(outer.other.a, "other");
+            assert_eq!(outer.b, "b");
+            assert!(outer.skipped.is_empty());
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("a", "bare".to_string()),
+                    ("p_skipped", "skipped".to_string())
+                ]
+            );
+            // Like any prefixed deep guzzle field, the inner keys can't be listed
+            assert_eq!(Outer::KEYS, ["p_b"]);
+        }
+
+        #[test]
+        fn ignored_fields_are_explained_with_the_prefix() {
+            let outer = Outer::default();
+            assert_eq!(
+                outer.explain_key("p_skipped"),
+                KeyDisposition::Discarded("skipped")
+            );
+            assert_eq!(outer.explain_key("skipped"), KeyDisposition::Unknown);
+        }
+    }
+
+    mod key_prefix {
+        use crate::{Guzzle, KeyDisposition, Unguzzle};
+        use std::collections::BTreeMap;
+
+        fn numbered((suffix, value): (String, String)) -> Option<(u32, String)> {
+            Some((suffix.parse().ok()?, value))
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            gallery_image_count: u32,
+            #[guzzle(key_prefix = "gallery_image_")]
+            gallery: Vec<(String, String)>,
+            #[guzzle(key_prefix = "attachment_", opt_parser = numbered)]
+            attachments: Vec<(u32, String)>,
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Labels {
+            #[guzzle(key_prefix = "label_")]
+            labels: BTreeMap<String, String>,
+        }
+
+        #[test]
+        fn any_key_with_the_prefix_is_consumed() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("gallery_image_count", "2".to_string()),
+                ("gallery_image_0", "cat.jpg".to_string()),
+                ("gallery_image_1", "dog.jpg".to_string()),
+                ("attachment_3", "cv.pdf".to_string()),
+                ("attachment_x", "notes.txt".to_string()),
+                ("gallery", "nope".to_string()),
+            ]);
+
+            assert_eq!(post.gallery_image_count, 2);
+            assert_eq!(
+                post.gallery,
+                vec![
+                    ("0".to_string(), "cat.jpg".to_string()),
+                    ("1".to_string(), "dog.jpg".to_string()),
+                ]
+            );
+            assert_eq!(post.attachments, vec![(3, "cv.pdf".to_string())]);
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("attachment_x", "notes.txt".to_string()),
+                    ("gallery", "nope".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn explain_prefixed_keys() {
+            let post = Post::default();
+            assert_eq!(
+                post.explain_key("gallery_image_count"),
+                KeyDisposition::Field("gallery_image_count")
+            );
+            assert_eq!(
+                post.explain_key("gallery_image_4"),
+                KeyDisposition::Field("gallery")
+            );
+            assert_eq!(post.explain_key("gallery"), KeyDisposition::Unknown);
+        }
+
+        #[test]
+        fn unguzzle_rebuilds_the_keys() {
+            let mut labels = Labels::default();
+            labels.guzzle_all(vec![
+                ("label_b", "two".to_string()),
+                ("label_a", "one".to_string()),
+            ]);
+            assert_eq!(
+                labels.to_metadata(),
+                vec![
+                    ("label_a".to_string(), "one".to_string()),
+                    ("label_b".to_string(), "two".to_string()),
+                ]
+            );
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    mod key_regex {
+        use crate::{Guzzle, KeyDisposition, Unguzzle};
+
+        fn numbered((key, value): (String, String)) -> (u32, String) {
+            (key["attr_".len()..].parse().unwrap(), value)
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Product {
+            attr_count: u32,
+            #[guzzle(key_regex = "^attr_[0-9]+$", parser = numbered)]
+            attributes: Vec<(u32, String)>,
+            #[guzzle(key_regex = "_(colour|color)$")]
+            colours: Vec<(String, String)>,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Flags {
+            #[guzzle(key_regex = "^(show|hide)_", when_value = "yes")]
+            enabled: Vec<(String, String)>,
+        }
+
+        #[test]
+        fn matching_keys_are_consumed() {
+            let mut product = Product::default();
+            let remaining_data = product.guzzle_all(vec![
+                ("attr_count", "2".to_string()),
+                ("attr_0", "cotton".to_string()),
+                ("attr_12", "blue".to_string()),
+                ("attr_x", "no".to_string()),
+                ("trim_colour", "red".to_string()),
+                ("button_color", "black".to_string()),
+            ]);
+
+            assert_eq!(product.attr_count, 2);
+            assert_eq!(
+                product.attributes,
+                vec![(0, "cotton".to_string()), (12, "blue".to_string())]
+            );
+            assert_eq!(
+                product.colours,
+                vec![
+                    ("trim_colour".to_string(), "red".to_string()),
+                    ("button_color".to_string(), "black".to_string()),
+                ]
+            );
+            assert_eq!(remaining_data, vec![("attr_x", "no".to_string())]);
+            assert_eq!(
+                product.explain_key("attr_3"),
+                KeyDisposition::Field("attributes")
+            );
+            assert_eq!(product.explain_key("attr_x"), KeyDisposition::Unknown);
+        }
+
+        #[test]
+        fn matching_keys_check_the_value() {
+            let mut flags = Flags::default();
+            let remaining_data = flags.guzzle_all(vec![
+                ("show_title", "no".to_string()),
+                ("hide_date", "yes".to_string()),
+            ]);
+
+            assert_eq!(
+                flags.enabled,
+                vec![("hide_date".to_string(), "yes".to_string())]
+            );
+            assert_eq!(remaining_data, vec![("show_title", "no".to_string())]);
+        }
+
+        #[test]
+        fn unguzzle_outputs_the_matched_keys() {
+            let mut product = Product::default();
+            product.guzzle_all(vec![("trim_colour", "red".to_string())]);
+            assert_eq!(
+                product.to_metadata(),
+                vec![
+                    ("attr_count".to_string(), "0".to_string()),
+                    ("trim_colour".to_string(), "red".to_string()),
+                ]
+            );
+        }
+    }
+
+    mod rename_all {
+        use crate::{Guzzle, KeyDisposition, Unguzzle};
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        #[guzzle(rename_all = "camelCase")]
+        struct Post {
+            post_title: String,
+            view_count: u32,
+            #[guzzle(keys = ["post_author"])]
+            author: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(rename_all = "SCREAMING_SNAKE_CASE", prefix = "WP_")]
+        struct Env {
+            site_url: String,
+            #[no_guzzle]
+            home_dir: String,
+        }
+
+        #[test]
+        fn field_names_are_renamed() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("postTitle", "Hello".to_string()),
+                ("viewCount", "3".to_string()),
+                ("post_author", "Daniel".to_string()),
+                ("post_title", "Ignored".to_string()),
+            ]);
+
+            assert_eq!(post.post_title, "Hello");
+            assert_eq!(post.view_count, 3);
+            assert_eq!(post.author, "Daniel");
+            assert_eq!(remaining_data, vec![("post_title", "Ignored".to_string())]);
+            assert_eq!(
+                post.to_metadata(),
+                vec![
+                    ("postTitle".to_string(), "Hello".to_string()),
+                    ("viewCount".to_string(), "3".to_string()),
+                    ("post_author".to_string(), "Daniel".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn prefix_is_not_renamed() {
+            let mut env = Env::default();
+            env.guzzle(("WP_SITE_URL", "https://example.com".to_string()));
+            assert_eq!(env.site_url, "https://example.com");
+        }
+
+        #[test]
+        fn ignored_fields_are_explained_with_renamed_keys() {
+            let mut env = Env::default();
+            assert!(env.guzzle(("WP_HOME_DIR", "/home".to_string())).is_some());
+            assert!(env.home_dir.is_empty());
+            assert_eq!(
+                env.explain_key("WP_HOME_DIR"),
+                KeyDisposition::Discarded("home_dir")
+            );
+            assert_eq!(env.explain_key("home_dir"), KeyDisposition::Unknown);
+        }
+    }
+
+    mod deny_unknown_keys {
+        use crate::{Guzzle, GuzzleError, TryGuzzle};
+
+        #[derive(Default, Guzzle)]
+        struct Author {
+            author: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(deny_unknown_keys)]
+        struct Post {
+            title: String,
+            #[guzzle(try_parser = crate::parsers::from_str)]
+            views: u32,
+            #[guzzle(opt_parser = crate::parsers::lenient_u64)]
+            rating: u64,
+            #[deep_guzzle]
+            author: Author,
+        }
+
+        #[test]
+        fn unknown_keys_are_errors() {
+            let mut post = Post::default();
+            assert_eq!(post.try_guzzle(("title", "Hello".to_string())), Ok(None));
+            assert_eq!(post.try_guzzle(("author", "Daniel".to_string())), Ok(None));
+            assert_eq!(
+                post.try_guzzle(("colour", "red".to_string())),
+                Err(GuzzleError::UnknownKey("colour".to_string()))
+            );
+            assert_eq!(post.title, "Hello");
+            assert_eq!(post.author.author, "Daniel");
+
+            // Guzzle itself still hands them back
+            assert_eq!(
+                post.guzzle(("colour", "red".to_string())),
+                Some(("colour", "red".to_string()))
+            );
+        }
+
+        #[test]
+        fn try_guzzle_all_lists_every_unknown_key() {
+            let mut post = Post::default();
+            let result = post.try_guzzle_all(vec![
+                ("colour", "red".to_string()),
+                ("title", "Hello".to_string()),
+                ("size", "big".to_string()),
+            ]);
+            assert_eq!(
+                result,
+                Err(GuzzleError::UnknownKeys(vec![
+                    "colour".to_string(),
+                    "size".to_string()
+                ]))
+            );
+            assert_eq!(post.title, "Hello");
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "Unknown keys: colour, size".to_string()
+            );
+
+            let mut post = Post::default();
+            assert_eq!(
+                post.try_guzzle_all(vec![("title", "Hello".to_string())]),
+                Ok(vec![])
+            );
+        }
+
+        #[test]
+        fn parse_errors_still_stop_try_guzzle_all() {
+            let mut post = Post::default();
+            let result = post.try_guzzle_all(vec![
+                ("colour", "red".to_string()),
+                ("views", "lots".to_string()),
+            ]);
+            assert!(matches!(result, Err(GuzzleError::Parse { .. })));
+        }
+
+        #[test]
+        fn try_guzzle_all_returns_rejected_pairs() {
+            let mut post = Post::default();
+            assert_eq!(
+                post.try_guzzle_all(vec![
+                    ("rating", "lots".to_string()),
+                    ("title", "Hello".to_string()),
+                ]),
+                Ok(vec![("rating", "lots".to_string())])
+            );
+            assert_eq!(post.title, "Hello");
+        }
+    }
+
+    mod try_from_metadata {
+        use crate::{Guzzle, GuzzleError, GuzzleState, TryFromMetadata};
+
+        #[derive(Debug, Default, Guzzle)]
+        struct Location {
+            #[guzzle(required)]
+            lat: f64,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[derive(Debug, Default, Guzzle)]
+        #[guzzle(strict_prefix = "post_")]
+        struct Post {
+            #[guzzle(required, keys = ["post_title"])]
+            title: String,
+            #[guzzle(keys = ["post_views"])]
+            views: u32,
+            #[deep_guzzle]
+            location: Location,
+            #[guzzle(default_if_absent = "publish")]
+            status: String,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn complete_metadata_builds() {
+            let post = Post::try_from_metadata(vec![
+                ("post_title", "Hello".to_string()),
+                ("post_views", "3".to_string()),
+                ("lat", "51.5".to_string()),
+                ("colour", "red".to_string()),
+            ])
+            .unwrap();
+
+            assert_eq!(post.title, "Hello");
+            assert_eq!(post.views, 3);
+            assert_eq!(post.location.lat, 51.5);
+            assert_eq!(post.status, "publish");
+        }
+
+        #[test]
+        fn every_problem_is_reported() {
+            let error = Post::try_from_metadata(vec![
+                ("post_views", "lots".to_string()),
+                ("post_colour", "red".to_string()),
+            ])
+            .unwrap_err();
+
+            assert_eq!(
+                error,
+                GuzzleError::Invalid {
+                    missing: vec!["post_title".to_string(), "lat".to_string()],
+                    errors: vec![
+                        GuzzleError::Parse {
+                            key: "post_views".to_string(),
+                            message: "invalid digit found in string".to_string(),
+                        },
+                        GuzzleError::UnknownKey("post_colour".to_string()),
+                    ],
+                }
+            );
+            assert_eq!(
+                error.to_string(),
+                "Invalid metadata, missing keys: post_title, lat; Could not parse the value for \
+                 post_views: invalid digit found in string; Unknown key: post_colour"
+            );
+        }
+    }
+
+    mod deep_prefix {
+        use crate::{Guzzle, KeyDisposition, Unguzzle};
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Location {
+            lat: String,
+            lng: String,
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Event {
+            name: String,
+            #[deep_guzzle(prefix = "venue_")]
+            venue: Location,
+            #[deep_guzzle(prefix = "parking_")]
+            parking: Location,
+        }
+
+        #[test]
+        fn keys_are_forwarded_without_the_prefix() {
+            let mut event = Event::default();
+            let remaining_data = event.guzzle_all(vec![
+                ("name", "Launch".to_string()),
+                ("venue_lat", "51.5".to_string()),
+                ("venue_lng", "-0.1".to_string()),
+                ("parking_lat", "51.6".to_string()),
+                ("parking_colour", "blue".to_string()),
+                ("lat", "0".to_string()),
+            ]);
+
+            assert_eq!(event.name, "Launch");
+            assert_eq!(event.venue.lat, "51.5");
+            assert_eq!(event.venue.lng, "-0.1");
+            assert_eq!(event.parking.lat, "51.6");
+            assert!(event.parking.lng.is_empty());
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("parking_colour", "blue".to_string()),
+                    ("lat", "0".to_string())
+                ]
+            );
+        }
+
+        #[test]
+        fn explain_and_unguzzle_use_the_prefix() {
+            let mut event = Event::default();
+            event.guzzle_all(vec![
+                ("venue_lat", "51.5".to_string()),
+                ("parking_lng", "-0.2".to_string()),
+            ]);
+
+            assert_eq!(
+                event.explain_key("venue_lat"),
+                KeyDisposition::DeepGuzzle("venue", Box::new(KeyDisposition::Field("lat")))
+            );
+            assert_eq!(event.explain_key("lat"), KeyDisposition::Unknown);
+            assert_eq!(
+                event.to_metadata(),
+                vec![
+                    ("name".to_string(), "".to_string()),
+                    ("venue_lat".to_string(), "51.5".to_string()),
+                    ("venue_lng".to_string(), "".to_string()),
+                    ("parking_lat".to_string(), "".to_string()),
+                    ("parking_lng".to_string(), "-0.2".to_string()),
+                ]
+            );
+        }
+    }
+
+    mod deep_option {
+        use crate::{Guzzle, GuzzleState, KeyDisposition, Unguzzle};
+
+        #[derive(Debug, Default, PartialEq, Guzzle, Unguzzle)]
+        struct Location {
+            #[guzzle(required)]
+            lat: String,
+            lng: String,
+            #[guzzle(default_if_absent = "wgs84")]
+            datum: String,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[derive(Debug, Default, Guzzle, Unguzzle)]
+        struct Post {
+            title: String,
+            #[deep_guzzle]
+            location: Option<Location>,
+            #[deep_guzzle(prefix = "home_")]
+            home: Option<Location>,
+        }
+
+        #[test]
+        fn stays_none_without_nested_keys() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("colour", "red".to_string()),
+            ]);
+            post.finalize();
+
+            assert_eq!(post.title, "Hello");
+            assert_eq!(post.location, None);
+            assert_eq!(post.home, None);
+            assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+            assert_eq!(post.remaining_required(), 0);
+            assert!(post.missing_keys().is_empty());
+            assert_eq!(
+                post.explain_key("lat"),
+                KeyDisposition::DeepGuzzle("location", Box::new(KeyDisposition::Field("lat")))
+            );
+            assert_eq!(
+                post.to_metadata(),
+                vec![("title".to_string(), "Hello".to_string())]
+            );
+        }
+
+        #[test]
+        fn created_on_the_first_nested_key() {
+            let mut post = Post::default();
+            post.guzzle_all(vec![
+                ("lng", "-0.1".to_string()),
+                ("home_lat", "51.5".to_string()),
+            ]);
+            post.finalize();
+
+            let location = post.location.as_ref().unwrap();
+            assert_eq!(location.lng, "-0.1");
+            assert_eq!(location.datum, "wgs84");
+            assert_eq!(post.home.as_ref().unwrap().lat, "51.5");
+            assert_eq!(post.remaining_required(), 1);
+            assert_eq!(post.missing_keys(), vec!["lat"]);
+        }
+    }
+
+    mod repeater {
+        use crate::{split_indexed_key, Guzzle, KeyDisposition, Unguzzle, DEFAULT_MAX_ROWS};
+
+        #[derive(Debug, Default, PartialEq, Guzzle, Unguzzle)]
+        struct Row {
+            title: String,
+            image: String,
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Page {
+            #[guzzle(keys = ["rows"])]
+            row_count: u32,
+            #[deep_guzzle]
+            rows: Vec<Row>,
+            #[deep_guzzle(prefix = "slides_", max_rows = 2)]
+            carousel: Vec<Row>,
+        }
+
+        #[test]
+        fn split_keys() {
+            assert_eq!(
+                split_indexed_key("rows_0_title", "rows_"),
+                Some((0, "title"))
+            );
+            assert_eq!(
+                split_indexed_key("rows_12_image_alt", "rows_"),
+                Some((12, "image_alt"))
+            );
+            assert_eq!(split_indexed_key("rows_", "rows_"), None);
+            assert_eq!(split_indexed_key("rows_x_title", "rows_"), None);
+            assert_eq!(split_indexed_key("rows_+1_title", "rows_"), None);
+            assert_eq!(split_indexed_key("rows__title", "rows_"), None);
+        }
+
+        #[test]
+        fn rows_are_guzzled_by_index() {
+            let mut page = Page::default();
+            let remaining_data = page.guzzle_all(vec![
+                ("rows", "2".to_string()),
+                ("rows_1_title", "Second".to_string()),
+                ("rows_0_title", "First".to_string()),
+                ("rows_0_image", "first.jpg".to_string()),
+                ("rows_2_colour", "red".to_string()),
+                ("slides_0_image", "slide.jpg".to_string()),
+            ]);
+
+            assert_eq!(page.row_count, 2);
+            assert_eq!(
+                page.rows,
+                vec![
+                    Row {
+                        title: "First".to_string(),
+                        image: "first.jpg".to_string()
+                    },
+                    Row {
+                        title: "Second".to_string(),
+                        image: "".to_string()
+                    },
+                ]
+            );
+            assert_eq!(
+                page.carousel,
+                vec![Row {
+                    title: "".to_string(),
+                    image: "slide.jpg".to_string()
+                }]
+            );
+            // Rows that nothing was consumed into aren't kept
+            assert_eq!(remaining_data, vec![("rows_2_colour", "red".to_string())]);
+        }
+
+        #[test]
+        fn overflowing_indexes_are_handed_back() {
+            let mut page = Page::default();
+            let remaining_data = page.guzzle_all(vec![(
+                "rows_18446744073709551615_title",
+                "Last".to_string(),
+            )]);
+
+            assert!(page.rows.is_empty());
+            assert_eq!(
+                remaining_data,
+                vec![("rows_18446744073709551615_title", "Last".to_string())]
+            );
+            assert_eq!(
+                page.explain_key("rows_18446744073709551615_title"),
+                KeyDisposition::Unknown
+            );
+        }
+
+        #[test]
+        fn rows_past_the_limit_are_handed_back() {
+            let mut page = Page::default();
+            let last_row = format!("rows_{}_title", DEFAULT_MAX_ROWS - 1);
+            let past_the_limit = format!("rows_{}_title", DEFAULT_MAX_ROWS);
+            let remaining_data = page.guzzle_all(vec![
+                ("rows_50000000_title", "Huge".to_string()),
+                (past_the_limit.as_str(), "Too far".to_string()),
+                (last_row.as_str(), "Last".to_string()),
+                ("slides_1_title", "Second".to_string()),
+                ("slides_2_title", "Third".to_string()),
+            ]);
+
+            assert_eq!(page.rows.len(), DEFAULT_MAX_ROWS);
+            assert_eq!(page.rows[DEFAULT_MAX_ROWS - 1].title, "Last");
+            assert_eq!(page.carousel.len(), 2);
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("rows_50000000_title", "Huge".to_string()),
+                    (past_the_limit.as_str(), "Too far".to_string()),
+                    ("slides_2_title", "Third".to_string()),
+                ]
+            );
+            assert_eq!(page.explain_key("slides_2_title"), KeyDisposition::Unknown);
+        }
+
+        #[test]
+        fn explain_and_unguzzle_rows() {
+            let mut page = Page::default();
+            page.guzzle_all(vec![
+                ("rows_0_title", "First".to_string()),
+                ("slides_1_image", "slide.jpg".to_string()),
+            ]);
+
+            assert_eq!(
+                page.explain_key("rows_5_title"),
+                KeyDisposition::DeepGuzzle("rows", Box::new(KeyDisposition::Field("title")))
+            );
+            assert_eq!(page.explain_key("rows_5_colour"), KeyDisposition::Unknown);
+            assert_eq!(
+                page.to_metadata(),
+                vec![
+                    ("rows".to_string(), "0".to_string()),
+                    ("rows_0_title".to_string(), "First".to_string()),
+                    ("rows_0_image".to_string(), "".to_string()),
+                    ("slides_0_title".to_string(), "".to_string()),
+                    ("slides_0_image".to_string(), "".to_string()),
+                    ("slides_1_title".to_string(), "".to_string()),
+                    ("slides_1_image".to_string(), "slide.jpg".to_string()),
+                ]
+            );
+        }
+    }
+
+    mod enums {
+        use crate::Guzzle;
+
+        #[derive(Debug, Default, PartialEq, Guzzle)]
+        #[guzzle(tag = "layout_type")]
+        enum Layout {
+            #[default]
+            Empty,
+            Hero {
+                title: String,
+                #[guzzle(keys = ["hero_image"])]
+                image: String,
+                height: u32,
+            },
+            #[guzzle(rename = "gallery")]
+            Gallery {
+                #[guzzle(keys = ["image"])]
+                images: Vec<String>,
+            },
+        }
+
+        #[test]
+        fn tag_chooses_the_variant() {
+            let mut layout = Layout::default();
+            let remaining_data = layout.guzzle_all(vec![
+                ("title", "Ignored".to_string()),
+                ("layout_type", "Hero".to_string()),
+                ("title", "Welcome".to_string()),
+                ("hero_image", "hero.jpg".to_string()),
+                ("height", "300".to_string()),
+                ("image", "unknown.jpg".to_string()),
+            ]);
+
+            assert_eq!(
+                layout,
+                Layout::Hero {
+                    title: "Welcome".to_string(),
+                    image: "hero.jpg".to_string(),
+                    height: 300,
+                }
+            );
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("title", "Ignored".to_string()),
+                    ("image", "unknown.jpg".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn repeating_the_tag_keeps_the_fields() {
+            let mut layout = Layout::default();
+            let remaining_data = layout.guzzle_all(vec![
+                ("layout_type", "gallery".to_string()),
+                ("image", "one.jpg".to_string()),
+                ("layout_type", "gallery".to_string()),
+                ("image", "two.jpg".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(
+                layout,
+                Layout::Gallery {
+                    images: vec!["one.jpg".to_string(), "two.jpg".to_string()]
+                }
+            );
+
+            // Switching variant starts again from the defaults
+            assert_eq!(layout.guzzle(("layout_type", "Empty".to_string())), None);
+            assert_eq!(layout, Layout::Empty);
+        }
+
+        #[test]
+        fn unknown_tags_are_left_over() {
+            let mut layout = Layout::default();
+            assert_eq!(
+                layout.guzzle(("layout_type", "Carousel".to_string())),
+                Some(("layout_type", "Carousel".to_string()))
+            );
+            assert_eq!(layout, Layout::Empty);
+        }
+    }
+
+    mod tuples {
+        use crate::Guzzle;
+
+        #[derive(Default, Guzzle)]
+        struct Title(#[guzzle(keys = ["title", "post_title"])] String);
+
+        #[derive(Default, Guzzle)]
+        struct Inner {
+            colour: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Wrapper(#[deep_guzzle] Inner);
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(prefix = "size_")]
+        struct Dimensions(
+            #[guzzle(keys = ["width"])] u32,
+            #[no_guzzle] String,
+            #[guzzle(keys = ["height"])] u32,
+        );
+
+        #[test]
+        fn newtype_with_keys() {
+            let mut title = Title::default();
+            let remaining_data = title.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("post_title", "Hello, World".to_string()),
+                ("field_0", "Nope".to_string()),
+            ]);
+            assert_eq!(title.0, "Hello, World");
+            assert_eq!(remaining_data, vec![("field_0", "Nope".to_string())]);
+        }
+
+        #[test]
+        fn newtype_delegates() {
+            let mut wrapper = Wrapper::default();
+            let remaining_data = wrapper.guzzle_all(vec![
+                ("colour", "red".to_string()),
+                ("size", "large".to_string()),
+            ]);
+            assert_eq!(wrapper.0.colour, "red");
+            assert_eq!(remaining_data, vec![("size", "large".to_string())]);
+        }
+
+        #[test]
+        fn positional_fields() {
+            let mut dimensions = Dimensions::default();
+            let remaining_data = dimensions.guzzle_all(vec![
+                ("size_width", "640".to_string()),
+                ("size_height", "480".to_string()),
+                ("width", "1".to_string()),
+            ]);
+            assert_eq!(
+                (dimensions.0, dimensions.1.as_str(), dimensions.2),
+                (640, "", 480)
+            );
+            assert_eq!(remaining_data, vec![("width", "1".to_string())]);
+        }
+    }
+
+    mod value_type {
+        use crate::Guzzle;
+
+        #[derive(Clone, Debug, PartialEq)]
+        enum Cell {
+            Int(i64),
+            Text(String),
+        }
+
+        impl Default for Cell {
+            fn default() -> Self {
+                Cell::Text(String::new())
+            }
+        }
+
+        fn int_parser(cell: Cell) -> Option<i64> {
+            match cell {
+                Cell::Int(int) => Some(int),
+                Cell::Text(_) => None,
+            }
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(value = Cell)]
+        struct Dimensions {
+            #[guzzle(opt_parser = int_parser)]
+            width: i64,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(value = Cell, inherent)]
+        struct Row {
+            label: Cell,
+            #[guzzle(opt_parser = int_parser)]
+            count: i64,
+            #[deep_guzzle]
+            dimensions: Dimensions,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(value = Vec<u8>)]
+        struct Blob(#[guzzle(keys = ["data"])] Vec<u8>);
+
+        #[test]
+        fn values_are_not_strings() {
+            let mut row = Row::default();
+            let remaining_data = row.guzzle_all(vec![
+                ("label", Cell::Text("Hello".to_string())),
+                ("count", Cell::Int(3)),
+                ("width", Cell::Int(640)),
+                ("count", Cell::Text("three".to_string())),
+                ("colour", Cell::Text("red".to_string())),
+            ]);
+            assert_eq!(row.label, Cell::Text("Hello".to_string()));
+            assert_eq!(row.count, 3);
+            assert_eq!(row.dimensions.width, 640);
+            assert_eq!(
+                remaining_data,
+                vec![
+                    ("count", Cell::Text("three".to_string())),
+                    ("colour", Cell::Text("red".to_string())),
+                ]
+            );
+            assert_eq!(row.guzzle_one("label", Cell::Int(1)), None);
+        }
+
+        #[test]
+        fn tuple_structs_use_the_value_type() {
+            let (blob, remaining_data) = Blob::from_metadata(vec![("data", vec![1, 2, 3])]);
+            assert_eq!(blob.0, vec![1, 2, 3]);
+            assert!(remaining_data.is_empty());
+        }
+    }
+
+    mod borrowed {
+        use crate::Guzzle;
+        use std::borrow::Cow;
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(borrowed)]
+        struct Post<'a> {
+            title: &'a str,
+            excerpt: Cow<'a, str>,
+            author: Option<&'a str>,
+            slug: String,
+            views: u32,
+        }
+
+        #[test]
+        fn fields_borrow_the_values() {
+            let rows = [
+                ("title".to_string(), "Hello".to_string()),
+                ("excerpt".to_string(), "Hi".to_string()),
+                ("author".to_string(), "Daniel".to_string()),
+                ("slug".to_string(), "hello".to_string()),
+                ("views".to_string(), "20".to_string()),
+                ("views".to_string(), "lots".to_string()),
+                ("colour".to_string(), "red".to_string()),
+            ];
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(
+                rows.iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str())),
+            );
+            assert_eq!(post.title, "Hello");
+            assert!(matches!(post.excerpt, Cow::Borrowed("Hi")));
+            assert_eq!(post.author, Some("Daniel"));
+            assert_eq!(post.slug, "hello".to_string());
+            assert_eq!(post.views, 20);
+            assert_eq!(remaining_data, vec![("views", "lots"), ("colour", "red")]);
+        }
+    }
+
+    mod guzzle_ref {
+        use crate::Guzzle;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Default, Guzzle)]
+        struct Inner {
+            colour: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+            #[guzzle(parser = u32_parser)]
+            views: u32,
+            #[no_guzzle]
+            ignored: String,
+            #[deep_guzzle(prefix = "inner_")]
+            inner: Inner,
+        }
+
+        fn u32_parser(s: String) -> u32 {
+            s.parse().unwrap_or_default()
+        }
+
+        #[test]
+        fn borrowed_keys_and_values() {
+            let mut post = Post::default();
+            assert!(post.guzzle_ref("title", "Hello"));
+            assert!(post.guzzle_ref("views", "12"));
+            assert!(post.guzzle_ref("inner_colour", "red"));
+            assert!(!post.guzzle_ref("ignored", "nope"));
+            assert!(!post.guzzle_ref("colour", "blue"));
+
+            assert_eq!(post.title, "Hello");
+            assert_eq!(post.views, 12);
+            assert_eq!(post.inner.colour, "red");
+            assert!(post.ignored.is_empty());
+        }
+
+        static DEFAULTS: AtomicUsize = AtomicUsize::new(0);
+
+        /// Counts how many times it's been made, to catch keys being checked against a default
+        #[derive(Guzzle)]
+        struct Counted {
+            colour: String,
+        }
+
+        impl Default for Counted {
+            fn default() -> Self {
+                DEFAULTS.fetch_add(1, Ordering::SeqCst);
+                Counted {
+                    colour: String::new(),
+                }
+            }
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Page {
+            #[deep_guzzle(prefix = "hero_")]
+            hero: Option<Counted>,
+            #[deep_guzzle]
+            rows: Vec<Counted>,
+            #[guzzle(key_prefix = "og_")]
+            open_graph: Vec<(String, String)>,
+        }
+
+        #[test]
+        fn keys_are_checked_without_an_instance() {
+            assert!(Page::may_consume("hero_colour"));
+            assert!(Page::may_consume("rows_3_colour"));
+            assert!(Page::may_consume("og_title"));
+            assert!(!Page::may_consume("colour"));
+            assert!(!Page::may_consume("hero_size"));
+            assert!(!Page::may_consume("rows_3_size"));
+
+            let mut page = Page::default();
+            for key in &["colour", "hero_size", "rows_0_size", "rows_x_colour"] {
+                assert!(!page.guzzle_ref(key, "red"));
+            }
+            assert_eq!(DEFAULTS.load(Ordering::SeqCst), 0);
+
+            assert!(page.guzzle_ref("rows_0_colour", "red"));
+            assert_eq!(page.rows[0].colour, "red");
+        }
+    }
+
+    mod guzzle_dyn {
+        use crate::{Guzzle, GuzzleDyn, GuzzleState, KeyDisposition};
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(group(location = ["lat", "lng"]))]
+        struct Location {
+            #[guzzle(state)]
+            state: GuzzleState,
+            name: String,
+            lat: String,
+            lng: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+        }
+
+        #[test]
+        fn pipelines_offer_each_stage_in_turn() {
+            let mut pipeline: Vec<Box<dyn GuzzleDyn>> =
+                vec![Box::new(Post::default()), Box::new(Location::default())];
+            let remaining_data = pipeline.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("lat", "51.5".to_string()),
+                ("colour", "red".to_string()),
+            ]);
+            assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+            assert_eq!(
+                pipeline.explain_key("title"),
+                KeyDisposition::Field("title")
+            );
+            assert_eq!(pipeline.explain_key("lat"), KeyDisposition::Field("lat"));
+            assert_eq!(pipeline.explain_key("colour"), KeyDisposition::Unknown);
+
+            // The location never got its lng, so its lat is handed back
+            assert_eq!(
+                pipeline.finalize(),
+                vec![("lat".to_string(), "51.5".to_string())]
+            );
+        }
+    }
+
+    mod option_impl {
+        use crate::{Guzzle, KeyDisposition};
+
+        #[derive(Debug, Default, PartialEq, Guzzle)]
+        struct Seo {
+            description: String,
+        }
+
+        #[test]
+        fn created_on_first_consumed_pair() {
+            let mut seo: Option<Seo> = None;
+            assert_eq!(
+                seo.explain_key("description"),
+                KeyDisposition::Field("description")
+            );
+            assert_eq!(
+                seo.guzzle(("title", "Hello".to_string())),
+                Some(("title", "Hello".to_string()))
+            );
+            assert_eq!(seo, None);
+
+            let remaining_data = seo.guzzle_all(vec![("description", "Hi".to_string())]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(
+                seo,
+                Some(Seo {
+                    description: "Hi".to_string()
+                })
+            );
+        }
+    }
+
+    mod pointers {
+        use crate::Guzzle;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+        }
+
+        fn guzzle_title<G: Guzzle>(guzzler: &mut G) -> Vec<(&'static str, String)> {
+            guzzler.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("colour", "red".to_string()),
+            ])
+        }
+
+        #[test]
+        fn boxed_and_borrowed() {
+            let mut post = Box::new(Post::default());
+            assert_eq!(guzzle_title(&mut post), vec![("colour", "red".to_string())]);
+            assert_eq!(post.title, "Hello");
+
+            let mut post = Post::default();
+            let mut borrowed = &mut post;
+            assert_eq!(
+                guzzle_title(&mut borrowed),
+                vec![("colour", "red".to_string())]
+            );
+            assert!(borrowed.guzzle_ref("title", "Bye"));
+            assert_eq!(post.title, "Bye");
+        }
+
+        #[test]
+        fn shared() {
+            let post = Rc::new(RefCell::new(Post::default()));
+            assert_eq!(
+                guzzle_title(&mut post.clone()),
+                vec![("colour", "red".to_string())]
+            );
+            assert_eq!(post.borrow().title, "Hello");
+
+            let post = Arc::new(Mutex::new(Post::default()));
+            assert_eq!(
+                guzzle_title(&mut post.clone()),
+                vec![("colour", "red".to_string())]
+            );
+            assert_eq!(post.lock().unwrap().title, "Hello");
+        }
+    }
+
+    mod tuple_impls {
+        use crate::{Guzzle, GuzzleState, KeyDisposition};
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Author {
+            #[guzzle(state)]
+            state: GuzzleState,
+            #[guzzle(required)]
+            name: String,
+            #[no_guzzle]
+            title: String,
+        }
+
+        #[test]
+        fn each_element_in_turn() {
+            let mut guzzlers = (Author::default(), Post::default());
+            assert_eq!(guzzlers.missing_keys(), vec!["name"]);
+            assert_eq!(
+                guzzlers.explain_key("title"),
+                KeyDisposition::Field("title")
+            );
+
+            let remaining_data = guzzlers.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("name", "Daniel".to_string()),
+                ("colour", "red".to_string()),
+            ]);
+            assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+
+            let (author, post) = guzzlers;
+            assert_eq!(post.title, "Hello");
+            assert_eq!(author.name, "Daniel");
+            assert!(author.title.is_empty());
+            assert_eq!(author.remaining_required(), 0);
+        }
+    }
+
+    mod chain {
+        use crate::{chain, Guzzle, KeyDisposition};
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+            #[no_guzzle]
+            name: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Author {
+            name: String,
+        }
+
+        #[test]
+        fn chains_of_chains() {
+            let mut chained = chain(chain(Post::default(), Author::default()), Author::default());
+            assert_eq!(chained.explain_key("name"), KeyDisposition::Field("name"));
+            assert_eq!(chained.guzzle(("name", "Daniel".to_string())), None);
+            assert_eq!(chained.first.second.name, "Daniel");
+            assert!(chained.first.first.name.is_empty());
+            assert!(chained.second.name.is_empty());
+        }
+    }
+
+    mod iterator_ext {
+        use crate::{Guzzle, GuzzleIteratorExt};
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+            author: String,
+        }
+
+        #[test]
+        fn guzzle_into_is_lazy() {
+            let mut post = Post::default();
+            let mut leftovers = vec![
+                ("title", "Hello".to_string()),
+                ("colour", "red".to_string()),
+                ("author", "Daniel".to_string()),
+            ]
+            .into_iter()
+            .guzzle_into(&mut post);
+
+            assert_eq!(leftovers.next(), Some(("colour", "red".to_string())));
+            drop(leftovers);
+            assert_eq!(post.title, "Hello");
+            assert!(post.author.is_empty());
+        }
+    }
+
+    mod guzzle_drain {
+        use crate::Guzzle;
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Author {
+            name: String,
+        }
+
+        #[test]
+        fn guzzlers_take_turns_over_a_buffer() {
+            let mut buffer = vec![
+                ("name", "Daniel".to_string()),
+                ("colour", "red".to_string()),
+                ("title", "Hello".to_string()),
+                ("size", "large".to_string()),
+            ];
+            let capacity = buffer.capacity();
+
+            let mut post = Post::default();
+            post.guzzle_drain(&mut buffer);
+            assert_eq!(post.title, "Hello");
+            assert_eq!(buffer.len(), 3);
+
+            let mut author = Author::default();
+            author.guzzle_drain(&mut buffer);
+            assert_eq!(author.name, "Daniel");
+            assert_eq!(
+                buffer,
+                vec![("colour", "red".to_string()), ("size", "large".to_string())]
+            );
+            assert_eq!(buffer.capacity(), capacity);
+        }
+    }
+
+    mod guzzle_map {
+        use crate::Guzzle;
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Default, Guzzle)]
+        struct Inner {
+            colour: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(keys = ["title", "post_title"])]
+            title: String,
+            #[deep_guzzle]
+            inner: Inner,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Wrapper {
+            #[deep_guzzle]
+            post: Post,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Prefixed {
+            #[deep_guzzle(prefix = "inner_")]
+            inner: Inner,
+        }
+
+        #[test]
+        fn keys_are_looked_up() {
+            let mut map: HashMap<String, String> = vec![
+                ("title".to_string(), "Hello".to_string()),
+                ("colour".to_string(), "red".to_string()),
+                ("size".to_string(), "large".to_string()),
+            ]
+            .into_iter()
+            .collect();
+
+            let mut wrapper = Wrapper::default();
+            wrapper.guzzle_map(&mut map);
+            assert_eq!(wrapper.post.title, "Hello");
+            assert_eq!(wrapper.post.inner.colour, "red");
+            assert_eq!(map.len(), 1);
+            assert_eq!(map.get("size"), Some(&"large".to_string()));
+        }
+
+        #[test]
+        fn everything_else_is_scanned() {
+            let mut map = BTreeMap::new();
+            map.insert("inner_colour".to_string(), "red".to_string());
+            map.insert("colour".to_string(), "blue".to_string());
+
+            let mut prefixed = Prefixed::default();
+            prefixed.guzzle_map(&mut map);
+            assert_eq!(prefixed.inner.colour, "red");
+            assert_eq!(
+                map.into_iter().collect::<Vec<_>>(),
+                vec![("colour".into(), "blue".into())]
+            );
+        }
+    }
+
+    mod keys_const {
+        use crate::Guzzle;
+
+        #[derive(Default, Guzzle)]
+        struct Seo {
+            description: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Image {
+            url: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Page {
+            #[guzzle(keys = ["title", "post_title"])]
+            title: String,
+            #[deep_guzzle]
+            seo: Option<Seo>,
+            #[deep_guzzle(prefix = "hero_")]
+            hero: Image,
+            #[deep_guzzle]
+            rows: Vec<Image>,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Site {
+            name: String,
+            #[deep_guzzle]
+            page: Page,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Generic<G: Guzzle> {
+            name: String,
+            #[deep_guzzle]
+            inner: G,
+        }
+
+        // Usable in a const context
+        const SITE_KEYS: &[&str] = <Site as Guzzle>::KEYS;
+
+        #[test]
+        fn keys_include_deep_fields() {
+            assert_eq!(<Seo as Guzzle>::KEYS, &["description"]);
+            assert_eq!(
+                <Page as Guzzle>::KEYS,
+                &["title", "post_title", "description"]
+            );
+            assert_eq!(SITE_KEYS, &["name", "title", "post_title", "description"]);
+            assert_eq!(<Generic<Seo> as Guzzle>::KEYS, &["name"]);
+        }
+    }
+
+    mod router {
+        use crate::{chain, Guzzle, KeyDisposition, Router};
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+            name: String,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Author {
+            name: String,
+            #[guzzle(key_prefix = "author_")]
+            extra: Vec<(String, String)>,
+        }
+
+        #[test]
+        fn pairs_go_to_the_first_guzzler_listing_the_key() {
+            let mut post = Post::default();
+            let mut author = Author::default();
+            let mut router = Router::new();
+            router.register(&mut post).register(&mut author);
+
+            assert_eq!(router.explain_key("name"), KeyDisposition::Field("name"));
+            assert_eq!(router.explain_key("author_bio"), KeyDisposition::Unknown);
+            let remaining_data = router.guzzle_all(vec![
+                ("name", "Daniel".to_string()),
+                ("title", "Hello".to_string()),
+                ("author_bio", "Writes things".to_string()),
+            ]);
+            assert_eq!(
+                remaining_data,
+                vec![("author_bio", "Writes things".to_string())]
+            );
+            drop(router);
+
+            assert_eq!(post.title, "Hello");
+            assert_eq!(post.name, "Daniel");
+            assert!(author.name.is_empty());
+            assert!(author.extra.is_empty());
+        }
+
+        #[test]
+        fn guzzlers_without_keys_are_offered_unrouted_keys() {
+            let mut post = Post::default();
+            let mut chained = chain(Post::default(), Author::default());
+            let mut router = Router::new();
+            router.register(&mut post).register(&mut chained);
+
+            assert_eq!(router.explain_key("title"), KeyDisposition::Field("title"));
+            assert_eq!(
+                router.explain_key("author_bio"),
+                KeyDisposition::Field("extra")
+            );
+            let remaining_data = router.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("author_bio", "Writes things".to_string()),
+                ("colour", "red".to_string()),
+            ]);
+            assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+            drop(router);
+
+            assert_eq!(post.title, "Hello");
+            assert!(chained.first.title.is_empty());
+            assert_eq!(
+                chained.second.extra,
+                vec![("bio".to_string(), "Writes things".to_string())]
+            );
+        }
+
+        #[test]
+        fn fallbacks_are_offered_keys_they_do_not_list() {
+            let mut post = Post::default();
+            let mut author = Author::default();
+            let mut router = Router::new();
+            router.register(&mut post).register_fallback(&mut author);
+
+            let remaining_data = router.guzzle_all(vec![
+                ("name", "Daniel".to_string()),
+                ("author_bio", "Writes things".to_string()),
+                ("colour", "red".to_string()),
+            ]);
+            assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+            drop(router);
+
+            assert_eq!(post.name, "Daniel");
+            assert!(author.name.is_empty());
+            assert_eq!(
+                author.extra,
+                vec![("bio".to_string(), "Writes things".to_string())]
+            );
+        }
+    }
+
+    mod perfect_hash {
+        use crate::{Guzzle, GuzzleError, GuzzleState, TryGuzzle};
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(matcher = "phf", group(location = ["lat", "lng"]), strict_prefix = "wp_")]
+        struct Post {
+            #[guzzle(state)]
+            state: GuzzleState,
+            #[guzzle(keys = ["title", "post_title"])]
+            title: String,
+            #[guzzle(keys = ["status"], when_value = "draft")]
+            draft: String,
+            #[guzzle(keys = ["status"])]
+            status: String,
+            views: u32,
+            lat: String,
+            lng: String,
+            #[guzzle(key_prefix = "tag_")]
+            tags: Vec<(String, String)>,
+        }
+
+        #[test]
+        fn matches_like_a_match() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("post_title", "Hello".to_string()),
+                ("status", "draft".to_string()),
+                ("views", "12".to_string()),
+                ("lng", "0.1".to_string()),
+                ("lat", "51.5".to_string()),
+                ("tag_colour", "red".to_string()),
+                ("colour", "red".to_string()),
+                ("", "empty".to_string()),
+            ]);
+            assert_eq!(post.title, "Hello");
+            assert_eq!(post.draft, "draft");
+            assert!(post.status.is_empty());
+            assert_eq!(post.views, 12);
+            assert_eq!((post.lat.as_str(), post.lng.as_str()), ("51.5", "0.1"));
+            assert_eq!(post.tags, vec![("colour".to_string(), "red".to_string())]);
+            assert_eq!(
+                remaining_data,
+                vec![("colour", "red".to_string()), ("", "empty".to_string())]
+            );
+
+            assert!(matches!(
+                post.try_guzzle(("wp_lock", "1".to_string())),
+                Err(GuzzleError::UnknownKey(_))
+            ));
+            assert_eq!(post.try_guzzle(("status", "live".to_string())), Ok(None));
+            assert_eq!(post.status, "live");
+        }
+    }
+
+    mod unparser {
+        use crate::{Guzzle, Unguzzle};
+
+        fn yes_no_parser(s: String) -> bool {
+            s == "yes"
+        }
+
+        fn yes_no_unparser(value: &bool) -> String {
+            if *value { "yes" } else { "no" }.to_string()
+        }
+
+        fn padded_unparser(count: &u64) -> String {
+            format!("{:03}", count)
+        }
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            #[guzzle(parser = yes_no_parser, unparser = yes_no_unparser)]
+            published: bool,
+            #[guzzle(parser = yes_no_parser, unparser = yes_no_unparser)]
+            featured: Option<bool>,
+            #[guzzle(split = ",", parser = yes_no_parser, unparser = yes_no_unparser)]
+            flags: Vec<bool>,
+            #[guzzle(unparser = padded_unparser)]
+            count: u64,
+        }
+
+        #[test]
+        fn unparsers_reverse_their_parsers() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("published", "yes".to_string()),
+                ("featured", "no".to_string()),
+                ("flags", "yes,no,yes".to_string()),
+                ("count", "7".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(
+                post.to_metadata(),
+                vec![
+                    ("published".to_string(), "yes".to_string()),
+                    ("featured".to_string(), "no".to_string()),
+                    ("flags".to_string(), "yes,no,yes".to_string()),
+                    ("count".to_string(), "007".to_string()),
+                ]
+            );
+        }
+    }
+
+    mod guzzle_diff {
+        use crate::{Guzzle, GuzzleDiff, Unguzzle};
+
+        #[derive(Clone, Default, Guzzle, Unguzzle, GuzzleDiff)]
+        struct Seo {
+            #[guzzle(keys = ["seo_title"])]
+            title: String,
+        }
+
+        #[derive(Clone, Default, Guzzle, Unguzzle, GuzzleDiff)]
+        struct Post {
+            title: String,
+            #[guzzle(keys = ["tag"], collect)]
+            tags: Vec<String>,
+            #[deep_guzzle]
+            seo: Seo,
+        }
+
+        #[test]
+        fn unchanged_instances_have_no_diff() {
+            let post = Post {
+                title: "Hi".to_string(),
+                ..Post::default()
+            };
+            assert!(post.guzzle_diff(&post.clone()).is_empty());
+        }
+
+        #[test]
+        fn changed_added_and_removed_keys() {
+            let old = Post {
+                title: "Hi".to_string(),
+                ..Post::default()
+            };
+            let mut new = old.clone();
+            new.tags.push("rust".to_string());
+            new.seo.title = "Hello".to_string();
+            assert_eq!(
+                new.guzzle_diff(&old),
+                vec![
+                    ("tag".to_string(), Some("rust".to_string())),
+                    ("seo_title".to_string(), Some("Hello".to_string())),
+                ]
+            );
+            assert_eq!(
+                old.guzzle_diff(&new),
+                vec![
+                    ("seo_title".to_string(), Some("".to_string())),
+                    ("tag".to_string(), None),
+                ]
+            );
+        }
+
+        #[test]
+        fn keys_with_several_values_are_compared_as_a_whole() {
+            let old = Post {
+                tags: vec!["a".to_string(), "b".to_string()],
+                ..Post::default()
+            };
+            assert!(old.guzzle_diff(&old.clone()).is_empty());
+
+            let mut new = old.clone();
+            new.tags.pop();
+            assert_eq!(
+                new.guzzle_diff(&old),
+                vec![("tag".to_string(), Some("a".to_string()))]
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod de {
+        use crate::de::from_metadata;
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Status {
+            Draft,
+            Published,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Post {
+            id: u64,
+            status: Status,
+            sticky: bool,
+            #[serde(default)]
+            tags: Vec<String>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Strict {
+            #[allow(dead_code)]
+            id: u64,
+        }
+
+        fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        }
+
+        #[test]
+        fn values_are_parsed_into_their_fields() {
+            let post: Post = from_metadata(pairs(&[
+                ("id", "7"),
+                ("status", "published"),
+                ("sticky", "false"),
+                ("_edit_last", "1"),
+            ]))
+            .unwrap();
+            assert_eq!(
+                post,
+                Post {
+                    id: 7,
+                    status: Status::Published,
+                    sticky: false,
+                    tags: vec![]
+                }
+            );
+        }
+
+        #[test]
+        fn unparseable_and_missing_values_are_errors() {
+            let error = from_metadata::<Post, _>(pairs(&[("id", "seven")])).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "invalid value: string \"seven\", expected u64"
+            );
+            let error = from_metadata::<Post, _>(pairs(&[("id", "7")])).unwrap_err();
+            assert_eq!(error.to_string(), "missing field `status`");
+            assert!(from_metadata::<Post, _>(pairs(&[("status", "pending")])).is_err());
+        }
+
+        #[test]
+        fn unknown_keys_can_be_denied() {
+            assert!(from_metadata::<Strict, _>(pairs(&[("id", "7")])).is_ok());
+            assert!(from_metadata::<Strict, _>(pairs(&[("id", "7"), ("other", "")])).is_err());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod ser {
+        use crate::ser::{to_metadata, MetadataSerializer};
+        use serde::{Deserialize, Serialize};
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Status {
+            Draft,
+            Published,
+        }
+
+        #[derive(Serialize)]
+        struct Row {
+            title: String,
+            order: u8,
+        }
+
+        #[derive(Serialize)]
+        struct Page {
+            status: Status,
+            rows: Vec<Row>,
+            extra: BTreeMap<u8, bool>,
+            nothing: (),
+        }
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Config {
+            name: String,
+            port: u16,
+            status: Status,
+            hosts: Vec<String>,
+        }
+
+        fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        }
+
+        #[test]
+        fn rows_maps_and_variants_are_flattened() {
+            let page = Page {
+                status: Status::Draft,
+                rows: vec![
+                    Row {
+                        title: "First".to_string(),
+                        order: 1,
+                    },
+                    Row {
+                        title: "Second".to_string(),
+                        order: 2,
+                    },
+                ],
+                extra: vec![(1, true), (2, false)].into_iter().collect(),
+                nothing: (),
+            };
+            assert_eq!(
+                to_metadata(&page).unwrap(),
+                pairs(&[
+                    ("status", "draft"),
+                    ("rows_0_title", "First"),
+                    ("rows_0_order", "1"),
+                    ("rows_1_title", "Second"),
+                    ("rows_1_order", "2"),
+                    ("extra_1", "true"),
+                    ("extra_2", "false"),
+                ])
+            );
+            assert_eq!(
+                MetadataSerializer::new()
+                    .separator("/")
+                    .to_metadata(&page.rows[..1])
+                    .unwrap(),
+                pairs(&[("0/title", "First"), ("0/order", "1")])
+            );
+        }
+
+        #[test]
+        fn round_trips_through_de() {
+            let config = Config {
+                name: "guzzle".to_string(),
+                port: 8080,
+                status: Status::Published,
+                hosts: vec!["a".to_string(), "b".to_string()],
+            };
+            let metadata = to_metadata(&config).unwrap();
+            assert_eq!(crate::de::from_metadata::<Config, _>(metadata), Ok(config));
+        }
+
+        #[test]
+        fn only_structs_and_maps_can_be_flattened() {
+            assert!(to_metadata(&1).is_err());
+            assert!(to_metadata(&vec![1, 2]).is_err());
+            let mut map = BTreeMap::new();
+            map.insert(None::<u8>, 1);
+            assert!(to_metadata(&map).is_err());
+        }
+    }
+
+    mod default_expr {
+        use crate::{Guzzle, GuzzleState};
+
+        const DEFAULT_TAGS: &[&str] = &["news"];
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(default = 20, required)]
+            views: u64,
+            #[guzzle(keys = ["tag"], default = DEFAULT_TAGS.iter().map(|t| t.to_string()).collect())]
+            tags: Vec<String>,
+            #[guzzle(default = Some("Untitled".to_string()))]
+            title: Option<String>,
+            #[guzzle(required)]
+            author: String,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn absent_keys_get_their_expression() {
+            let mut post = Post::default();
+            assert_eq!(post.missing_keys(), vec!["author"]);
+            post.finalize();
+            assert_eq!(post.views, 20);
+            assert_eq!(post.tags, vec!["news".to_string()]);
+            assert_eq!(post.title, Some("Untitled".to_string()));
+            assert_eq!(post.remaining_required(), 1);
+        }
+
+        #[test]
+        fn present_keys_are_kept() {
+            let mut post = Post::default();
+            post.guzzle_all(vec![
+                ("views", "3".to_string()),
+                ("tag", "rust".to_string()),
+                ("title", "Hello".to_string()),
+                ("author", "Daniel".to_string()),
+            ]);
+            post.finalize();
+            assert_eq!(post.views, 3);
+            assert_eq!(post.tags, vec!["rust".to_string()]);
+            assert_eq!(post.title, Some("Hello".to_string()));
+            assert!(post.missing_keys().is_empty());
+        }
+    }
+
+    mod empty_is_missing {
+        use crate::{BytesGuzzle, Guzzle, GuzzleState};
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(empty_is_missing, default_if_absent = "draft")]
+            status: String,
+            #[guzzle(empty_is_missing, required)]
+            views: Option<u64>,
+            subtitle: String,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(empty_is_missing)]
+        struct Seo {
+            title: String,
+            #[guzzle(key_prefix = "og_")]
+            open_graph: Vec<(String, String)>,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(bytes, empty_is_missing)]
+        struct Raw {
+            data: Vec<u8>,
+        }
+
+        #[test]
+        fn empty_values_are_consumed_without_clobbering() {
+            let mut post = Post {
+                subtitle: "Kept".to_string(),
+                ..Post::default()
+            };
+            let remaining_data = post.guzzle_all(vec![
+                ("status", String::new()),
+                ("views", String::new()),
+                ("subtitle", String::new()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(post.missing_keys(), vec!["views"]);
+            post.finalize();
+            assert_eq!(post.status, "draft".to_string());
+            assert_eq!(post.views, None);
+            // Fields without it are still set to the empty value
+            assert!(post.subtitle.is_empty());
+        }
+
+        #[test]
+        fn container_applies_to_every_field() {
+            let mut seo = Seo {
+                title: "Kept".to_string(),
+                ..Seo::default()
+            };
+            seo.guzzle_all(vec![
+                ("title", String::new()),
+                ("og_image", String::new()),
+                ("og_type", "article".to_string()),
+            ]);
+            assert_eq!(seo.title, "Kept".to_string());
+            assert_eq!(
+                seo.open_graph,
+                vec![("type".to_string(), "article".to_string())]
+            );
+
+            let mut raw = Raw {
+                data: b"kept".to_vec(),
+            };
+            assert_eq!(raw.guzzle_bytes((b"data".as_ref(), vec![])), None);
+            assert_eq!(raw.data, b"kept".to_vec());
+        }
+    }
+
+    mod transforms {
+        use crate::{transforms, Guzzle, GuzzleState};
+
+        fn trim(s: String) -> String {
+            s.trim_matches('*').to_string()
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(map = [
+                transforms::trim,
+                transforms::collapse_whitespace,
+                transforms::to_uppercase,
+            ])]
+            title: String,
+            // A transform of our own is used by name rather than guzzle's
+            #[guzzle(map = [trim], split = ",")]
+            tags: Vec<String>,
+            #[guzzle(map = [transforms::trim], empty_is_missing, default_if_absent = "draft")]
+            status: String,
+            #[guzzle(key_prefix = "og_", map = [transforms::to_lowercase])]
+            open_graph: Vec<(String, String)>,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn transforms_apply_in_order_before_parsing() {
+            let mut post = Post::default();
+            post.guzzle_all(vec![
+                ("title", "  hello \n  world ".to_string()),
+                ("tags", "**rust, news**".to_string()),
+                ("status", "   ".to_string()),
+                ("og_type", "Article".to_string()),
+            ]);
+            post.finalize();
+            assert_eq!(post.title, "HELLO WORLD".to_string());
+            assert_eq!(post.tags, vec!["rust".to_string(), " news".to_string()]);
+            assert_eq!(post.status, "draft".to_string());
+            assert_eq!(
+                post.open_graph,
+                vec![("type".to_string(), "article".to_string())]
+            );
+        }
+    }
+
+    mod setter {
+        use crate::{Guzzle, GuzzleState};
+
+        #[derive(Default, Guzzle)]
+        struct Location {
+            #[guzzle(setter = set_lat)]
+            lat: f64,
+            #[guzzle(keys = ["tag"], split = ",", setter = add_tag, default_if_absent = "none")]
+            tags: Vec<String>,
+            #[no_guzzle]
+            valid: bool,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        impl Location {
+            fn set_lat(&mut self, lat: f64) {
+                self.valid = (-90.0..=90.0).contains(&lat);
+                self.lat = lat.clamp(-90.0, 90.0);
+            }
+
+            fn add_tag(&mut self, tag: String) {
+                if !self.tags.contains(&tag) {
+                    self.tags.push(tag);
+                }
+            }
+        }
+
+        #[test]
+        fn setters_are_called_with_each_parsed_value() {
+            let mut location = Location::default();
+            location.guzzle_all(vec![
+                ("lat", "91.5".to_string()),
+                ("tag", "a,b,a".to_string()),
+            ]);
+            assert_eq!(location.lat, 90.0);
+            assert!(!location.valid);
+            assert_eq!(location.tags, vec!["a".to_string(), "b".to_string()]);
+        }
+
+        #[test]
+        fn defaults_go_through_the_setter() {
+            let mut location = Location::default();
+            location.guzzle_all(vec![("lat", "51.5".to_string())]);
+            location.finalize();
+            assert!(location.valid);
+            assert_eq!(location.tags, vec!["none".to_string()]);
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Fallback {
+            #[guzzle(setter = set_lat, default = 5.0)]
+            lat: f64,
+            #[no_guzzle]
+            valid: bool,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        impl Fallback {
+            fn set_lat(&mut self, lat: f64) {
+                self.valid = true;
+                self.lat = lat;
+            }
+        }
+
+        #[test]
+        fn default_expressions_go_through_the_setter() {
+            let mut fallback = Fallback::default();
+            fallback.finalize();
+            assert_eq!(fallback.lat, 5.0);
+            assert!(fallback.valid);
+        }
+    }
+
+    mod append {
+        use crate::Guzzle;
+        use std::collections::HashSet;
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(keys = ["note"], append = "\n")]
+            notes: String,
+            #[guzzle(append)]
+            slug: String,
+            #[guzzle(keys = ["tag"], append)]
+            tags: Vec<String>,
+            #[guzzle(keys = ["category"], append)]
+            categories: HashSet<String>,
+        }
+
+        #[test]
+        fn every_value_is_kept() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("note", "First".to_string()),
+                ("slug", "hello".to_string()),
+                ("tag", "rust".to_string()),
+                ("note", "Second".to_string()),
+                ("slug", "-world".to_string()),
+                ("tag", "news".to_string()),
+                ("category", "tech".to_string()),
+                ("category", "tech".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(post.notes, "First\nSecond".to_string());
+            assert_eq!(post.slug, "hello-world".to_string());
+            assert_eq!(post.tags, vec!["rust".to_string(), "news".to_string()]);
+            assert_eq!(post.categories.len(), 1);
+        }
+    }
+
+    mod duplicates {
+        use crate::{Guzzle, GuzzleError, GuzzleState, TryGuzzle};
+
+        fn u64_parser(s: String) -> u64 {
+            s.parse().unwrap()
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(duplicates = "first")]
+        struct Post {
+            #[guzzle(keys = ["title", "post_title"])]
+            title: String,
+            #[guzzle(duplicates = "last")]
+            status: String,
+            #[guzzle(duplicates = "error", parser = u64_parser)]
+            id: u64,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn first_last_and_error() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("title", "Oldest".to_string()),
+                ("post_title", "Newer".to_string()),
+                ("status", "draft".to_string()),
+                ("status", "published".to_string()),
+                ("id", "1".to_string()),
+                ("id", "2".to_string()),
+            ]);
+            assert_eq!(post.title, "Oldest".to_string());
+            assert_eq!(post.status, "published".to_string());
+            assert_eq!(post.id, 1);
+            assert_eq!(remaining_data, vec![("id", "2".to_string())]);
+        }
+
+        #[test]
+        fn errors_are_reported_by_try_guzzle() {
+            let mut post = Post::default();
+            assert_eq!(post.try_guzzle(("id", "1".to_string())), Ok(None));
+            assert_eq!(
+                post.try_guzzle(("id", "2".to_string())),
+                Err(GuzzleError::Duplicate("id".to_string()))
+            );
+        }
+    }
+
+    mod parser_with_key {
+        use crate::{Guzzle, GuzzleState};
+
+        // Sizes come in under keys for their unit, but are all kept in millimetres
+        fn millimetres(key: &str, value: String) -> f64 {
+            let size: f64 = value.parse().unwrap_or_default();
+            if key.ends_with("_cm") {
+                size * 10.0
+            } else if key.ends_with("_in") {
+                size * 25.4
+            } else {
+                size
+            }
+        }
+
+        fn dimension(key: &str, value: String) -> (String, f64) {
+            let name = key.trim_start_matches("size_").to_string();
+            (name, millimetres(key, value))
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Product {
+            #[guzzle(
+                keys = ["width_mm", "width_cm", "width_in"],
+                parser_with_key = millimetres,
+                default_if_absent = "100"
+            )]
+            width: f64,
+            #[guzzle(key_prefix = "size_", parser_with_key = dimension)]
+            sizes: Vec<(String, f64)>,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn the_parser_is_given_the_key() {
+            let mut product = Product::default();
+            product.guzzle(("width_cm", "2.5".to_string()));
+            assert_eq!(product.width, 25.0);
+            product.guzzle(("width_in", "1".to_string()));
+            assert_eq!(product.width, 25.4);
+        }
+
+        #[test]
+        fn prefixed_fields_are_given_the_whole_key() {
+            let mut product = Product::default();
+            product.guzzle_all(vec![
+                ("size_cm", "3".to_string()),
+                ("size_mm", "4".to_string()),
+            ]);
+            assert_eq!(
+                product.sizes,
+                vec![("cm".to_string(), 30.0), ("mm".to_string(), 4.0)]
+            );
+        }
+
+        #[test]
+        fn defaults_are_parsed_with_the_first_key() {
+            let mut product = Product::default();
+            product.finalize();
+            assert_eq!(product.width, 100.0);
+        }
+    }
+
+    mod closure_parsers {
+        use crate::Guzzle;
 
-pub trait Guzzle {
-    fn guzzle<T>(&mut self, current: (T, String)) -> Option<(T, String)>
-    where
-        T: AsRef<str>;
-}
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(parser = |s: String| s.len() as u64)]
+            title_length: u64,
+            #[guzzle(opt_parser = |s: String| s.strip_prefix('#').map(str::to_string))]
+            colour: Option<String>,
+            #[guzzle(try_parser = |s: String| s.trim().parse::<u32>())]
+            views: u32,
+            #[guzzle(
+                keys = ["metres"],
+                key_parsers = [("feet", |s: String| s.parse::<f64>().unwrap_or_default() * 0.3048)],
+                parser = |s: String| s.parse::<f64>().unwrap_or_default()
+            )]
+            height: f64,
+        }
 
-#[cfg(test)]
-mod tests {
-    mod guzzle_trait {
-        use crate::Guzzle;
+        #[test]
+        fn closures_can_be_used_as_parsers() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("title_length", "Hello".to_string()),
+                ("colour", "#ff0000".to_string()),
+                ("views", " 20 ".to_string()),
+                ("feet", "10".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(post.title_length, 5);
+            assert_eq!(post.colour, Some("ff0000".to_string()));
+            assert_eq!(post.views, 20);
+            assert!((post.height - 3.048).abs() < f64::EPSILON);
+        }
 
-        #[derive(Default)]
-        struct Tester {
-            pub one: String,
-            pub two: String,
+        #[test]
+        fn closures_can_turn_values_down() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("colour", "red".to_string()),
+                ("views", "lots".to_string()),
+            ]);
+            assert_eq!(
+                remaining_data,
+                vec![("colour", "red".to_string()), ("views", "lots".to_string())]
+            );
         }
+    }
 
-        impl Guzzle for Tester {
-            fn guzzle<T>(&mut self, (key, value): (T, String)) -> Option<(T, String)>
-            where
-                T: AsRef<str>,
-            {
-                match key.as_ref() {
-                    "one" => self.one = value,
-                    "two" => self.two = value,
-                    _ => return Some((key, value)),
-                }
+    mod parser_opt {
+        use crate::{Guzzle, TryGuzzle};
+
+        fn hex_colour(s: String) -> Option<String> {
+            let hex = s.strip_prefix('#')?;
+            if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                Some(hex.to_lowercase())
+            } else {
                 None
             }
         }
 
+        #[derive(Default, Guzzle)]
+        struct Theme {
+            #[guzzle(parser_opt = hex_colour)]
+            background: Option<String>,
+            #[guzzle(parser_opt = hex_colour, drop_rejected)]
+            foreground: Option<String>,
+            #[guzzle(keys = ["accent"], parser_opt = hex_colour, drop_rejected, split = ",")]
+            accents: Vec<String>,
+        }
+
         #[test]
-        fn guzzle_with_vec_string_string() {
-            let test_data = vec![
-                ("one".to_string(), "1".to_string()),
-                ("two".to_string(), "2".to_string()),
-                ("three".to_string(), "3".to_string()),
-            ];
+        fn rejected_values_are_handed_back() {
+            let mut theme = Theme::default();
+            assert_eq!(
+                theme.guzzle(("background", "blue".to_string())),
+                Some(("background", "blue".to_string()))
+            );
+            assert_eq!(theme.guzzle(("background", "#00FF00".to_string())), None);
+            assert_eq!(theme.background, Some("00ff00".to_string()));
+        }
 
-            let mut tester = Tester::default();
+        #[test]
+        fn rejected_values_can_be_dropped() {
+            let mut theme = Theme::default();
+            let remaining_data = theme.guzzle_all(vec![
+                ("foreground", "#000000".to_string()),
+                ("foreground", "black".to_string()),
+                ("accent", "#ff0000,red".to_string()),
+            ]);
+            assert!(remaining_data.is_empty());
+            assert_eq!(theme.foreground, Some("000000".to_string()));
+            assert!(theme.accents.is_empty());
+        }
 
-            let remaining_data: Vec<(String, String)> = test_data
-                .into_iter()
-                .filter_map(|v| tester.guzzle(v))
-                .collect();
+        #[test]
+        fn dropped_values_are_not_errors() {
+            let mut theme = Theme::default();
+            assert_eq!(
+                theme.try_guzzle(("foreground", "black".to_string())),
+                Ok(None)
+            );
+            assert_eq!(
+                theme.try_guzzle(("background", "blue".to_string())),
+                Ok(Some(("background", "blue".to_string())))
+            );
+        }
+    }
 
-            assert_eq!(tester.one, "1".to_string());
-            assert_eq!(tester.two, "2".to_string());
+    mod finish {
+        use crate::Guzzle;
 
-            assert_eq!(remaining_data.len(), 1);
+        #[derive(Debug, PartialEq)]
+        struct GeoPoint {
+            lat: f64,
+            lng: f64,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(finish = locate)]
+        struct Venue {
+            lat: Option<f64>,
+            lng: Option<f64>,
+            #[no_guzzle]
+            location: Option<GeoPoint>,
+            #[no_guzzle]
+            finished: usize,
+        }
+
+        impl Venue {
+            fn locate(&mut self) {
+                self.finished += 1;
+                if let (Some(lat), Some(lng)) = (self.lat, self.lng) {
+                    self.location = Some(GeoPoint { lat, lng });
+                }
+            }
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Event {
+            name: String,
+            #[deep_guzzle(prefix = "venue_")]
+            venue: Venue,
+            #[deep_guzzle(prefix = "backup_")]
+            backup: Option<Box<Venue>>,
+        }
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(finish = locate)]
+        struct StrictVenue {
+            #[guzzle(try_parser = crate::parsers::from_str)]
+            lat: f64,
+            #[guzzle(try_parser = crate::parsers::from_str)]
+            lng: f64,
+            #[no_guzzle]
+            located: bool,
+        }
+
+        impl StrictVenue {
+            fn locate(&mut self) {
+                self.located = true;
+            }
+        }
+
+        #[test]
+        fn finish_is_called_once_input_is_exhausted() {
+            let (venue, remaining_data) = Venue::from_metadata_finalized(vec![
+                ("lat", "51.5".to_string()),
+                ("lng", "-0.1".to_string()),
+                ("colour", "red".to_string()),
+            ]);
             assert_eq!(
-                remaining_data.into_iter().next(),
-                Some(("three".to_string(), "3".to_string()))
+                venue.location,
+                Some(GeoPoint {
+                    lat: 51.5,
+                    lng: -0.1
+                })
+            );
+            assert_eq!(venue.finished, 1);
+            assert_eq!(
+                remaining_data,
+                vec![("colour".to_string(), "red".to_string())]
             );
         }
 
         #[test]
-        fn guzzle_with_vec_str_string() {
-            let test_data = vec![
-                ("one", "1".to_string()),
-                ("two", "2".to_string()),
-                ("three", "3".to_string()),
-            ];
-
-            let mut tester = Tester::default();
-
-            let remaining_data: Vec<(&str, String)> = test_data
-                .into_iter()
-                .filter_map(|v| tester.guzzle(v))
-                .collect();
+        fn finish_is_called_by_finalize() {
+            let (mut venue, _) = Venue::from_metadata(vec![
+                ("lat", "51.5".to_string()),
+                ("lng", "-0.1".to_string()),
+            ]);
+            venue.finalize();
+            assert_eq!(
+                venue.location,
+                Some(GeoPoint {
+                    lat: 51.5,
+                    lng: -0.1
+                })
+            );
+            assert_eq!(venue.finished, 1);
+        }
 
-            assert_eq!(tester.one, "1".to_string());
-            assert_eq!(tester.two, "2".to_string());
+        #[test]
+        fn finish_is_not_called_for_single_values() {
+            let mut venue = Venue::default();
+            venue.guzzle(("lat", "51.5".to_string()));
+            venue.guzzle(("lng", "-0.1".to_string()));
+            assert_eq!(venue.location, None);
+            assert_eq!(venue.finished, 0);
+        }
 
-            assert_eq!(remaining_data.len(), 1);
+        #[test]
+        fn deep_fields_are_finished() {
+            let (mut event, _) = Event::from_metadata(vec![
+                ("name", "Launch".to_string()),
+                ("venue_lat", "10".to_string()),
+                ("venue_lng", "20".to_string()),
+                ("backup_lat", "30".to_string()),
+                ("backup_lng", "40".to_string()),
+            ]);
+            event.finalize();
             assert_eq!(
-                remaining_data.into_iter().next(),
-                Some(("three", "3".to_string()))
+                event.venue.location,
+                Some(GeoPoint {
+                    lat: 10.0,
+                    lng: 20.0
+                })
+            );
+            let backup = event.backup.unwrap();
+            assert_eq!(
+                backup.location,
+                Some(GeoPoint {
+                    lat: 30.0,
+                    lng: 40.0
+                })
             );
+            assert_eq!(backup.finished, 1);
         }
 
         #[test]
-        fn guzzle_with_hash_str_string() {
-            use std::collections::HashMap;
+        fn try_from_metadata_finishes() {
+            use crate::TryFromMetadata;
+            let venue = StrictVenue::try_from_metadata(vec![
+                ("lat", "51.5".to_string()),
+                ("lng", "-0.1".to_string()),
+            ])
+            .unwrap();
+            assert!(venue.located);
+        }
+    }
 
-            let test_data: HashMap<String, String> = vec![
-                ("one".to_string(), "1".to_string()),
-                ("two".to_string(), "2".to_string()),
-                ("three".to_string(), "3".to_string()),
-            ]
-            .into_iter()
-            .collect();
+    mod skip {
+        use crate::{Guzzle, Unguzzle};
 
-            let mut tester = Tester::default();
+        /// Neither parses nor displays, so it can't be guzzled
+        #[derive(Default)]
+        struct Cache {
+            hits: usize,
+        }
 
-            let remaining_data: Vec<(String, String)> = test_data
-                .into_iter()
-                .filter_map(|v| tester.guzzle(v))
-                .collect();
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            title: String,
+            #[guzzle(skip)]
+            cache: Cache,
+            #[guzzle(skip_unguzzle)]
+            password: String,
+        }
 
-            assert_eq!(tester.one, "1".to_string());
-            assert_eq!(tester.two, "2".to_string());
+        #[test]
+        fn skipped_fields_are_not_guzzled() {
+            let mut post = Post::default();
+            let remaining_data = post.guzzle_all(vec![
+                ("title", "Hello".to_string()),
+                ("cache", "full".to_string()),
+                ("password", "hunter2".to_string()),
+            ]);
+            assert_eq!(remaining_data, vec![("cache", "full".to_string())]);
+            assert_eq!(post.cache.hits, 0);
+            assert_eq!(post.password, "hunter2".to_string());
+            assert!(!Post::KEYS.contains(&"cache"));
+        }
 
-            assert_eq!(remaining_data.len(), 1);
+        #[test]
+        fn skip_unguzzle_fields_are_not_output() {
+            let post = Post {
+                title: "Hello".to_string(),
+                password: "hunter2".to_string(),
+                ..Post::default()
+            };
             assert_eq!(
-                remaining_data.into_iter().next(),
-                Some(("three".to_string(), "3".to_string()))
+                post.to_metadata(),
+                vec![("title".to_string(), "Hello".to_string())]
             );
         }
     }
 
-    mod guzzle_meta_data_derive {
-        use crate::Guzzle;
+    mod inferred_bounds {
+        use crate::{Guzzle, Unguzzle};
 
-        fn u64_parser(s: String) -> u64 {
-            s.parse().unwrap()
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Post {
+            title: String,
         }
-        fn bool_parser(s: String) -> bool {
-            s.parse().unwrap()
+
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Meta<T> {
+            #[deep_guzzle]
+            inner: T,
         }
 
-        #[derive(Default, Guzzle)]
-        struct TypeThatAlsoImplementsGuzzle {
-            /// This struct represents some deeply nested data
-            #[guzzle(keys = ["deep_data"], parser = bool_parser)]
-            deeply_nested_data: bool,
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Measured<N> {
+            width: N,
+            heights: Vec<N>,
+            depth: Option<N>,
         }
 
         #[derive(Default, Guzzle)]
-        struct GuzzleExample {
-            /// This field is annotated with no_guzzle, therefore it will not be parsed by guzzle
-            #[no_guzzle]
-            ignored: String,
-
-            /// This field is not annotated, so if a key matches the field name, it will set the
-            /// value
-            basic: String,
+        struct Rows<R> {
+            #[deep_guzzle]
+            rows: Vec<R>,
+        }
 
-            /// This field is annotated, but with no keys, so it uses the field name as a key
-            /// Currently `#[guzzle]` doesn't do anything different to the default behavior (see
-            /// above), but in the future the default behaviour may be toggleable.
-            #[guzzle]
-            basic_too: String,
+        #[test]
+        fn deep_guzzle_fields_can_be_generic() {
+            let mut meta = Meta::<Post>::default();
+            assert_eq!(meta.guzzle(("title", "Hello".to_string())), None);
+            assert_eq!(meta.inner.title, "Hello".to_string());
+            assert_eq!(
+                meta.to_metadata(),
+                vec![("title".to_string(), "Hello".to_string())]
+            );
 
-            /// This field may be filled from multiple keys
-            #[guzzle(keys = ["one", "two"])]
-            listed_keys: String,
+            let mut rows = Rows::<Post>::default();
+            assert_eq!(rows.guzzle(("rows_1_title", "Second".to_string())), None);
+            assert_eq!(rows.rows[1].title, "Second".to_string());
+        }
 
-            /// This field is not a string, you must provider a parser that will transform it into
-            /// the correct type
-            #[guzzle(parser = u64_parser)]
-            other_types: u64,
+        #[test]
+        fn parsed_fields_can_be_generic() {
+            let (measured, remaining_data) = Measured::<u32>::from_metadata(vec![
+                ("width", "10".to_string()),
+                ("heights", "20".to_string()),
+                ("depth", "wide".to_string()),
+            ]);
+            assert_eq!(remaining_data, vec![("depth", "wide".to_string())]);
+            assert_eq!(measured.width, 10);
+            assert_eq!(measured.heights, vec![20]);
+            assert_eq!(measured.depth, None);
+        }
+    }
 
-            /// This field isn't a string and has multiple keys
-            #[guzzle(parser = u64_parser, keys = ["three", "four"])]
-            other_types_with_listed_keys: u64,
+    #[cfg(feature = "async")]
+    mod stream {
+        use crate::{AsyncGuzzleExt, Guzzle};
+        use futures::executor::block_on;
+        use futures::stream;
 
-            /// Guzzle will wire up this field so that data being guzzled by the `GuzzleExample`
-            /// will first be sent to the `TypeThatAlsoImplementsGuzzle`. If the
-            /// `TypeThatAlsoImplementsGuzzle` consumes the value, `GuzzleExample` will not.
-            #[deep_guzzle]
-            recurse_guzzle_to_populate_this_field: TypeThatAlsoImplementsGuzzle,
+        #[derive(Default, Guzzle)]
+        struct Post {
+            title: String,
+            #[guzzle(keys = ["tag"])]
+            tags: Vec<String>,
         }
 
         #[test]
-        fn everything() {
-            let test_data: Vec<(&str, String)> = vec![
-                ("basic", "basic info".to_string()),
-                ("basic_too", "more basic info".to_string()),
-                ("one", "1".to_string()),
-                ("two", "2".to_string()),
-                ("other_types", "20".to_string()),
-                ("three", "3".to_string()),
-                ("four", "4".to_string()),
-                ("ignored", "ignored data".to_string()),
-                ("deep_data", "true".to_string()),
-            ];
-
-            let mut guzzle_example = GuzzleExample::default();
+        fn streams_are_drained() {
+            let rows = stream::iter(vec![
+                ("title", "Hello".to_string()),
+                ("tag", "rust".to_string()),
+                ("colour", "red".to_string()),
+                ("tag", "async".to_string()),
+            ]);
+            let mut post = Post::default();
+            let remaining_data = block_on(post.guzzle_stream(rows));
+            assert_eq!(post.title, "Hello".to_string());
+            assert_eq!(post.tags, vec!["rust".to_string(), "async".to_string()]);
+            assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+        }
 
-            // This just makes sure deeply_nested_data isn't accidentally set to true
-            assert!(
-                !guzzle_example
-                    .recurse_guzzle_to_populate_this_field
-                    .deeply_nested_data
+        #[test]
+        fn try_streams_stop_at_the_first_error() {
+            let rows = stream::iter(vec![
+                Ok(("title", "Hello".to_string())),
+                Ok(("colour", "red".to_string())),
+                Err("connection lost"),
+                Ok(("tag", "rust".to_string())),
+            ]);
+            let mut post = Post::default();
+            assert_eq!(
+                block_on(post.guzzle_try_stream(rows)),
+                Err("connection lost")
             );
+            assert_eq!(post.title, "Hello".to_string());
+            assert!(post.tags.is_empty());
 
-            let remaining_data: Vec<(&str, String)> = test_data
-                .into_iter()
-                .filter_map(|v| guzzle_example.guzzle(v))
-                .collect();
+            let rows = stream::iter(vec![Ok::<_, &str>(("tag", "rust".to_string()))]);
+            let mut post = Post::default();
+            assert_eq!(block_on(post.guzzle_try_stream(rows)), Ok(vec![]));
+            assert_eq!(post.tags, vec!["rust".to_string()]);
+        }
+    }
 
-            // All appropriate fields are now set
-            assert_eq!(guzzle_example.basic, "basic info".to_string());
-            assert_eq!(guzzle_example.basic_too, "more basic info".to_string());
-            assert_eq!(guzzle_example.listed_keys, "2".to_string());
-            assert_eq!(guzzle_example.other_types, 20);
-            assert_eq!(guzzle_example.other_types_with_listed_keys, 4);
+    mod slots {
+        use crate::{Guzzle, Unguzzle};
 
-            // Including the deeply nested field
-            assert!(
-                guzzle_example
-                    .recurse_guzzle_to_populate_this_field
-                    .deeply_nested_data
-            );
+        #[derive(Default, Guzzle, Unguzzle)]
+        struct Record {
+            #[guzzle(keys = ["col0"], slot = 0)]
+            #[guzzle(keys = ["col1"], slot = 1)]
+            #[guzzle(keys = ["col2", "last_col"], slot = 2)]
+            columns: [Option<String>; 3],
+        }
 
-            // Ignored data is left over
-            assert!(guzzle_example.ignored.is_empty());
+        #[test]
+        fn fill_named_slots() {
+            let mut record = Record::default();
+            let remaining_data = record.guzzle_all(vec![
+                ("col0", "zero".to_string()),
+                ("last_col", "two".to_string()),
+                ("col3", "three".to_string()),
+            ]);
             assert_eq!(
-                remaining_data,
-                vec![("ignored", "ignored data".to_string())]
+                record.columns,
+                [Some("zero".to_string()), None, Some("two".to_string())]
+            );
+            assert_eq!(remaining_data, vec![("col3", "three".to_string())]);
+            assert_eq!(
+                record.to_metadata(),
+                vec![
+                    ("col0".to_string(), "zero".to_string()),
+                    ("col2".to_string(), "two".to_string()),
+                ]
             );
         }
     }
 
+    mod remaining_required {
+        use crate::{Guzzle, GuzzleState};
+
+        #[derive(Default, Guzzle)]
+        struct Location {
+            #[guzzle(keys = ["lat"], required)]
+            lat: String,
+            #[guzzle(keys = ["lng"], required)]
+            lng: String,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[derive(Default, Guzzle)]
+        struct Post {
+            #[guzzle(required)]
+            title: String,
+            #[guzzle(required, keys = ["content", "body"])]
+            content: String,
+            excerpt: String,
+            #[deep_guzzle]
+            location: Location,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        #[test]
+        fn count_decreases_as_required_keys_arrive() {
+            let mut post = Post::default();
+            assert_eq!(post.remaining_required(), 4);
+            assert_eq!(post.missing_keys(), vec!["title", "content", "lat", "lng"]);
+
+            post.guzzle(("excerpt", "Not required".to_string()));
+            assert_eq!(post.remaining_required(), 4);
+
+            post.guzzle(("body", "Hello".to_string()));
+            assert_eq!(post.remaining_required(), 3);
+            assert_eq!(post.missing_keys(), vec!["title", "lat", "lng"]);
+
+            post.guzzle(("lat", "51.5".to_string()));
+            assert_eq!(post.remaining_required(), 2);
+
+            // Seeing the same key twice doesn't count twice
+            post.guzzle(("content", "Hello again".to_string()));
+            assert_eq!(post.remaining_required(), 2);
+
+            post.guzzle(("title", "Hi".to_string()));
+            post.guzzle(("lng", "-0.1".to_string()));
+            assert_eq!(post.remaining_required(), 0);
+            assert!(post.missing_keys().is_empty());
+            assert_eq!(post.content, "Hello again".to_string());
+            assert_eq!(post.location.lng, "-0.1".to_string());
+        }
+    }
+
     mod try_build {
         use trybuild::TestCases;
 
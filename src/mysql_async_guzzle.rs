@@ -0,0 +1,84 @@
+//! Guzzling the rows of a MySQL metadata table, such as Wordpress's `wp_postmeta`, fetched with
+//! the `mysql_async` crate. Each row needs a `meta_key` and a `meta_value` column.
+//!
+//! ```rust,no_run
+//! use guzzle::Guzzle;
+//! use mysql_async::prelude::Queryable;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Post {
+//!     #[guzzle(keys = ["_yoast_wpseo_title"])]
+//!     seo_title: String,
+//! }
+//!
+//! async fn load(conn: &mut mysql_async::Conn, post_id: u64) -> mysql_async::Result<Post> {
+//!     let mut rows = conn
+//!         .exec_iter("SELECT meta_key, meta_value FROM wp_postmeta WHERE post_id = ?", (post_id,))
+//!         .await?;
+//!     let mut post = Post::default();
+//!     guzzle::mysql_async::guzzle_query_result(&mut post, &mut rows).await?;
+//!     Ok(post)
+//! }
+//! ```
+
+use crate::Guzzle;
+use mysql_async::prelude::{FromValue, Protocol};
+use mysql_async::{DriverError, QueryResult, Result, Row};
+
+/// The `meta_key` and `meta_value` of a row, where a `NULL` value is an empty string
+pub fn meta_pair(row: Row) -> Result<(String, String)> {
+    let key = column(&row, "meta_key")?;
+    let value: Option<String> = column(&row, "meta_value")?;
+    Ok((key, value.unwrap_or_default()))
+}
+
+/// Guzzles the `meta_key` and `meta_value` of each row in the current result set as it arrives,
+/// returning the pairs that weren't consumed or the first error. Rows after an error are still
+/// read, so the connection can carry on, but aren't guzzled.
+pub async fn guzzle_query_result<G, P>(
+    target: &mut G,
+    result: &mut QueryResult<'_, '_, P>,
+) -> Result<Vec<(String, String)>>
+where
+    G: Guzzle + ?Sized,
+    P: Protocol,
+{
+    let mut leftovers = vec![];
+    let mut error = None;
+    result
+        .for_each(|row| {
+            if error.is_some() {
+                return;
+            }
+            match meta_pair(row) {
+                Ok(pair) => leftovers.extend(target.guzzle(pair)),
+                Err(row_error) => error = Some(row_error),
+            }
+        })
+        .await?;
+    if let Some(error) = error {
+        return Err(error);
+    }
+    Ok(leftovers)
+}
+
+/// Guzzles rows that have already been collected, such as those from `Queryable::query`
+pub fn guzzle_rows<G, I>(target: &mut G, rows: I) -> Result<Vec<(String, String)>>
+where
+    G: Guzzle + ?Sized,
+    I: IntoIterator<Item = Row>,
+{
+    let mut leftovers = vec![];
+    for row in rows {
+        leftovers.extend(target.guzzle(meta_pair(row)?));
+    }
+    Ok(leftovers)
+}
+
+fn column<T: FromValue>(row: &Row, name: &str) -> Result<T> {
+    match row.get_opt(name) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(error)) => Err(DriverError::FromValue { value: error.0 }.into()),
+        None => Err(DriverError::FromRow { row: row.clone() }.into()),
+    }
+}
@@ -0,0 +1,57 @@
+//! Guzzling the rows of a MySQL metadata table, such as Wordpress's `wp_postmeta`, fetched with
+//! the `mysql` crate. Each row needs a `meta_key` and a `meta_value` column.
+//!
+//! ```rust,no_run
+//! use guzzle::Guzzle;
+//! use mysql::prelude::Queryable;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Post {
+//!     #[guzzle(keys = ["_yoast_wpseo_title"])]
+//!     seo_title: String,
+//! }
+//!
+//! fn load(conn: &mut mysql::Conn, post_id: u64) -> mysql::Result<Post> {
+//!     let rows = conn.exec_iter(
+//!         "SELECT meta_key, meta_value FROM wp_postmeta WHERE post_id = ?",
+//!         (post_id,),
+//!     )?;
+//!     let mut post = Post::default();
+//!     guzzle::mysql::guzzle_rows(&mut post, rows)?;
+//!     Ok(post)
+//! }
+//! ```
+
+use crate::Guzzle;
+use mysql::prelude::FromValue;
+use mysql::{Error, Result, Row};
+
+/// The `meta_key` and `meta_value` of a row, where a `NULL` value is an empty string
+pub fn meta_pair(row: Row) -> Result<(String, String)> {
+    let key = column(&row, "meta_key")?;
+    let value: Option<String> = column(&row, "meta_value")?;
+    Ok((key, value.unwrap_or_default()))
+}
+
+/// Guzzles the `meta_key` and `meta_value` of each row as it's read, such as from a
+/// `QueryResult`, returning the pairs that weren't consumed or the first error
+pub fn guzzle_rows<G, I>(target: &mut G, rows: I) -> Result<Vec<(String, String)>>
+where
+    G: Guzzle + ?Sized,
+    I: IntoIterator<Item = Result<Row>>,
+{
+    let mut leftovers = vec![];
+    for row in rows {
+        if let Some(leftover) = target.guzzle(meta_pair(row?)?) {
+            leftovers.push(leftover);
+        }
+    }
+    Ok(leftovers)
+}
+
+fn column<T: FromValue>(row: &Row, name: &str) -> Result<T> {
+    match row.get_opt(name) {
+        Some(value) => Ok(value?),
+        None => Err(Error::FromRowError(row.clone())),
+    }
+}
@@ -0,0 +1,284 @@
+//! Parsers for values that often turn up in real world metadata. Those that can fail return
+//! `None` rather than panicking, so they're intended to be used as an `opt_parser`, while those
+//! that can't, such as `trimmed` and `comma_list`, are a plain `parser`.
+//!
+//! ```rust
+//! use guzzle::Guzzle;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Product {
+//!     #[guzzle(opt_parser = guzzle::parsers::lenient_f64)]
+//!     price: f64,
+//!     #[guzzle(opt_parser = guzzle::parsers::lenient_bool)]
+//!     in_stock: bool,
+//!     #[guzzle(opt_parser = guzzle::parsers::parse_trimmed)]
+//!     quantity: u32,
+//!     #[guzzle(parser = guzzle::parsers::comma_list)]
+//!     colours: Option<Vec<String>>,
+//! }
+//!
+//! let mut product = Product::default();
+//! assert_eq!(product.guzzle(("price", "$1,234.50".to_string())), None);
+//! assert_eq!(product.guzzle(("in_stock", "yes".to_string())), None);
+//! assert_eq!(product.guzzle(("quantity", " 12 ".to_string())), None);
+//! assert_eq!(product.guzzle(("colours", "red, green,".to_string())), None);
+//! assert_eq!(product.price, 1234.5);
+//! assert!(product.in_stock);
+//! assert_eq!(product.quantity, 12);
+//! assert_eq!(product.colours, Some(vec!["red".to_string(), "green".to_string()]));
+//! ```
+
+use crate::__private::*;
+use core::fmt::Display;
+use core::str::FromStr;
+
+/// Parses the value with its type's `FromStr` implementation, keeping the error's message. This is
+/// what fields that don't hold strings use when they have no parser of their own, as a
+/// `try_parser`.
+pub fn from_str<F>(s: String) -> Result<F, String>
+where
+    F: FromStr,
+    F::Err: Display,
+{
+    s.parse().map_err(|error: F::Err| error.to_string())
+}
+
+/// `from_str` for borrowed values, which is what `#[guzzle(borrowed)]` fields that aren't strings
+/// use when they have no parser of their own
+pub fn from_str_ref<F>(s: &str) -> Result<F, String>
+where
+    F: FromStr,
+    F::Err: Display,
+{
+    s.parse().map_err(|error: F::Err| error.to_string())
+}
+
+/// Parses any `FromStr` type, such as the integers and floats, ignoring whitespace around it
+pub fn parse_trimmed<F: FromStr>(s: String) -> Option<F> {
+    s.trim().parse().ok()
+}
+
+/// Parses the many ways people write yes and no, ignoring case and whitespace: `1`, `yes`, `y`,
+/// `on` and `true` are `true`, while `0`, `no`, `n`, `off` and `false` are `false`.
+pub fn lenient_bool(s: String) -> Option<bool> {
+    match s.trim().to_lowercase().as_str() {
+        "1" | "yes" | "y" | "on" | "true" => Some(true),
+        "0" | "no" | "n" | "off" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Removes whitespace from around the value
+pub fn trimmed(s: String) -> String {
+    s.trim().to_string()
+}
+
+/// Splits the value on `,`, trimming each item and leaving out any that are empty. `Vec` fields
+/// collect each value they're given, so this is for fields holding the list as a whole, such as
+/// an `Option<Vec<String>>`, while `Vec` fields can use `split = ","`.
+pub fn comma_list(s: String) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parses a value holding JSON into any type that can be deserialized from it, or `None` if it
+/// isn't valid JSON for that type.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+/// use serde::Deserialize;
+///
+/// #[derive(Default, Deserialize)]
+/// struct Dimensions {
+///     width: u32,
+///     height: u32,
+/// }
+///
+/// #[derive(Default, Guzzle)]
+/// struct Image {
+///     #[guzzle(opt_parser = guzzle::parsers::json)]
+///     dimensions: Dimensions,
+/// }
+///
+/// let mut image = Image::default();
+/// let dimensions = r#"{"width": 640, "height": 480}"#.to_string();
+/// assert_eq!(image.guzzle(("dimensions", dimensions)), None);
+/// assert_eq!(image.dimensions.width, 640);
+/// assert_eq!(image.dimensions.height, 480);
+/// ```
+#[cfg(feature = "json")]
+pub fn json<T: serde::de::DeserializeOwned>(s: String) -> Option<T> {
+    serde_json::from_str(&s).ok()
+}
+
+/// The currency symbols that are ignored when they appear at the start or end of a number
+const CURRENCY_SYMBOLS: &[char] = &['$', '€', '£', '¥', '₹', '₩', '¢'];
+
+/// Parses a number written for people rather than machines, eg `"$1,234.50"` or `"-£ 5"`.
+///
+/// Whitespace is ignored, as is a single currency symbol at the start or end. `,` is only
+/// accepted as a thousands separator, in groups of three, and `.` is the decimal point.
+/// Formats that use `,` as the decimal point, such as `"1 234,50"`, are not supported and will
+/// return `None` rather than a number that's off by a factor of a hundred.
+pub fn lenient_f64(s: String) -> Option<f64> {
+    let (negative, integer, fraction) = split_number(&s)?;
+    let sign = if negative { "-" } else { "" };
+    let fraction = fraction.unwrap_or_default();
+    format!("{}{}.{}0", sign, integer, fraction).parse().ok()
+}
+
+/// Parses a whole number the same way as `lenient_f64`. Negative numbers and numbers with
+/// anything other than zeros after the decimal point return `None`.
+pub fn lenient_u64(s: String) -> Option<u64> {
+    let (negative, integer, fraction) = split_number(&s)?;
+    let whole = fraction
+        .map(|fraction| fraction.chars().all(|c| c == '0'))
+        .unwrap_or(true);
+    if negative || !whole {
+        return None;
+    }
+    integer.parse().ok()
+}
+
+/// Breaks a lenient number down into its sign, its integer digits with any thousands separators
+/// removed, and its fractional digits if it had any
+fn split_number(s: &str) -> Option<(bool, String, Option<String>)> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    // The sign may come before or after a leading currency symbol
+    let (mut negative, mut rest) = strip_sign(&s);
+    if let Some(stripped) = rest.strip_prefix(CURRENCY_SYMBOLS) {
+        rest = stripped;
+        if !negative {
+            let (now_negative, stripped) = strip_sign(rest);
+            negative = now_negative;
+            rest = stripped;
+        }
+    } else if let Some(stripped) = rest.strip_suffix(CURRENCY_SYMBOLS) {
+        rest = stripped;
+    }
+
+    let mut parts = rest.splitn(2, '.');
+    let integer = parts.next()?;
+    let fraction = parts.next();
+
+    let mut groups = integer.split(',');
+    let first = groups.next()?;
+    let first_is_valid = !first.is_empty() && is_digits(first);
+    let grouped = integer.contains(',');
+    if !first_is_valid || (grouped && first.len() > 3) {
+        return None;
+    }
+    if !groups.all(|group| group.len() == 3 && is_digits(group)) {
+        return None;
+    }
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() || !is_digits(fraction) {
+            return None;
+        }
+    }
+
+    Some((
+        negative,
+        integer.replace(',', ""),
+        fraction.map(String::from),
+    ))
+}
+
+/// Whether the number is negative, and the rest of it without its sign
+fn strip_sign(s: &str) -> (bool, &str) {
+    if let Some(rest) = s.strip_prefix('-') {
+        (true, rest)
+    } else {
+        (false, s.strip_prefix('+').unwrap_or(s))
+    }
+}
+
+fn is_digits(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn f64_of(s: &str) -> Option<f64> {
+        lenient_f64(s.to_string())
+    }
+
+    fn u64_of(s: &str) -> Option<u64> {
+        lenient_u64(s.to_string())
+    }
+
+    #[test]
+    fn lenient_bools() {
+        for s in &["1", "yes", "Y", " On ", "TRUE"] {
+            assert_eq!(lenient_bool(s.to_string()), Some(true));
+        }
+        for s in &["0", "No", "n", "off", "false "] {
+            assert_eq!(lenient_bool(s.to_string()), Some(false));
+        }
+        assert_eq!(lenient_bool("".to_string()), None);
+        assert_eq!(lenient_bool("maybe".to_string()), None);
+    }
+
+    #[test]
+    fn trimmed_values_and_lists() {
+        assert_eq!(parse_trimmed::<i32>(" -5\n".to_string()), Some(-5));
+        assert_eq!(parse_trimmed::<f32>("1.5 ".to_string()), Some(1.5));
+        assert_eq!(parse_trimmed::<u8>("256".to_string()), None);
+        assert_eq!(trimmed("  guzzle\t".to_string()), "guzzle".to_string());
+        assert_eq!(comma_list(" a,b , ,c ".to_string()), vec!["a", "b", "c"]);
+        assert!(comma_list("".to_string()).is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_values() {
+        let ids: Option<Vec<u64>> = json("[1, 2, 3]".to_string());
+        assert_eq!(ids, Some(vec![1, 2, 3]));
+        assert_eq!(json::<Vec<u64>>("[1, \"two\"]".to_string()), None);
+        assert_eq!(json::<String>("not json".to_string()), None);
+    }
+
+    #[test]
+    fn currency_and_thousands_separators() {
+        assert_eq!(f64_of("$1,234.50"), Some(1234.5));
+        assert_eq!(f64_of("£ 1,234,567"), Some(1_234_567.0));
+        assert_eq!(f64_of("1 234.50 €"), Some(1234.5));
+        assert_eq!(f64_of("  42  "), Some(42.0));
+        assert_eq!(u64_of("$1,234"), Some(1234));
+        assert_eq!(u64_of("$1,234.00"), Some(1234));
+    }
+
+    #[test]
+    fn negative_values() {
+        assert_eq!(f64_of("-$1,234.50"), Some(-1234.5));
+        assert_eq!(f64_of("$-1,234.50"), Some(-1234.5));
+        assert_eq!(f64_of("-5"), Some(-5.0));
+        assert_eq!(u64_of("-5"), None);
+    }
+
+    #[test]
+    fn decimal_commas_are_not_supported() {
+        assert_eq!(f64_of("1 234,50"), None);
+        assert_eq!(f64_of("1.234,50"), None);
+    }
+
+    #[test]
+    fn garbage() {
+        assert_eq!(f64_of(""), None);
+        assert_eq!(f64_of("$"), None);
+        assert_eq!(f64_of("$$5"), None);
+        assert_eq!(f64_of("$5$"), None);
+        assert_eq!(f64_of("five"), None);
+        assert_eq!(f64_of("1e5"), None);
+        assert_eq!(f64_of("1,23"), None);
+        assert_eq!(f64_of("1234,567"), None);
+        assert_eq!(f64_of("1."), None);
+        assert_eq!(f64_of("--1"), None);
+        assert_eq!(u64_of("1.5"), None);
+    }
+}
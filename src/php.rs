@@ -0,0 +1,317 @@
+//! Parses values written by PHP's `serialize()`, which is how Wordpress stores arrays and objects
+//! in metadata, eg `a:2:{i:0;s:3:"red";i:1;s:4:"blue";}`.
+//!
+//! `php_value` gives the whole value, while `php_list` and `php_map` take an array apart into
+//! strings. They all return `None` for anything they can't parse rather than panicking, so they're
+//! intended to be used as an `opt_parser`.
+//!
+//! ```rust
+//! use guzzle::Guzzle;
+//! use guzzle::php::PhpValue;
+//! use std::collections::HashMap;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Product {
+//!     #[guzzle(opt_parser = guzzle::php::php_list)]
+//!     colours: Option<Vec<String>>,
+//!     #[guzzle(opt_parser = guzzle::php::php_map)]
+//!     sizes: Option<HashMap<String, String>>,
+//!     #[guzzle(opt_parser = guzzle::php::php_value)]
+//!     raw: Option<PhpValue>,
+//! }
+//!
+//! let mut product = Product::default();
+//! let colours = r#"a:2:{i:0;s:3:"red";i:1;s:4:"blue";}"#;
+//! let sizes = r#"a:2:{s:5:"small";i:10;s:5:"large";d:12.5;}"#;
+//! assert_eq!(product.guzzle(("colours", colours.to_string())), None);
+//! assert_eq!(product.guzzle(("sizes", sizes.to_string())), None);
+//! assert_eq!(product.guzzle(("raw", "b:1;".to_string())), None);
+//! assert_eq!(product.colours, Some(vec!["red".to_string(), "blue".to_string()]));
+//! assert_eq!(product.sizes.unwrap()["large"], "12.5".to_string());
+//! assert_eq!(product.raw, Some(PhpValue::Bool(true)));
+//! ```
+
+use std::collections::HashMap;
+
+/// A value written by PHP's `serialize()`
+#[derive(Clone, Debug, PartialEq)]
+pub enum PhpValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    /// The keys and values of an array in order, where each key is an `Int` or a `String`
+    Array(Vec<(PhpValue, PhpValue)>),
+    /// An object's class and its properties, in the same way as an `Array`
+    Object(String, Vec<(PhpValue, PhpValue)>),
+}
+
+impl PhpValue {
+    /// Parses a whole serialized value, or returns `None` if it isn't one. References (`r:` and
+    /// `R:`) and custom serialized objects (`C:`) are not supported, and neither are arrays and
+    /// objects nested more than 128 deep.
+    pub fn unserialize(s: &str) -> Option<PhpValue> {
+        let mut reader = Reader {
+            bytes: s.as_bytes(),
+            position: 0,
+            depth: 0,
+        };
+        let value = reader.value()?;
+        if reader.position == reader.bytes.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// The value as a string, as PHP would write it out, if it isn't an array or an object
+    pub fn to_scalar_string(&self) -> Option<String> {
+        match self {
+            PhpValue::Null => Some(String::new()),
+            PhpValue::Bool(true) => Some("1".to_string()),
+            PhpValue::Bool(false) => Some(String::new()),
+            PhpValue::Int(int) => Some(int.to_string()),
+            PhpValue::Float(float) => Some(float.to_string()),
+            PhpValue::String(string) => Some(string.clone()),
+            PhpValue::Array(_) | PhpValue::Object(_, _) => None,
+        }
+    }
+
+    /// The values of an array as strings, ignoring its keys. `None` if this isn't an array or
+    /// any of its values aren't scalars.
+    pub fn to_strings(&self) -> Option<Vec<String>> {
+        match self {
+            PhpValue::Array(entries) => entries
+                .iter()
+                .map(|(_, value)| value.to_scalar_string())
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// The keys and values of an array as strings. `None` if this isn't an array or any of its
+    /// values aren't scalars.
+    pub fn to_string_map(&self) -> Option<HashMap<String, String>> {
+        match self {
+            PhpValue::Array(entries) => entries
+                .iter()
+                .map(|(key, value)| Some((key.to_scalar_string()?, value.to_scalar_string()?)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the value into a `PhpValue`
+pub fn php_value(s: String) -> Option<PhpValue> {
+    PhpValue::unserialize(&s)
+}
+
+/// Parses a serialized array into its values as strings
+pub fn php_list(s: String) -> Option<Vec<String>> {
+    PhpValue::unserialize(&s)?.to_strings()
+}
+
+/// Parses a serialized array into its keys and values as strings
+pub fn php_map(s: String) -> Option<HashMap<String, String>> {
+    PhpValue::unserialize(&s)?.to_string_map()
+}
+
+/// How deeply arrays and objects can be nested before the value is given up on, so that a value
+/// can't run the stack out however it was written
+const MAX_DEPTH: usize = 128;
+
+/// Reads through the bytes of a serialized value, as string lengths are counted in bytes
+struct Reader<'b> {
+    bytes: &'b [u8],
+    position: usize,
+    /// How many arrays and objects are being read
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn value(&mut self) -> Option<PhpValue> {
+        let kind = self.next()?;
+        if kind == b'N' {
+            self.expect(b';')?;
+            return Some(PhpValue::Null);
+        }
+        self.expect(b':')?;
+        match kind {
+            b'b' => match self.until(b';')? {
+                "0" => Some(PhpValue::Bool(false)),
+                "1" => Some(PhpValue::Bool(true)),
+                _ => None,
+            },
+            b'i' => self.until(b';')?.parse().ok().map(PhpValue::Int),
+            b'd' => {
+                let float = match self.until(b';')? {
+                    "INF" => f64::INFINITY,
+                    "-INF" => f64::NEG_INFINITY,
+                    "NAN" => f64::NAN,
+                    float => float.parse().ok()?,
+                };
+                Some(PhpValue::Float(float))
+            }
+            b's' => {
+                let string = self.string()?;
+                self.expect(b';')?;
+                Some(PhpValue::String(string))
+            }
+            b'a' => self.entries().map(PhpValue::Array),
+            b'O' => {
+                let class = self.string()?;
+                self.expect(b':')?;
+                let properties = self.entries()?;
+                Some(PhpValue::Object(class, properties))
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads a length prefixed string, eg `5:"hello"`
+    fn string(&mut self) -> Option<String> {
+        let length: usize = self.until(b':')?.parse().ok()?;
+        self.expect(b'"')?;
+        let end = self.position.checked_add(length)?;
+        let string = self.bytes.get(self.position..end)?;
+        self.position = end;
+        self.expect(b'"')?;
+        String::from_utf8(string.to_vec()).ok()
+    }
+
+    /// Reads a counted list of keys and values, eg `1:{i:0;s:1:"a";}`
+    fn entries(&mut self) -> Option<Vec<(PhpValue, PhpValue)>> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let entries = self.counted_entries();
+        self.depth -= 1;
+        entries
+    }
+
+    fn counted_entries(&mut self) -> Option<Vec<(PhpValue, PhpValue)>> {
+        let count: usize = self.until(b':')?.parse().ok()?;
+        self.expect(b'{')?;
+        // The count comes from the data, so it's not trusted with an allocation
+        let mut entries = vec![];
+        for _ in 0..count {
+            let key = self.value()?;
+            if !matches!(key, PhpValue::Int(_) | PhpValue::String(_)) {
+                return None;
+            }
+            entries.push((key, self.value()?));
+        }
+        self.expect(b'}')?;
+        Some(entries)
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.position)?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        if self.next()? == expected {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Reads up to the terminator, skipping over it
+    fn until(&mut self, terminator: u8) -> Option<&str> {
+        let rest = self.bytes.get(self.position..)?;
+        let length = rest.iter().position(|byte| *byte == terminator)?;
+        self.position += length + 1;
+        std::str::from_utf8(&rest[..length]).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> PhpValue {
+        PhpValue::String(s.to_string())
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(PhpValue::unserialize("N;"), Some(PhpValue::Null));
+        assert_eq!(PhpValue::unserialize("b:0;"), Some(PhpValue::Bool(false)));
+        assert_eq!(PhpValue::unserialize("i:-42;"), Some(PhpValue::Int(-42)));
+        assert_eq!(PhpValue::unserialize("d:0.5;"), Some(PhpValue::Float(0.5)));
+        assert_eq!(
+            PhpValue::unserialize("d:-INF;"),
+            Some(PhpValue::Float(f64::NEG_INFINITY))
+        );
+        assert_eq!(PhpValue::unserialize(r#"s:0:"";"#), Some(string("")));
+    }
+
+    #[test]
+    fn string_lengths_are_in_bytes() {
+        assert_eq!(
+            PhpValue::unserialize(r#"s:5:"café";"#),
+            Some(string("café"))
+        );
+        assert_eq!(
+            PhpValue::unserialize(r#"s:4:"a";b";"#),
+            Some(string(r#"a";b"#))
+        );
+        assert_eq!(PhpValue::unserialize(r#"s:4:"café";"#), None);
+    }
+
+    #[test]
+    fn nested_arrays_and_objects() {
+        let value = r#"a:2:{s:4:"rows";a:1:{i:0;O:8:"stdClass":1:{s:1:"a";N;}}i:7;b:1;}"#;
+        assert_eq!(
+            PhpValue::unserialize(value),
+            Some(PhpValue::Array(vec![
+                (
+                    string("rows"),
+                    PhpValue::Array(vec![(
+                        PhpValue::Int(0),
+                        PhpValue::Object(
+                            "stdClass".to_string(),
+                            vec![(string("a"), PhpValue::Null)],
+                        ),
+                    )]),
+                ),
+                (PhpValue::Int(7), PhpValue::Bool(true)),
+            ]))
+        );
+        assert_eq!(php_list(value.to_string()), None);
+        assert_eq!(php_list("a:0:{}".to_string()), Some(vec![]));
+    }
+
+    #[test]
+    fn garbage() {
+        for s in &[
+            "",
+            "x",
+            "i:1",
+            "i:1;;",
+            "i:one;",
+            "b:2;",
+            r#"s:10:"short";"#,
+            "a:2:{i:0;i:1;}",
+            "a:1:{a:0:{}i:1;}",
+            "a:999999999999:{",
+            "r:1;",
+        ] {
+            assert_eq!(PhpValue::unserialize(s), None, "{}", s);
+        }
+        let nested = "a:1:{i:0;".repeat(100_000) + "N;" + &"}".repeat(100_000);
+        assert_eq!(PhpValue::unserialize(&nested), None);
+    }
+
+    #[test]
+    fn nesting_up_to_the_limit() {
+        let nested = "a:1:{i:0;".repeat(MAX_DEPTH) + "N;" + &"}".repeat(MAX_DEPTH);
+        assert!(PhpValue::unserialize(&nested).is_some());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{Guzzle, GuzzleDyn, KeyDisposition};
+use std::collections::HashMap;
+
+/// Dispatches each key and value straight to the guzzler that consumes it, rather than offering
+/// every pair to every guzzler in turn. Guzzlers register with their `Guzzle::KEYS`, and if two of
+/// them list the same key it goes to the one registered first.
+///
+/// Keys that no guzzler lists are offered in turn to the fallback guzzlers, and are left over if
+/// none of them consume it. Guzzlers that don't list any keys, such as tuples, `Chain`s and boxed
+/// `GuzzleDyn`s, are always fallbacks. Guzzlers that list some keys but consume others too, with a
+/// `key_prefix`, `key_regex` or `rest` field, only get those others if they're registered with
+/// `register_fallback`.
+///
+/// Guzzlers can be registered by mutable reference, so they can still be used once the router is
+/// done with.
+///
+/// ```rust
+/// use guzzle::{Guzzle, Router};
+///
+/// #[derive(Default, Guzzle)]
+/// struct Post {
+///     title: String,
+/// }
+///
+/// #[derive(Default, Guzzle)]
+/// struct Author {
+///     name: String,
+/// }
+///
+/// let mut post = Post::default();
+/// let mut author = Author::default();
+/// let remaining_data = Router::new()
+///     .register(&mut post)
+///     .register(&mut author)
+///     .guzzle_all(vec![
+///         ("name", "Daniel".to_string()),
+///         ("title", "Hello".to_string()),
+///         ("colour", "red".to_string()),
+///     ]);
+/// assert_eq!(post.title, "Hello");
+/// assert_eq!(author.name, "Daniel");
+/// assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+/// ```
+#[derive(Default)]
+pub struct Router<'g> {
+    guzzlers: Vec<Box<dyn GuzzleDyn + 'g>>,
+    routes: HashMap<&'static str, usize>,
+    /// The guzzlers offered keys that aren't routed, in the order they were registered
+    fallbacks: Vec<usize>,
+}
+
+impl<'g> Router<'g> {
+    pub fn new() -> Self {
+        Router::default()
+    }
+
+    /// Adds a guzzler, routing each of its keys that isn't already taken to it. A guzzler that
+    /// doesn't list any keys is a fallback instead.
+    pub fn register<G>(&mut self, guzzler: G) -> &mut Self
+    where
+        G: Guzzle + 'g,
+    {
+        let fallback = G::KEYS.is_empty();
+        self.add(guzzler, fallback)
+    }
+
+    /// Adds a guzzler like `register`, but also offers it the keys that aren't routed to anything,
+    /// for guzzlers that consume more keys than they list
+    pub fn register_fallback<G>(&mut self, guzzler: G) -> &mut Self
+    where
+        G: Guzzle + 'g,
+    {
+        self.add(guzzler, true)
+    }
+
+    fn add<G>(&mut self, guzzler: G, fallback: bool) -> &mut Self
+    where
+        G: Guzzle + 'g,
+    {
+        let index = self.guzzlers.len();
+        for key in G::KEYS {
+            self.routes.entry(key).or_insert(index);
+        }
+        if fallback {
+            self.fallbacks.push(index);
+        }
+        self.guzzlers.push(Box::new(guzzler));
+        self
+    }
+}
+
+impl Guzzle for Router<'_> {
+    fn guzzle<T>(&mut self, (key, value): (T, String)) -> Option<(T, String)>
+    where
+        T: AsRef<str>,
+    {
+        // Guzzlers take owned keys behind `dyn`, the original key goes back to the caller
+        let owned_key = key.as_ref().to_string();
+        let value = match self.routes.get(key.as_ref()) {
+            Some(index) => self.guzzlers[*index].guzzle_dyn((owned_key, value))?.1,
+            None => {
+                let mut pair = (owned_key, value);
+                for index in &self.fallbacks {
+                    pair = self.guzzlers[*index].guzzle_dyn(pair)?;
+                }
+                pair.1
+            }
+        };
+        Some((key, value))
+    }
+
+    fn finalize(&mut self) -> Vec<(String, String)> {
+        self.guzzlers
+            .iter_mut()
+            .flat_map(|guzzler| guzzler.finalize_dyn())
+            .collect()
+    }
+
+    fn explain_key(&self, key: &str) -> KeyDisposition {
+        match self.routes.get(key) {
+            Some(index) => self.guzzlers[*index].explain_key_dyn(key),
+            None => self
+                .fallbacks
+                .iter()
+                .map(|index| self.guzzlers[*index].explain_key_dyn(key))
+                .find(|disposition| *disposition != KeyDisposition::Unknown)
+                .unwrap_or(KeyDisposition::Unknown),
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! A serde `Serializer` that flattens a struct or map into keys and values. For flat structs this
+//! is the reverse of `de`, but `de` doesn't read back the joined keys written for nested structs
+//! and rows, which are meant for `Guzzle` types with `#[deep_guzzle]` fields instead.
+//!
+//! Nested structs and maps have their keys joined onto the key of the field they're in with a
+//! separator, `_` unless configured otherwise. Sequences of plain values are joined into a single
+//! value with a delimiter, `,` unless configured otherwise, while sequences of structs give each
+//! row's keys its index, as `#[deep_guzzle]` `Vec` fields expect. `None`, `()` and empty sequences
+//! are left out.
+//!
+//! ```rust
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Seo {
+//!     title: String,
+//! }
+//!
+//! #[derive(Serialize)]
+//! struct Post {
+//!     views: u64,
+//!     tags: Vec<String>,
+//!     subtitle: Option<String>,
+//!     seo: Seo,
+//! }
+//!
+//! let post = Post {
+//!     views: 20,
+//!     tags: vec!["rust".to_string(), "serde".to_string()],
+//!     subtitle: None,
+//!     seo: Seo { title: "Hello".to_string() },
+//! };
+//! assert_eq!(
+//!     guzzle::ser::to_metadata(&post).unwrap(),
+//!     vec![
+//!         ("views".to_string(), "20".to_string()),
+//!         ("tags".to_string(), "rust,serde".to_string()),
+//!         ("seo_title".to_string(), "Hello".to_string()),
+//!     ]
+//! );
+//! assert_eq!(
+//!     guzzle::ser::MetadataSerializer::new().separator(".").delimiter("|").to_metadata(&post),
+//!     Ok(vec![
+//!         ("views".to_string(), "20".to_string()),
+//!         ("tags".to_string(), "rust|serde".to_string()),
+//!         ("seo.title".to_string(), "Hello".to_string()),
+//!     ])
+//! );
+//! ```
+
+pub use crate::de::Error;
+
+use serde::ser::{self, Serialize};
+
+/// Flattens a struct or map into keys and values, using `_` between nested keys and `,` between
+/// the values in a sequence
+pub fn to_metadata<T: Serialize + ?Sized>(value: &T) -> Result<Vec<(String, String)>, Error> {
+    MetadataSerializer::new().to_metadata(value)
+}
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(message: T) -> Self {
+        serde::de::Error::custom(message)
+    }
+}
+
+/// Flattens structs and maps into keys and values, with a configurable separator between nested
+/// keys and delimiter between the values in a sequence
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataSerializer {
+    separator: String,
+    delimiter: String,
+}
+
+impl Default for MetadataSerializer {
+    fn default() -> Self {
+        MetadataSerializer {
+            separator: "_".to_string(),
+            delimiter: ",".to_string(),
+        }
+    }
+}
+
+impl MetadataSerializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What goes between the key of a field and the keys nested inside it
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// What goes between the values of a sequence when they're joined into one
+    pub fn delimiter(mut self, delimiter: &str) -> Self {
+        self.delimiter = delimiter.to_string();
+        self
+    }
+
+    pub fn to_metadata<T: Serialize + ?Sized>(
+        &self,
+        value: &T,
+    ) -> Result<Vec<(String, String)>, Error> {
+        let mut pairs = vec![];
+        value.serialize(KeySerializer {
+            flattener: self,
+            key: String::new(),
+            pairs: &mut pairs,
+        })?;
+        Ok(pairs)
+    }
+
+    fn join(&self, key: &str, child: &str) -> String {
+        if key.is_empty() {
+            child.to_string()
+        } else {
+            format!("{}{}{}", key, self.separator, child)
+        }
+    }
+
+    /// Map keys have to become a single string, so they can only be plain values
+    fn plain_value<T: Serialize + ?Sized>(&self, value: &T) -> Result<String, Error> {
+        let mut pairs = vec![];
+        let key = "key".to_string();
+        value.serialize(KeySerializer {
+            flattener: self,
+            key: key.clone(),
+            pairs: &mut pairs,
+        })?;
+        match pairs.pop() {
+            Some((value_key, value)) if value_key == key && pairs.is_empty() => Ok(value),
+            _ => Err(ser::Error::custom("map keys have to be plain values")),
+        }
+    }
+}
+
+/// Serializes a value into the pairs under the key it was found at
+struct KeySerializer<'s> {
+    flattener: &'s MetadataSerializer,
+    key: String,
+    pairs: &'s mut Vec<(String, String)>,
+}
+
+impl<'s> KeySerializer<'s> {
+    fn push(self, value: String) -> Result<(), Error> {
+        // Only the top level has no key
+        if self.key.is_empty() {
+            return Err(ser::Error::custom(
+                "only structs and maps can be turned into metadata",
+            ));
+        }
+        self.pairs.push((self.key, value));
+        Ok(())
+    }
+
+    fn nested(&mut self, child: &str) -> KeySerializer<'_> {
+        KeySerializer {
+            flattener: self.flattener,
+            key: self.flattener.join(&self.key, child),
+            pairs: &mut *self.pairs,
+        }
+    }
+
+    fn into_nested(self, child: &str) -> Self {
+        KeySerializer {
+            key: self.flattener.join(&self.key, child),
+            ..self
+        }
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident: $ty:ty,)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<(), Error> {
+                self.push(value.to_string())
+            }
+        )*
+    };
+}
+
+impl<'s> ser::Serializer for KeySerializer<'s> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'s>;
+    type SerializeTuple = SeqSerializer<'s>;
+    type SerializeTupleStruct = SeqSerializer<'s>;
+    type SerializeTupleVariant = SeqSerializer<'s>;
+    type SerializeMap = MapSerializer<'s>;
+    type SerializeStruct = MapSerializer<'s>;
+    type SerializeStructVariant = MapSerializer<'s>;
+
+    serialize_display! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        self.push(String::from_utf8_lossy(value).into_owned())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.push(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self.into_nested(variant))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'s>, Error> {
+        Ok(SeqSerializer {
+            parent: self,
+            elements: vec![],
+            index: 0,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'s>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'s>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'s>, Error> {
+        self.into_nested(variant).serialize_seq(Some(len))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'s>, Error> {
+        Ok(MapSerializer {
+            parent: self,
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer<'s>, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer<'s>, Error> {
+        self.into_nested(variant).serialize_map(Some(len))
+    }
+}
+
+/// Gives each element its index, then joins them back into one value if they were all plain
+struct SeqSerializer<'s> {
+    parent: KeySerializer<'s>,
+    elements: Vec<(String, String)>,
+    index: usize,
+}
+
+impl SeqSerializer<'_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let flattener = self.parent.flattener;
+        let key = flattener.join(&self.parent.key, &self.index.to_string());
+        value.serialize(KeySerializer {
+            flattener,
+            key,
+            pairs: &mut self.elements,
+        })?;
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        let flattener = self.parent.flattener;
+        let plain = self.elements.len() == self.index
+            && self.elements.iter().enumerate().all(|(index, (key, _))| {
+                *key == flattener.join(&self.parent.key, &index.to_string())
+            });
+        if self.elements.is_empty() {
+            Ok(())
+        } else if plain {
+            let values: Vec<String> = self.elements.into_iter().map(|(_, value)| value).collect();
+            self.parent.push(values.join(&flattener.delimiter))
+        } else {
+            self.parent.pairs.extend(self.elements);
+            Ok(())
+        }
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+/// Serializes each field or entry under its own key nested in the parent's
+struct MapSerializer<'s> {
+    parent: KeySerializer<'s>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for MapSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.next_key = Some(self.parent.flattener.plain_value(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.next_key.take().unwrap_or_default();
+        value.serialize(self.parent.nested(&key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self.parent.nested(key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self.parent.nested(key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
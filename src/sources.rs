@@ -0,0 +1,871 @@
+//! Turns common formats into the keys and values guzzle expects, so they can be handed straight
+//! to `Guzzle::guzzle_all`, such as query strings, environment variables, command line options,
+//! INI files, Wordpress shortcodes and, with the `http`, `csv`, `toml` and `json` features, HTTP
+//! headers, CSV records, TOML tables and JSON objects.
+//!
+//! ```rust
+//! use guzzle::Guzzle;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Search {
+//!     q: String,
+//!     #[guzzle(parser = page_parser)]
+//!     page: u32,
+//! }
+//!
+//! fn page_parser(s: String) -> u32 {
+//!     s.parse().unwrap_or(1)
+//! }
+//!
+//! let mut search = Search::default();
+//! let query = "?q=caf%C3%A9+au+lait&page=2&utm_source=x";
+//! let remaining_data = search.guzzle_all(guzzle::sources::query_string(query));
+//! assert_eq!(search.q, "café au lait".to_string());
+//! assert_eq!(search.page, 2);
+//! assert_eq!(remaining_data, vec![("utm_source".to_string(), "x".to_string())]);
+//! ```
+
+use crate::__private::*;
+
+/// The parameters of a URL query string or a form body, eg `a=1&b=2`, percent-decoded and with
+/// `+` as a space. A leading `?` is ignored, as are empty parameters, and a parameter without an
+/// `=` has an empty value. Escapes that aren't valid are left as they are.
+pub fn query_string(query: &str) -> QueryString<'_> {
+    QueryString {
+        parameters: query.strip_prefix('?').unwrap_or(query).split('&'),
+    }
+}
+
+/// The decoded keys and values of a query string, from `query_string`
+#[derive(Clone, Debug)]
+pub struct QueryString<'q> {
+    parameters: core::str::Split<'q, char>,
+}
+
+impl Iterator for QueryString<'_> {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<(String, String)> {
+        let parameter = self.parameters.find(|parameter| !parameter.is_empty())?;
+        let (key, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+        Some((percent_decode(key), percent_decode(value)))
+    }
+}
+
+/// Command line options written as `--key value` or `--key=value`, with the dashes in keys turned
+/// into underscores so `--dry-run` is guzzled as `dry_run`. An option followed by another option
+/// or by nothing is a flag, and has the value `true`. Anything that isn't an option is ignored, as
+/// is everything after a lone `--`. Leave out the program's name, eg with
+/// `std::env::args().skip(1)`.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Options {
+///     input: String,
+///     #[guzzle(parser = flag)]
+///     dry_run: bool,
+///     #[guzzle(parser = count)]
+///     retries: i32,
+/// }
+///
+/// fn flag(s: String) -> bool {
+///     s == "true"
+/// }
+///
+/// fn count(s: String) -> i32 {
+///     s.parse().unwrap_or_default()
+/// }
+///
+/// let mut options = Options::default();
+/// let args = vec!["--input=posts.csv", "--dry-run", "--retries", "-1"];
+/// options.guzzle_all(guzzle::sources::args(args));
+/// assert_eq!(options.input, "posts.csv".to_string());
+/// assert!(options.dry_run);
+/// assert_eq!(options.retries, -1);
+/// ```
+pub fn args<I>(args: I) -> Args<I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    Args {
+        args: args.into_iter(),
+        peeked: None,
+    }
+}
+
+/// The keys and values of command line options, from `args`
+#[derive(Debug)]
+pub struct Args<I> {
+    args: I,
+    /// The argument after a `--key`, once it has turned out to be another option
+    peeked: Option<String>,
+}
+
+impl<I> Args<I>
+where
+    I: Iterator,
+    I::Item: Into<String>,
+{
+    fn next_arg(&mut self) -> Option<String> {
+        self.peeked
+            .take()
+            .or_else(|| self.args.next().map(Into::into))
+    }
+}
+
+impl<I> Iterator for Args<I>
+where
+    I: Iterator,
+    I::Item: Into<String>,
+{
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<(String, String)> {
+        loop {
+            let arg = self.next_arg()?;
+            let option = match arg.strip_prefix("--") {
+                Some("") => {
+                    self.args.by_ref().for_each(drop);
+                    return None;
+                }
+                Some(option) => option,
+                None => continue,
+            };
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key, value.to_string()),
+                None => match self.next_arg() {
+                    Some(next) if next.starts_with("--") => {
+                        self.peeked = Some(next);
+                        (option, "true".to_string())
+                    }
+                    Some(value) => (option, value),
+                    None => (option, "true".to_string()),
+                },
+            };
+            return Some((key.replace('-', "_"), value));
+        }
+    }
+}
+
+/// The environment variables whose names start with the prefix, with it stripped off and the
+/// rest of the name in lower case, so `APP_DATABASE_URL` is guzzled as `database_url`. Variables
+/// that aren't unicode are left out.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Config {
+///     #[guzzle(parser = port_parser)]
+///     port: u16,
+/// }
+///
+/// fn port_parser(s: String) -> u16 {
+///     s.parse().unwrap_or(8080)
+/// }
+///
+/// std::env::set_var("DOCTEST_PORT", "3000");
+/// let mut config = Config::default();
+/// config.guzzle_all(guzzle::sources::env_with_prefix("DOCTEST_"));
+/// assert_eq!(config.port, 3000);
+/// ```
+#[cfg(feature = "std")]
+pub fn env_with_prefix(prefix: &str) -> EnvWithPrefix {
+    EnvWithPrefix {
+        vars: std::env::vars_os(),
+        prefix: prefix.to_string(),
+    }
+}
+
+/// The keys and values of the environment, from `env_with_prefix`
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct EnvWithPrefix {
+    vars: std::env::VarsOs,
+    prefix: String,
+}
+
+#[cfg(feature = "std")]
+impl Iterator for EnvWithPrefix {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<(String, String)> {
+        let prefix = &self.prefix;
+        self.vars.find_map(|(name, value)| {
+            let key = name.to_str()?.strip_prefix(prefix.as_str())?.to_lowercase();
+            Some((key, value.into_string().ok()?))
+        })
+    }
+}
+
+/// What `headers` does with values that aren't UTF-8, which HTTP allows but rarely sees
+#[cfg(feature = "http")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Replace anything that isn't UTF-8 with `U+FFFD`
+    Lossy,
+    /// Leave the header out altogether
+    Skip,
+}
+
+/// The headers of a request or response, with their names in lower case as `http` keeps them.
+/// Headers with several values give a pair for each of them.
+///
+/// ```rust
+/// use guzzle::sources::{headers, Utf8Policy};
+/// use guzzle::Guzzle;
+/// use std::collections::BTreeMap;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Request {
+///     #[guzzle(keys = ["user-agent"])]
+///     user_agent: String,
+///     #[guzzle(key_prefix = "x-tenant-")]
+///     tenant: BTreeMap<String, String>,
+/// }
+///
+/// let mut map = http::HeaderMap::new();
+/// map.insert("user-agent", "curl/8.0".parse().unwrap());
+/// map.insert("x-tenant-id", "42".parse().unwrap());
+/// let mut request = Request::default();
+/// assert!(request.guzzle_all(headers(&map, Utf8Policy::Lossy)).is_empty());
+/// assert_eq!(request.user_agent, "curl/8.0".to_string());
+/// assert_eq!(request.tenant["id"], "42".to_string());
+/// ```
+#[cfg(feature = "http")]
+pub fn headers(map: &http::HeaderMap, policy: Utf8Policy) -> Headers<'_> {
+    Headers {
+        headers: map.iter(),
+        policy,
+    }
+}
+
+/// The keys and values of a `HeaderMap`, from `headers`
+#[cfg(feature = "http")]
+#[derive(Debug)]
+pub struct Headers<'h> {
+    headers: http::header::Iter<'h, http::HeaderValue>,
+    policy: Utf8Policy,
+}
+
+#[cfg(feature = "http")]
+impl Iterator for Headers<'_> {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<(String, String)> {
+        loop {
+            let (name, value) = self.headers.next()?;
+            let bytes = value.as_bytes();
+            let value = match (core::str::from_utf8(bytes), self.policy) {
+                (Ok(value), _) => value.to_string(),
+                (Err(_), Utf8Policy::Lossy) => String::from_utf8_lossy(bytes).into_owned(),
+                (Err(_), Utf8Policy::Skip) => continue,
+            };
+            return Some((name.as_str().to_string(), value));
+        }
+    }
+}
+
+/// Each column of a CSV record paired with its header, for CSV files with a header row
+#[cfg(feature = "csv")]
+pub fn csv_record<'r>(
+    headers: &'r csv::StringRecord,
+    record: &'r csv::StringRecord,
+) -> CsvRecord<'r> {
+    CsvRecord {
+        headers: headers.iter(),
+        values: record.iter(),
+    }
+}
+
+/// The keys and values of a CSV record, from `csv_record`
+#[cfg(feature = "csv")]
+pub struct CsvRecord<'r> {
+    headers: csv::StringRecordIter<'r>,
+    values: csv::StringRecordIter<'r>,
+}
+
+#[cfg(feature = "csv")]
+impl Iterator for CsvRecord<'_> {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<(String, String)> {
+        Some((
+            self.headers.next()?.to_string(),
+            self.values.next()?.to_string(),
+        ))
+    }
+}
+
+/// Guzzles each record of a CSV file into a new `G`, along with the columns that weren't consumed,
+/// using the reader's header row for the keys. Columns past the end of the header, which only a
+/// `flexible` reader allows, are left out. Each `G` is finalized once its record has been read,
+/// and anything `finalize` hands back joins that record's leftovers.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Product {
+///     sku: String,
+///     #[guzzle(parser = price_parser)]
+///     price: f64,
+/// }
+///
+/// fn price_parser(s: String) -> f64 {
+///     s.parse().unwrap_or_default()
+/// }
+///
+/// let export = "sku,price,colour\nA1,9.99,red\nB2,1.50,blue\n";
+/// let mut reader = csv::Reader::from_reader(export.as_bytes());
+/// let products: Vec<_> = guzzle::sources::csv_rows::<Product, _>(&mut reader)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(products[1].0.sku, "B2".to_string());
+/// assert_eq!(products[1].0.price, 1.5);
+/// assert_eq!(products[1].1, vec![("colour".to_string(), "blue".to_string())]);
+/// ```
+#[cfg(feature = "csv")]
+pub fn csv_rows<G, R>(reader: &mut csv::Reader<R>) -> CsvRows<'_, G, R>
+where
+    G: crate::Guzzle + Default,
+    R: std::io::Read,
+{
+    let headers = reader.headers().cloned();
+    CsvRows {
+        reader,
+        headers: Some(headers),
+        target: core::marker::PhantomData,
+    }
+}
+
+/// Each record of a CSV file guzzled into a `G`, from `csv_rows`
+#[cfg(feature = "csv")]
+#[derive(Debug)]
+pub struct CsvRows<'r, G, R> {
+    reader: &'r mut csv::Reader<R>,
+    /// The header row, or the error reading it until that has been handed back
+    headers: Option<csv::Result<csv::StringRecord>>,
+    target: core::marker::PhantomData<fn() -> G>,
+}
+
+#[cfg(feature = "csv")]
+impl<G, R> Iterator for CsvRows<'_, G, R>
+where
+    G: crate::Guzzle + Default,
+    R: std::io::Read,
+{
+    type Item = csv::Result<(G, Vec<(String, String)>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // An unreadable header row is handed back once, and that's the end
+        if let Some(Err(_)) = &self.headers {
+            return self.headers.take().and_then(Result::err).map(Err);
+        }
+        let headers = self.headers.as_ref()?.as_ref().ok()?;
+        let mut record = csv::StringRecord::new();
+        match self.reader.read_record(&mut record) {
+            Ok(true) => Some(Ok(G::from_metadata_finalized(csv_record(headers, &record)))),
+            Ok(false) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+/// A TOML table flattened into dotted keys, so `[database]` and `port = 5432` become
+/// `("database.port", "5432")`. Strings are given as they are and anything else is written out as
+/// TOML. Arrays of values are joined with `,`, the same way `Unguzzle` joins them, while arrays of
+/// tables are numbered, eg `servers.0.name`.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Database {
+///     host: String,
+///     #[guzzle(parser = port_parser)]
+///     port: u16,
+/// }
+///
+/// #[derive(Default, Guzzle)]
+/// struct Config {
+///     name: String,
+///     #[deep_guzzle(prefix = "database.")]
+///     database: Database,
+/// }
+///
+/// fn port_parser(s: String) -> u16 {
+///     s.parse().unwrap_or(5432)
+/// }
+///
+/// let text = "name = \"guzzle\"\n[database]\nhost = \"db\"\nport = 6543";
+/// let table = toml::from_str(text).unwrap();
+/// let mut config = Config::default();
+/// assert!(config.guzzle_all(guzzle::sources::toml_table(&table)).is_empty());
+/// assert_eq!(config.database.host, "db".to_string());
+/// assert_eq!(config.database.port, 6543);
+/// ```
+#[cfg(feature = "toml")]
+pub fn toml_table(table: &toml::value::Table) -> Vec<(String, String)> {
+    let mut pairs = vec![];
+    flatten_toml_table("", table, &mut pairs);
+    pairs
+}
+
+#[cfg(feature = "toml")]
+fn flatten_toml_table(prefix: &str, table: &toml::value::Table, pairs: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        flatten_toml(format!("{}{}", prefix, key), value, pairs);
+    }
+}
+
+#[cfg(feature = "toml")]
+fn flatten_toml(key: String, value: &toml::Value, pairs: &mut Vec<(String, String)>) {
+    use toml::Value;
+    match value {
+        Value::Table(table) => flatten_toml_table(&format!("{}.", key), table, pairs),
+        Value::Array(array) if array.iter().any(Value::is_table) => {
+            for (index, value) in array.iter().enumerate() {
+                flatten_toml(format!("{}.{}", key, index), value, pairs);
+            }
+        }
+        Value::Array(array) if !array.iter().any(Value::is_array) => {
+            let values: Vec<String> = array.iter().map(crate::toml_to_string).collect();
+            pairs.push((key, values.join(",")));
+        }
+        value => pairs.push((key, crate::toml_to_string(value))),
+    }
+}
+
+/// An INI file flattened into dotted keys, so `port = 5432` in the `[database]` section becomes
+/// `("database.port", "5432")`, and keys before the first section have no prefix. Keys and values
+/// are trimmed and a value wrapped in double quotes has them removed. Blank lines and lines
+/// starting with `;` or `#` are skipped, as are any other lines without an `=`.
+///
+/// ```rust
+/// let ini = "; Generated\nname = guzzle\n\n[database]\nhost = \"db.local\"\n";
+/// assert_eq!(
+///     guzzle::sources::ini(ini),
+///     vec![
+///         ("name".to_string(), "guzzle".to_string()),
+///         ("database.host".to_string(), "db.local".to_string()),
+///     ]
+/// );
+/// ```
+pub fn ini(text: &str) -> Vec<(String, String)> {
+    let mut section = String::new();
+    let mut pairs = vec![];
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = format!("{}.", name.trim());
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            pairs.push((format!("{}{}", section, key.trim()), value.to_string()));
+        }
+    }
+    pairs
+}
+
+/// How `json_object` turns values that aren't strings into strings
+#[cfg(feature = "json")]
+#[derive(Clone, Copy, Debug)]
+pub enum JsonStrings {
+    /// Write them out as JSON, eg `12.5`, `true`, `null` or `[1,2]`
+    Json,
+    /// Leave them out altogether, so only strings are guzzled
+    Skip,
+    /// Convert them however the function likes, leaving out those it returns `None` for
+    With(fn(&serde_json::Value) -> Option<String>),
+}
+
+/// The entries of a JSON object as keys and string values, such as the `meta` of a Wordpress REST
+/// API response. Strings are given as they are, without their quotes, and anything else is
+/// converted as `non_strings` says.
+///
+/// ```rust
+/// use guzzle::sources::{json_object, JsonStrings};
+///
+/// let meta = serde_json::json!({"subtitle": "Hi", "views": 12, "pinned": null});
+/// let pairs = json_object(meta.as_object().unwrap(), JsonStrings::Json);
+/// assert_eq!(
+///     pairs,
+///     vec![
+///         ("pinned".to_string(), "null".to_string()),
+///         ("subtitle".to_string(), "Hi".to_string()),
+///         ("views".to_string(), "12".to_string()),
+///     ]
+/// );
+/// ```
+#[cfg(feature = "json")]
+pub fn json_object(
+    object: &serde_json::Map<String, serde_json::Value>,
+    non_strings: JsonStrings,
+) -> Vec<(String, String)> {
+    object
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = match (value, non_strings) {
+                (serde_json::Value::String(string), _) => Some(string.clone()),
+                (value, JsonStrings::Json) => Some(value.to_string()),
+                (_, JsonStrings::Skip) => None,
+                (value, JsonStrings::With(convert)) => convert(value),
+            }?;
+            Some((key.clone(), value))
+        })
+        .collect()
+}
+
+/// The name and attributes of a Wordpress shortcode, eg `[gallery ids="1,2" size=large]`, which
+/// may be followed by anything such as its content and closing tag. Values can be in double or
+/// single quotes or be a single word, and attributes without a name are numbered from `0` as
+/// Wordpress does. Names are in lower case. `None` if the text doesn't start with a shortcode.
+///
+/// ```rust
+/// use guzzle::Guzzle;
+///
+/// #[derive(Default, Guzzle)]
+/// struct Gallery {
+///     #[guzzle(split = ",")]
+///     ids: Vec<String>,
+///     size: String,
+/// }
+///
+/// let text = r#"[gallery ids="1,2" SIZE='large']"#;
+/// let (name, attributes) = guzzle::sources::shortcode(text).unwrap();
+/// assert_eq!(name, "gallery".to_string());
+/// let (gallery, remaining_data) = Gallery::from_metadata(attributes);
+/// assert_eq!(gallery.ids, vec!["1".to_string(), "2".to_string()]);
+/// assert_eq!(gallery.size, "large".to_string());
+/// assert!(remaining_data.is_empty());
+/// ```
+pub fn shortcode(text: &str) -> Option<(String, Vec<(String, String)>)> {
+    let mut rest = text.trim_start().strip_prefix('[')?;
+    let name = word(&mut rest);
+    if name.is_empty() || name.starts_with('/') {
+        return None;
+    }
+
+    let mut attributes = vec![];
+    let mut positional = 0;
+    loop {
+        rest = rest.trim_start();
+        if rest.starts_with(']') || rest.starts_with("/]") {
+            return Some((name.to_string(), attributes));
+        }
+        let (key, value) = if rest.starts_with(['"', '\'']) {
+            (None, quoted(&mut rest)?)
+        } else {
+            let key = word(&mut rest);
+            match rest.strip_prefix('=') {
+                Some(after) if !key.is_empty() => {
+                    rest = after;
+                    let value = if rest.starts_with(['"', '\'']) {
+                        quoted(&mut rest)?
+                    } else {
+                        word(&mut rest)
+                    };
+                    (Some(key), value)
+                }
+                // A stray `=` or `/` can't start an attribute
+                _ if key.is_empty() => return None,
+                _ => (None, key),
+            }
+        };
+        let key = match key {
+            Some(key) => key.to_lowercase(),
+            None => {
+                positional += 1;
+                (positional - 1).to_string()
+            }
+        };
+        attributes.push((key, value.to_string()));
+    }
+}
+
+/// Takes everything up to the next space, `=`, quote or end of the shortcode
+fn word<'t>(rest: &mut &'t str) -> &'t str {
+    let end = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, '=' | '"' | '\'' | ']'))
+        .unwrap_or(rest.len());
+    let end = match rest[..end].strip_suffix('/') {
+        // `/]` closes the shortcode rather than being part of the word
+        Some(word) if rest[end..].starts_with(']') => word.len(),
+        _ => end,
+    };
+    let (word, after) = rest.split_at(end);
+    *rest = after;
+    word
+}
+
+/// Takes a value in the quotes it starts with, or `None` if they're never closed
+fn quoted<'t>(rest: &mut &'t str) -> Option<&'t str> {
+    let quote = rest.chars().next()?;
+    let (value, after) = rest[1..].split_once(quote)?;
+    *rest = after;
+    Some(value)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            // `from_str_radix` would also allow a sign
+            .filter(|hex| bytes[index] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(core::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[index], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (b'+', None) => decoded.push(b' '),
+            (byte, None) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn pairs(query: &str) -> Vec<(String, String)> {
+        query_string(query).collect()
+    }
+
+    fn pair(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn parameters_are_split_and_decoded() {
+        assert_eq!(
+            pairs("a=1&b=x%20y+z&c"),
+            vec![pair("a", "1"), pair("b", "x y z"), pair("c", "")]
+        );
+        assert_eq!(pairs("k%3D=v%3Dw=x"), vec![pair("k=", "v=w=x")]);
+        assert_eq!(pairs("?&&a=1&"), vec![pair("a", "1")]);
+        assert!(pairs("").is_empty());
+    }
+
+    #[test]
+    fn command_line_options() {
+        let options: Vec<_> = args(vec![
+            "positional",
+            "--log-level",
+            "debug",
+            "--verbose",
+            "--empty=",
+            "--name=a=b",
+            "-x",
+            "--last",
+        ])
+        .collect();
+        assert_eq!(
+            options,
+            vec![
+                pair("log_level", "debug"),
+                pair("verbose", "true"),
+                pair("empty", ""),
+                pair("name", "a=b"),
+                pair("last", "true"),
+            ]
+        );
+        let options: Vec<_> = args(vec!["--a", "1", "--", "--b", "2"]).collect();
+        assert_eq!(options, vec![pair("a", "1")]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv_records_are_keyed_by_header() {
+        let headers = csv::StringRecord::from(vec!["title", "status"]);
+        let record = csv::StringRecord::from(vec!["Hello", "draft", "extra"]);
+        let pairs: Vec<_> = csv_record(&headers, &record).collect();
+        assert_eq!(pairs, vec![pair("title", "Hello"), pair("status", "draft")]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv_rows_are_finalized() {
+        use crate::{Guzzle, GuzzleState};
+
+        #[derive(Default, Guzzle)]
+        #[guzzle(group(location = ["lat", "lng"]))]
+        struct Place {
+            #[guzzle(default_if_absent = "draft")]
+            status: String,
+            lat: String,
+            lng: String,
+            #[guzzle(state)]
+            state: GuzzleState,
+        }
+
+        let export = "lat,colour\n51.5,red\n";
+        let mut reader = csv::Reader::from_reader(export.as_bytes());
+        let mut rows = csv_rows::<Place, _>(&mut reader);
+        let (place, remaining_data) = rows.next().unwrap().unwrap();
+        assert_eq!(place.status, "draft");
+        assert!(place.lat.is_empty());
+        assert_eq!(
+            remaining_data,
+            vec![pair("colour", "red"), pair("lat", "51.5")]
+        );
+        assert!(rows.next().is_none());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_tables_are_flattened() {
+        let table = toml::from_str(
+            r#"
+                tags = ["a", "b"]
+                matrix = [[1, 2], [3]]
+                [server]
+                port = 80
+                [[server.routes]]
+                path = "/"
+                [[server.routes]]
+                path = "/posts"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            toml_table(&table),
+            vec![
+                pair("matrix", "[[1, 2], [3]]"),
+                pair("server.port", "80"),
+                pair("server.routes.0.path", "/"),
+                pair("server.routes.1.path", "/posts"),
+                pair("tags", "a,b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ini_sections_prefix_their_keys() {
+        let text = "top=1\n[ a ]\n# comment\nb = \"two\"\nnot a pair\n[c]\nd= =3\ne = \"\n";
+        assert_eq!(
+            ini(text),
+            vec![
+                pair("top", "1"),
+                pair("a.b", "two"),
+                pair("c.d", "=3"),
+                pair("c.e", "\"")
+            ]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_non_strings() {
+        let object = serde_json::json!({"a": "x", "b": [1, "y"], "c": false});
+        let object = object.as_object().unwrap();
+        assert_eq!(
+            json_object(object, JsonStrings::Json),
+            vec![pair("a", "x"), pair("b", r#"[1,"y"]"#), pair("c", "false")]
+        );
+        assert_eq!(json_object(object, JsonStrings::Skip), vec![pair("a", "x")]);
+        let scalars = JsonStrings::With(|value| match value {
+            serde_json::Value::Bool(_) | serde_json::Value::Number(_) => Some(value.to_string()),
+            _ => None,
+        });
+        assert_eq!(
+            json_object(object, scalars),
+            vec![pair("a", "x"), pair("c", "false")]
+        );
+    }
+
+    #[test]
+    fn shortcode_attributes() {
+        assert_eq!(
+            shortcode(r#"[caption id=attachment_7 align="alignleft" 'quoted' bare width=300/]x"#),
+            Some((
+                "caption".to_string(),
+                vec![
+                    pair("id", "attachment_7"),
+                    pair("align", "alignleft"),
+                    pair("0", "quoted"),
+                    pair("1", "bare"),
+                    pair("width", "300"),
+                ]
+            ))
+        );
+        assert_eq!(shortcode("[audio]"), Some(("audio".to_string(), vec![])));
+        assert_eq!(shortcode("[embed /]"), Some(("embed".to_string(), vec![])));
+        let (_, attributes) = shortcode(r#"[a title="it's [here]"]"#).unwrap();
+        assert_eq!(attributes, vec![pair("title", "it's [here]")]);
+    }
+
+    #[test]
+    fn not_shortcodes() {
+        for text in &[
+            "",
+            "gallery",
+            "[",
+            "[]",
+            "[/gallery]",
+            r#"[a b="open]"#,
+            "[a =b]",
+            "[a b",
+        ] {
+            assert_eq!(shortcode(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn environment_variables_with_the_prefix() {
+        std::env::set_var("GUZZLE_SOURCES_TEST_NAME", "guzzle");
+        std::env::set_var("GUZZLE_SOURCES_TEST_LOG_LEVEL", "debug");
+        std::env::set_var("GUZZLE_SOURCES_OTHER", "ignored");
+        let mut vars: Vec<_> = env_with_prefix("GUZZLE_SOURCES_TEST_").collect();
+        vars.sort();
+        assert_eq!(
+            vars,
+            vec![pair("log_level", "debug"), pair("name", "guzzle")]
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn header_values_that_are_not_utf8() {
+        let mut map = http::HeaderMap::new();
+        map.append("x-name", http::HeaderValue::from_bytes(b"caf\xe9").unwrap());
+        map.append("x-name", http::HeaderValue::from_static("plain"));
+        let lossy: Vec<_> = headers(&map, Utf8Policy::Lossy).collect();
+        assert_eq!(
+            lossy,
+            vec![pair("x-name", "caf\u{fffd}"), pair("x-name", "plain")]
+        );
+        let skipped: Vec<_> = headers(&map, Utf8Policy::Skip).collect();
+        assert_eq!(skipped, vec![pair("x-name", "plain")]);
+    }
+
+    #[test]
+    fn bad_escapes_are_kept() {
+        assert_eq!(
+            pairs("a=100%&b=%zz&c=%4"),
+            vec![pair("a", "100%"), pair("b", "%zz"), pair("c", "%4")]
+        );
+        assert_eq!(pairs("a=%FF"), vec![pair("a", "\u{fffd}")]);
+        assert_eq!(pairs("a=%+1"), vec![pair("a", "% 1")]);
+    }
+}
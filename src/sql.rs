@@ -0,0 +1,91 @@
+//! SQL fragments that filter rows to the keys a type lists in `Guzzle::KEYS`, so a metadata
+//! table can be narrowed down in the database rather than every row being fetched and most of
+//! them left over. Keys that `KEYS` can't list, such as those matched by a prefix or a pattern,
+//! won't be fetched.
+//!
+//! ```rust
+//! use guzzle::sql::{keys_sql_in_clause, keys_sql_in_numbered, keys_sql_in_placeholders};
+//! use guzzle::Guzzle;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Post {
+//!     title: String,
+//!     #[guzzle(keys = ["_thumbnail_id", "thumbnail"])]
+//!     thumbnail: String,
+//! }
+//!
+//! assert_eq!(
+//!     keys_sql_in_clause::<Post>("meta_key"),
+//!     "meta_key IN ('title', '_thumbnail_id', 'thumbnail')"
+//! );
+//! // Bind `Post::KEYS` in order to the placeholders
+//! assert_eq!(keys_sql_in_placeholders::<Post>("meta_key"), "meta_key IN (?, ?, ?)");
+//! assert_eq!(keys_sql_in_numbered::<Post>("meta_key", 2), "meta_key IN ($2, $3, $4)");
+//! ```
+//!
+//! An empty list isn't valid SQL, so a type with no keys gets a condition that's never true.
+
+use crate::__private::*;
+use crate::Guzzle;
+
+/// `column IN (...)` with the keys written out as string literals
+pub fn keys_sql_in_clause<G: Guzzle + ?Sized>(column: &str) -> String {
+    in_clause(column, G::KEYS.iter().map(|key| quote(key)))
+}
+
+/// `column IN (?, ...)` with a `?` placeholder for each key, as MySQL and SQLite use
+pub fn keys_sql_in_placeholders<G: Guzzle + ?Sized>(column: &str) -> String {
+    in_clause(column, G::KEYS.iter().map(|_| "?".to_string()))
+}
+
+/// `column IN ($1, ...)` with a numbered placeholder for each key, as Postgres uses, starting at
+/// `first` so there's room for other parameters before them
+pub fn keys_sql_in_numbered<G: Guzzle + ?Sized>(column: &str, first: usize) -> String {
+    in_clause(
+        column,
+        (first..first + G::KEYS.len()).map(|index| format!("${}", index)),
+    )
+}
+
+fn in_clause(column: &str, values: impl Iterator<Item = String>) -> String {
+    let values: Vec<String> = values.collect();
+    if values.is_empty() {
+        return "1 = 0".to_string();
+    }
+    format!("{} IN ({})", column, values.join(", "))
+}
+
+/// A string literal, doubling quotes and backslashes so the key can't end it early whether or
+/// not the database treats backslashes as escapes
+fn quote(key: &str) -> String {
+    format!("'{}'", key.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[derive(Default, Guzzle)]
+    struct Awkward {
+        #[guzzle(keys = ["it's", "back\\slash"])]
+        value: String,
+    }
+
+    #[derive(Default, Guzzle)]
+    struct Nothing {}
+
+    #[test]
+    fn keys_are_quoted() {
+        assert_eq!(
+            keys_sql_in_clause::<Awkward>("k"),
+            r"k IN ('it''s', 'back\\slash')"
+        );
+    }
+
+    #[test]
+    fn no_keys_matches_nothing() {
+        assert_eq!(keys_sql_in_clause::<Nothing>("k"), "1 = 0");
+        assert_eq!(keys_sql_in_placeholders::<Nothing>("k"), "1 = 0");
+        assert_eq!(keys_sql_in_numbered::<Nothing>("k", 1), "1 = 0");
+    }
+}
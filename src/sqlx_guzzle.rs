@@ -0,0 +1,120 @@
+//! Guzzling straight from the rows of a MySQL metadata table, such as Wordpress's `wp_postmeta`,
+//! where each row has a `meta_key` and a `meta_value` column.
+//!
+//! `meta_query` builds a query for only the keys a type can consume, from its `Guzzle::KEYS`, and
+//! `guzzle_rows` guzzles the rows it fetches as they arrive.
+//!
+//! ```rust,no_run
+//! use guzzle::sqlx::{guzzle_rows, meta_query};
+//! use guzzle::Guzzle;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Post {
+//!     #[guzzle(keys = ["_yoast_wpseo_title"])]
+//!     seo_title: String,
+//! }
+//!
+//! async fn load(pool: &sqlx::MySqlPool, post_id: u64) -> Result<Post, sqlx::Error> {
+//!     let mut query = meta_query::<Post>("wp_postmeta");
+//!     query.push(" AND post_id = ").push_bind(post_id);
+//!     let mut post = Post::default();
+//!     guzzle_rows(&mut post, query.build().fetch(pool)).await?;
+//!     Ok(post)
+//! }
+//! ```
+
+use crate::{AsyncGuzzleExt, Guzzle};
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use futures_core::Stream;
+use sqlx::mysql::{MySql, MySqlRow};
+use sqlx::{QueryBuilder, Row};
+
+/// Starts a query for the `meta_key` and `meta_value` of every row in the table whose key is one
+/// of `G::KEYS`, which more conditions can be pushed on to with `AND`. Keys that `KEYS` can't
+/// list, such as those matched by a prefix or a pattern, won't be fetched.
+pub fn meta_query<G: Guzzle + ?Sized>(table: &str) -> QueryBuilder<MySql> {
+    let mut query = QueryBuilder::new(format!("SELECT meta_key, meta_value FROM {} WHERE ", table));
+    push_meta_keys::<G>(&mut query);
+    query
+}
+
+/// Pushes `meta_key IN (...)` for the keys in `G::KEYS` on to a query, for queries that need more
+/// than `meta_query` gives. MySQL doesn't allow an empty list, so a type with no keys pushes a
+/// condition that's never true.
+pub fn push_meta_keys<G: Guzzle + ?Sized>(query: &mut QueryBuilder<MySql>) {
+    if G::KEYS.is_empty() {
+        query.push("FALSE");
+        return;
+    }
+    query.push("meta_key IN (");
+    let mut keys = query.separated(", ");
+    for key in G::KEYS {
+        keys.push_bind(*key);
+    }
+    query.push(")");
+}
+
+/// Guzzles the `meta_key` and `meta_value` of each row as it arrives, resolving to the pairs that
+/// weren't consumed or the first error. A `NULL` value is guzzled as an empty string.
+pub async fn guzzle_rows<G, S>(
+    target: &mut G,
+    rows: S,
+) -> Result<Vec<(String, String)>, sqlx::Error>
+where
+    G: Guzzle + ?Sized,
+    S: Stream<Item = Result<MySqlRow, sqlx::Error>> + Unpin,
+{
+    target.guzzle_try_stream(MetaPairs(rows)).await
+}
+
+/// Turns a stream of rows into a stream of their keys and values
+struct MetaPairs<S>(S);
+
+impl<S> Stream for MetaPairs<S>
+where
+    S: Stream<Item = Result<MySqlRow, sqlx::Error>> + Unpin,
+{
+    type Item = Result<(String, String), sqlx::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let row = match ready!(Pin::new(&mut self.0).poll_next(cx)) {
+            Some(row) => row,
+            None => return Poll::Ready(None),
+        };
+        Poll::Ready(Some(row.and_then(|row| {
+            let value: Option<String> = row.try_get("meta_value")?;
+            Ok((row.try_get("meta_key")?, value.unwrap_or_default()))
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default, Guzzle)]
+    struct Post {
+        title: String,
+        #[guzzle(keys = ["_thumbnail_id", "thumbnail"])]
+        thumbnail: String,
+    }
+
+    #[derive(Default, Guzzle)]
+    struct Nothing {}
+
+    #[test]
+    fn queries_only_fetch_known_keys() {
+        let mut query = meta_query::<Post>("wp_postmeta");
+        query.push(" AND post_id = ").push_bind(1);
+        assert_eq!(
+            query.sql().as_str(),
+            "SELECT meta_key, meta_value FROM wp_postmeta \
+             WHERE meta_key IN (?, ?, ?) AND post_id = ?"
+        );
+        assert_eq!(
+            meta_query::<Nothing>("wp_postmeta").sql().as_str(),
+            "SELECT meta_key, meta_value FROM wp_postmeta WHERE FALSE"
+        );
+    }
+}
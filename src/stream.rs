@@ -0,0 +1,118 @@
+use crate::__private::*;
+use crate::Guzzle;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use futures_core::{Stream, TryStream};
+
+/// Guzzling straight from a stream of keys and values, such as the rows of a database query, so
+/// they don't have to be collected first. The streams have to be `Unpin`, so pin any that aren't
+/// with `Box::pin` before handing them over.
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::stream;
+/// use guzzle::{AsyncGuzzleExt, Guzzle};
+///
+/// #[derive(Default, Guzzle)]
+/// struct Post {
+///     title: String,
+/// }
+///
+/// let rows = stream::iter(vec![("title", "Hello".to_string()), ("colour", "red".to_string())]);
+/// let mut post = Post::default();
+/// let remaining_data = block_on(post.guzzle_stream(rows));
+/// assert_eq!(post.title, "Hello");
+/// assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+/// ```
+pub trait AsyncGuzzleExt<V = String>: Guzzle<V> {
+    /// Guzzles every key and value from the stream, resolving to those that weren't consumed in
+    /// the order they arrived, as `Guzzle::guzzle_all` does
+    fn guzzle_stream<T, S>(&mut self, stream: S) -> GuzzleStream<'_, Self, S, T, V>
+    where
+        T: AsRef<str>,
+        S: Stream<Item = (T, V)> + Unpin,
+    {
+        GuzzleStream {
+            target: self,
+            stream,
+            leftovers: Vec::new(),
+        }
+    }
+
+    /// Guzzles every key and value from a stream that can fail, stopping at the first error.
+    /// Whatever was guzzled before the error stays guzzled.
+    fn guzzle_try_stream<T, S>(&mut self, stream: S) -> GuzzleTryStream<'_, Self, S, T, V>
+    where
+        T: AsRef<str>,
+        S: TryStream<Ok = (T, V)> + Unpin,
+    {
+        GuzzleTryStream {
+            target: self,
+            stream,
+            leftovers: Vec::new(),
+        }
+    }
+}
+
+impl<V, G: Guzzle<V> + ?Sized> AsyncGuzzleExt<V> for G {}
+
+/// The leftovers from guzzling a stream with `AsyncGuzzleExt::guzzle_stream`
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GuzzleStream<'g, G: ?Sized, S, T, V> {
+    target: &'g mut G,
+    stream: S,
+    leftovers: Vec<(T, V)>,
+}
+
+// The leftovers are never pinned, so they needn't be `Unpin` themselves
+impl<G: ?Sized, S: Unpin, T, V> Unpin for GuzzleStream<'_, G, S, T, V> {}
+
+impl<G, S, T, V> Future for GuzzleStream<'_, G, S, T, V>
+where
+    G: Guzzle<V> + ?Sized,
+    S: Stream<Item = (T, V)> + Unpin,
+    T: AsRef<str>,
+{
+    type Output = Vec<(T, V)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        while let Some(current) = ready!(Pin::new(&mut this.stream).poll_next(cx)) {
+            if let Some(leftover) = this.target.guzzle(current) {
+                this.leftovers.push(leftover);
+            }
+        }
+        Poll::Ready(core::mem::take(&mut this.leftovers))
+    }
+}
+
+/// The leftovers from guzzling a stream with `AsyncGuzzleExt::guzzle_try_stream`, or the
+/// stream's first error
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GuzzleTryStream<'g, G: ?Sized, S, T, V> {
+    target: &'g mut G,
+    stream: S,
+    leftovers: Vec<(T, V)>,
+}
+
+impl<G: ?Sized, S: Unpin, T, V> Unpin for GuzzleTryStream<'_, G, S, T, V> {}
+
+impl<G, S, T, V> Future for GuzzleTryStream<'_, G, S, T, V>
+where
+    G: Guzzle<V> + ?Sized,
+    S: TryStream<Ok = (T, V)> + Unpin,
+    T: AsRef<str>,
+{
+    type Output = Result<Vec<(T, V)>, S::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        while let Some(current) = ready!(Pin::new(&mut this.stream).try_poll_next(cx)) {
+            if let Some(leftover) = this.target.guzzle(current?) {
+                this.leftovers.push(leftover);
+            }
+        }
+        Poll::Ready(Ok(core::mem::take(&mut this.leftovers)))
+    }
+}
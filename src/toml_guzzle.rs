@@ -0,0 +1,103 @@
+use std::convert::TryFrom;
+use toml::value::{Table, Value};
+
+/// Like `Guzzle`, but for values that have already been parsed from TOML, so they don't need to
+/// be turned into strings only to be parsed again. Adding `#[guzzle(toml)]` to a struct that
+/// derives `Guzzle` will implement this in place of `Guzzle`, much like `#[guzzle(bytes)]`.
+///
+/// Fields are filled using `FromToml` unless they have a `toml_parser`, which is given the
+/// `toml::Value` itself. Fields with a string `parser` are given the value as a string.
+///
+/// ```rust
+/// use guzzle::TomlGuzzle;
+///
+/// #[derive(Default, guzzle::Guzzle)]
+/// #[guzzle(toml)]
+/// struct Config {
+///     name: String,
+///     #[guzzle(keys = ["port"], parser = u16_parser)]
+///     port: u16,
+/// }
+///
+/// fn u16_parser(s: String) -> u16 {
+///     s.parse().unwrap()
+/// }
+///
+/// let table: toml::value::Table = toml::from_str("name = \"guzzle\"\nport = 8080").unwrap();
+/// let mut config = Config::default();
+/// assert!(config.guzzle_table(table).is_empty());
+/// assert_eq!(config.name, "guzzle".to_string());
+/// assert_eq!(config.port, 8080);
+/// ```
+pub trait TomlGuzzle {
+    fn guzzle_toml<T>(&mut self, current: (T, Value)) -> Option<(T, Value)>
+    where
+        T: AsRef<str>;
+
+    /// Guzzles every entry in the table, returning a table of those that weren't consumed
+    fn guzzle_table(&mut self, table: Table) -> Table {
+        table
+            .into_iter()
+            .filter_map(|current| self.guzzle_toml(current))
+            .collect()
+    }
+}
+
+/// Converts a `toml::Value` into a field's type, or `None` if the value is the wrong type for it
+pub trait FromToml: Sized {
+    fn from_toml(value: &Value) -> Option<Self>;
+}
+
+impl FromToml for Value {
+    fn from_toml(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl FromToml for String {
+    fn from_toml(value: &Value) -> Option<Self> {
+        value.as_str().map(String::from)
+    }
+}
+
+impl FromToml for bool {
+    fn from_toml(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl FromToml for f64 {
+    fn from_toml(value: &Value) -> Option<Self> {
+        value.as_float()
+    }
+}
+
+impl FromToml for f32 {
+    fn from_toml(value: &Value) -> Option<Self> {
+        value.as_float().map(|float| float as f32)
+    }
+}
+
+macro_rules! from_toml_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl FromToml for $integer {
+                fn from_toml(value: &Value) -> Option<Self> {
+                    value.as_integer().and_then(|integer| <$integer>::try_from(integer).ok())
+                }
+            }
+        )*
+    };
+}
+
+from_toml_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// How a string `parser` sees a TOML value. Strings are passed as they are, anything else is
+/// written out as TOML.
+#[doc(hidden)]
+pub fn toml_to_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        other => other.to_string(),
+    }
+}
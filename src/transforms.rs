@@ -0,0 +1,52 @@
+//! Transforms for `#[guzzle(map = [...])]`, which are applied to a value in order before it's
+//! parsed so that parsers don't each have to clean values up themselves.
+//!
+//! Any `fn(String) -> String` can be used as a transform, and names are resolved wherever the
+//! struct is, just like parsers. So these are used with their path, eg `guzzle::transforms::trim`,
+//! or imported like anything else.
+//!
+//! ```rust
+//! use guzzle::transforms::trim;
+//! use guzzle::Guzzle;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Post {
+//!     #[guzzle(map = [trim, guzzle::transforms::to_lowercase])]
+//!     status: String,
+//!     #[guzzle(map = [trim, strip_hash], parser = u64_parser)]
+//!     id: u64,
+//! }
+//!
+//! fn strip_hash(s: String) -> String {
+//!     s.trim_start_matches('#').to_string()
+//! }
+//!
+//! fn u64_parser(s: String) -> u64 {
+//!     s.parse().unwrap()
+//! }
+//!
+//! let mut post = Post::default();
+//! post.guzzle_all(vec![("status", " Published ".to_string()), ("id", " #42".to_string())]);
+//! assert_eq!(post.status, "published".to_string());
+//! assert_eq!(post.id, 42);
+//! ```
+
+use crate::__private::*;
+
+/// Removes whitespace from around the value
+pub fn trim(s: String) -> String {
+    s.trim().to_string()
+}
+
+pub fn to_lowercase(s: String) -> String {
+    s.to_lowercase()
+}
+
+pub fn to_uppercase(s: String) -> String {
+    s.to_uppercase()
+}
+
+/// Replaces each run of whitespace with a single space, as HTML would display it
+pub fn collapse_whitespace(s: String) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
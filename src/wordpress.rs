@@ -0,0 +1,145 @@
+//! Loading Wordpress metadata straight into a guzzler. `WpMeta` queries a post's, term's or
+//! user's metadata table for only the keys the type lists in `Guzzle::KEYS`, so types that match
+//! keys by a prefix or a pattern won't be given those keys.
+//!
+//! ```rust,no_run
+//! use guzzle::wordpress::WpMeta;
+//! use guzzle::Guzzle;
+//!
+//! #[derive(Default, Guzzle)]
+//! struct Product {
+//!     #[guzzle(keys = ["_price"])]
+//!     price: String,
+//!     #[guzzle(keys = ["_stock_status"])]
+//!     stock_status: String,
+//! }
+//!
+//! async fn load(pool: &sqlx::MySqlPool) -> Result<Product, sqlx::Error> {
+//!     let (product, _remaining_data) = WpMeta::default().load_post_meta(pool, 42).await?;
+//!     Ok(product)
+//! }
+//! ```
+
+use crate::sqlx::{guzzle_rows, meta_query};
+use crate::Guzzle;
+use sqlx::mysql::MySql;
+use sqlx::{Executor, QueryBuilder};
+
+/// Where Wordpress keeps its metadata, which is in tables starting `wp_` unless the site was set
+/// up with another prefix
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WpMeta {
+    prefix: String,
+}
+
+impl Default for WpMeta {
+    fn default() -> Self {
+        WpMeta::new("wp_")
+    }
+}
+
+impl WpMeta {
+    /// Metadata in tables starting with the prefix, eg `"wp_"` for `wp_postmeta`
+    pub fn new(prefix: impl Into<String>) -> Self {
+        WpMeta {
+            prefix: prefix.into(),
+        }
+    }
+
+    /// Loads a post's metadata from `postmeta` and finalizes it, returning it along with the rows
+    /// that weren't consumed and anything `finalize` handed back
+    pub async fn load_post_meta<'c, T, E>(
+        &self,
+        conn: E,
+        post_id: u64,
+    ) -> Result<(T, Vec<(String, String)>), sqlx::Error>
+    where
+        T: Guzzle + Default,
+        E: Executor<'c, Database = MySql>,
+    {
+        self.load("postmeta", "post_id", "meta_id", conn, post_id)
+            .await
+    }
+
+    /// Loads a term's metadata from `termmeta`, in the same way as `load_post_meta`
+    pub async fn load_term_meta<'c, T, E>(
+        &self,
+        conn: E,
+        term_id: u64,
+    ) -> Result<(T, Vec<(String, String)>), sqlx::Error>
+    where
+        T: Guzzle + Default,
+        E: Executor<'c, Database = MySql>,
+    {
+        self.load("termmeta", "term_id", "meta_id", conn, term_id)
+            .await
+    }
+
+    /// Loads a user's metadata from `usermeta`, in the same way as `load_post_meta`
+    pub async fn load_user_meta<'c, T, E>(
+        &self,
+        conn: E,
+        user_id: u64,
+    ) -> Result<(T, Vec<(String, String)>), sqlx::Error>
+    where
+        T: Guzzle + Default,
+        E: Executor<'c, Database = MySql>,
+    {
+        self.load("usermeta", "user_id", "umeta_id", conn, user_id)
+            .await
+    }
+
+    async fn load<'c, T, E>(
+        &self,
+        table: &str,
+        id_column: &str,
+        order_column: &str,
+        conn: E,
+        id: u64,
+    ) -> Result<(T, Vec<(String, String)>), sqlx::Error>
+    where
+        T: Guzzle + Default,
+        E: Executor<'c, Database = MySql>,
+    {
+        let mut query = self.query::<T>(table, id_column, order_column, id);
+        let mut target = T::default();
+        let mut remaining_data = guzzle_rows(&mut target, query.build().fetch(conn)).await?;
+        // Every row has been read, so defaults and finish can be applied
+        remaining_data.extend(target.finalize());
+        Ok((target, remaining_data))
+    }
+
+    fn query<T: Guzzle>(
+        &self,
+        table: &str,
+        id_column: &str,
+        order_column: &str,
+        id: u64,
+    ) -> QueryBuilder<MySql> {
+        let mut query = meta_query::<T>(&format!("{}{}", self.prefix, table));
+        query.push(format!(" AND {} = ", id_column)).push_bind(id);
+        // Rows are guzzled in the order they were added, as Wordpress would read them
+        query.push(format!(" ORDER BY {}", order_column));
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default, Guzzle)]
+    struct Profile {
+        nickname: String,
+    }
+
+    #[test]
+    fn queries_use_the_prefix() {
+        let query = WpMeta::new("site2_").query::<Profile>("usermeta", "user_id", "umeta_id", 1);
+        assert_eq!(
+            query.sql().as_str(),
+            "SELECT meta_key, meta_value FROM site2_usermeta \
+             WHERE meta_key IN (?) AND user_id = ? ORDER BY umeta_id"
+        );
+    }
+}
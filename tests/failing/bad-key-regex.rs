@@ -0,0 +1,9 @@
+use guzzle::Guzzle;
+
+#[derive(Default, Guzzle)]
+struct Product {
+    #[guzzle(key_regex = "attr_(")]
+    attributes: Vec<(String, String)>,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use guzzle::Guzzle;
+
+fn u64_parser(s: String) -> u64 {
+    s.parse().unwrap()
+}
+
+fn opt_u64_parser(s: String) -> Option<u64> {
+    s.parse().ok()
+}
+
+fn try_u64_parser(s: String) -> Result<u64, std::num::ParseIntError> {
+    s.parse()
+}
+
+#[derive(Default, Guzzle)]
+struct Conflicting {
+    #[guzzle(parser = u64_parser, opt_parser = opt_u64_parser)]
+    one: u64,
+    #[guzzle(parser = u64_parser, try_parser = try_u64_parser)]
+    two: u64,
+    #[guzzle(opt_parser = opt_u64_parser, try_parser = try_u64_parser)]
+    three: u64,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use guzzle::Guzzle;
+
+#[derive(Default, Guzzle)]
+struct GuzzleExample {
+    /// A field can either be guzzled deeply or have its own keys, not both
+    #[deep_guzzle]
+    #[guzzle(keys = ["deep_data"])]
+    recurse_guzzle_to_populate_this_field: TypeThatAlsoImplementsGuzzle,
+}
+
+#[derive(Default, Guzzle)]
+struct TypeThatAlsoImplementsGuzzle {
+    deep_data: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use guzzle::Guzzle;
+
+#[derive(Default, Guzzle)]
+struct GuzzleExample {
+    /// Each key may only be listed once
+    #[guzzle(keys = ["a", "b", "a"])]
+    listed_keys: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use guzzle::Guzzle;
+
+#[derive(Guzzle)]
+enum Layout {
+    Empty,
+    Hero { title: String },
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use guzzle::{Guzzle, GuzzleState};
+
+#[derive(Default, Guzzle)]
+#[guzzle(group(location = ["lat", "long"]))]
+struct GuzzleExample {
+    lat: String,
+    /// Every key in a group must be guzzled into a field
+    lng: String,
+    #[guzzle(state)]
+    state: GuzzleState,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use guzzle::Guzzle;
+
+fn m_parser(s: String) -> f64 {
+    s.parse().unwrap()
+}
+
+fn opt_f64_parser(s: String) -> Option<f64> {
+    s.parse().ok()
+}
+
+#[derive(Default, Guzzle)]
+struct Measurement {
+    #[guzzle(key_parsers = [("meters", m_parser)], opt_parser = opt_f64_parser)]
+    distance: f64,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use guzzle::Guzzle;
+
+#[derive(Default, Guzzle)]
+struct GuzzleExample {
+    /// This field is guzzled from its own name
+    title: String,
+    /// So this key could never reach this field
+    #[guzzle(keys = ["post_title", "title"])]
+    heading: String,
+    /// Unless the earlier field only wants some values
+    #[guzzle(keys = ["status"], when_value = "draft")]
+    draft_status: String,
+    status: String,
+}
+
+fn main() {}
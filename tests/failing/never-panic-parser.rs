@@ -0,0 +1,14 @@
+use guzzle::Guzzle;
+
+fn u64_parser(s: String) -> u64 {
+    s.parse().unwrap()
+}
+
+#[derive(Default, Guzzle)]
+#[guzzle(never_panic)]
+struct NeverPanics {
+    #[guzzle(parser = u64_parser)]
+    number: u64,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use guzzle::Guzzle;
+
+#[derive(Default, Guzzle)]
+struct GuzzleExample {
+    /// A field that isn't guzzled can't have keys as well
+    #[no_guzzle]
+    #[guzzle(keys = ["title"])]
+    title: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use guzzle::Guzzle;
+
+#[derive(Default, Guzzle)]
+struct Dimensions(#[guzzle(keys = ["width"])] u32, u32);
+
+fn main() {}
@@ -0,0 +1,18 @@
+use guzzle::{Guzzle, Unguzzle};
+
+/// Unions have no way of knowing which field a key is for
+#[derive(Guzzle)]
+union GuzzleUnion {
+    number: u64,
+    float: f64,
+}
+
+/// Unit structs have nowhere to put anything
+#[derive(Default, Guzzle)]
+struct GuzzleUnit;
+
+/// Tuple structs can be guzzled from keys given to each field, but there are no names to output
+#[derive(Default, Unguzzle)]
+struct UnguzzleTuple(String);
+
+fn main() {}
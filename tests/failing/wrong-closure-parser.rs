@@ -0,0 +1,14 @@
+use guzzle::Guzzle;
+
+#[derive(Default, Guzzle)]
+struct GuzzleExample {
+    /// This closure returns the wrong type
+    #[guzzle(parser = |s: String| s.len())]
+    title_length: u64,
+}
+
+fn main() {
+    let mut guzzle_example = GuzzleExample::default();
+    guzzle_example.guzzle(("title_length", "Hello".to_string()));
+    assert_eq!(guzzle_example.title_length, 5);
+}
@@ -0,0 +1,50 @@
+use guzzle::Guzzle;
+
+/// Another trait with a similarly named method, as a second key/value derive might generate
+trait KeyValuePredicates {
+    fn guzzle_matches(&self, key: &str) -> bool;
+    fn guzzle_schema(&self) -> Vec<&'static str>;
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Guzzle)]
+#[guzzle(schema)]
+struct GuzzleExample {
+    basic: String,
+
+    #[guzzle(keys = ["one", "two"])]
+    listed_keys: String,
+}
+
+impl KeyValuePredicates for GuzzleExample {
+    fn guzzle_matches(&self, key: &str) -> bool {
+        GuzzleExample::guzzle_schema()
+            .iter()
+            .any(|(schema_key, _, _)| *schema_key == key)
+    }
+
+    fn guzzle_schema(&self) -> Vec<&'static str> {
+        vec!["basic", "one", "two"]
+    }
+}
+
+fn main() {
+    let mut guzzle_example = GuzzleExample::default();
+
+    assert_eq!(guzzle_example.guzzle(("basic", "basic info".to_string())), None);
+    assert_eq!(guzzle_example.guzzle(("two", "2".to_string())), None);
+    assert_eq!(guzzle_example.basic, "basic info".to_string());
+    assert_eq!(guzzle_example.listed_keys, "2".to_string());
+
+    // The derived inherent schema and the trait method of the same name don't get in each
+    // others way
+    assert_eq!(GuzzleExample::guzzle_schema().len(), 3);
+    assert_eq!(
+        KeyValuePredicates::guzzle_schema(&guzzle_example),
+        vec!["basic", "one", "two"]
+    );
+    assert!(guzzle_example.guzzle_matches("one"));
+    assert!(!guzzle_example.guzzle_matches("three"));
+
+    // Other derives still work as normal
+    assert_eq!(guzzle_example.clone(), guzzle_example);
+}
@@ -0,0 +1,40 @@
+//! Neither `std` nor its prelude are in scope, as in a crate that only has `alloc`. `std` is only
+//! linked so there is something to run `main`.
+#![no_std]
+
+extern crate alloc;
+extern crate std;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use guzzle::{Guzzle, Unguzzle};
+
+#[derive(Default, Guzzle, Unguzzle)]
+struct Config {
+    name: String,
+    #[guzzle(keys = ["port"])]
+    port: u16,
+    #[guzzle(keys = ["host"], split = ",")]
+    hosts: Vec<String>,
+    #[guzzle(rest)]
+    rest: Vec<(String, String)>,
+}
+
+#[derive(Default, Guzzle)]
+struct Device {
+    #[deep_guzzle(prefix = "config_")]
+    config: Config,
+}
+
+fn main() {
+    let mut device = Device::default();
+    let remaining_data = device.guzzle_all(vec![
+        ("config_name", String::from("sensor")),
+        ("config_port", String::from("8080")),
+        ("config_host", String::from("a,b")),
+    ]);
+    assert!(remaining_data.is_empty());
+    assert_eq!(device.config.port, 8080);
+    assert_eq!(device.config.to_metadata().len(), 3);
+}
@@ -0,0 +1,24 @@
+//! When every key belongs to a group, the derived code shouldn't leave anything unreachable
+#![deny(warnings)]
+
+use guzzle::{Guzzle, GuzzleState, TryGuzzle};
+
+#[derive(Default, Guzzle)]
+#[guzzle(group(location = ["lat", "lng"]))]
+struct Location {
+    lat: String,
+    lng: String,
+    #[guzzle(state)]
+    state: GuzzleState,
+}
+
+fn main() {
+    let mut location = Location::default();
+    let remaining_data = location
+        .try_guzzle_all(vec![("lat", "51.5".to_string()), ("lng", "-0.1".to_string())])
+        .unwrap();
+
+    assert!(remaining_data.is_empty());
+    assert_eq!(location.lat, "51.5".to_string());
+    assert_eq!(location.lng, "-0.1".to_string());
+}
@@ -0,0 +1,62 @@
+/// A crate that re-exports guzzle, as a framework might
+mod framework {
+    pub use guzzle as storage;
+}
+
+/// Nothing from guzzle is imported, and a local type even takes its name
+#[allow(dead_code)]
+struct Guzzle;
+
+#[derive(Default, guzzle::Guzzle, guzzle::Unguzzle)]
+#[guzzle(crate = "framework::storage")]
+struct Location {
+    #[guzzle(parser = parse_coordinate)]
+    lat: f64,
+    #[guzzle(keys = ["tag"], split = ",")]
+    tags: Vec<String>,
+    #[guzzle(state)]
+    state: framework::storage::GuzzleState,
+}
+
+#[derive(Default, guzzle::Guzzle)]
+#[guzzle(crate = "framework::storage")]
+struct Venue {
+    name: String,
+    #[deep_guzzle]
+    location: Location,
+}
+
+/// Generated methods have generics of their own, which mustn't clash with the struct's
+#[derive(Default, guzzle::Guzzle)]
+#[guzzle(crate = "framework::storage")]
+struct Tagged<T: framework::storage::Guzzle + Default, I, M> {
+    label: String,
+    #[deep_guzzle]
+    inner: T,
+    #[no_guzzle]
+    markers: Vec<(I, M)>,
+}
+
+fn parse_coordinate(s: String) -> f64 {
+    s.parse().unwrap()
+}
+
+fn main() {
+    use framework::storage::{Guzzle as _, Unguzzle as _};
+
+    let mut venue = Venue::default();
+    let remaining_data = venue.guzzle_all(vec![
+        ("name", "Home".to_string()),
+        ("lat", "51.5".to_string()),
+        ("tag", "a,b".to_string()),
+        ("colour", "red".to_string()),
+    ]);
+    assert_eq!(remaining_data, vec![("colour", "red".to_string())]);
+    assert_eq!(venue.location.lat, 51.5);
+    assert_eq!(venue.location.to_metadata()[1], ("tag".to_string(), "a,b".to_string()));
+
+    let mut tagged = Tagged::<Location, u8, u8>::default();
+    tagged.guzzle(("lat", "10".to_string()));
+    assert_eq!(tagged.inner.lat, 10.0);
+    assert!(tagged.label.is_empty() && tagged.markers.is_empty());
+}
@@ -0,0 +1,36 @@
+use guzzle::Guzzle;
+
+/// Guzzle should only look at its own attributes and leave everything else alone
+#[derive(Debug, Clone, Default, Guzzle)]
+#[repr(C)]
+#[allow(dead_code)]
+#[cfg_attr(not(test), derive(PartialEq))]
+#[guzzle(never_panic)]
+struct GuzzleExample {
+    #[allow(unused)]
+    #[doc = "A doc attribute written out by hand"]
+    basic: String,
+
+    #[cfg_attr(test, allow(unused))]
+    #[guzzle(keys = ["one", "two"])]
+    #[allow(clippy::all)]
+    listed_keys: String,
+
+    #[deprecated]
+    #[no_guzzle]
+    ignored: String,
+}
+
+fn main() {
+    let mut guzzle_example = GuzzleExample::default();
+
+    let remaining_data = guzzle_example.guzzle_all(vec![
+        ("basic", "basic info".to_string()),
+        ("two", "2".to_string()),
+        ("ignored", "ignored data".to_string()),
+    ]);
+
+    assert_eq!(guzzle_example.basic, "basic info".to_string());
+    assert_eq!(guzzle_example.listed_keys, "2".to_string());
+    assert_eq!(remaining_data, vec![("ignored", "ignored data".to_string())]);
+}
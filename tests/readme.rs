@@ -0,0 +1,52 @@
+#[path = "../build/readme.rs"]
+mod readme;
+
+#[test]
+fn plain_markdown_is_unchanged() {
+    let comments = vec!["Guzzle", "======", "", "What is it?"];
+    assert_eq!(readme::render(comments), "Guzzle\n======\n\nWhat is it?");
+}
+
+#[test]
+fn hidden_doctest_lines_are_shown() {
+    let comments = vec![
+        "```rust",
+        "# use guzzle::Guzzle;",
+        "#",
+        "#[derive(Default, Guzzle)]",
+        "struct Example {",
+        "    # #[allow(unused)]",
+        "    basic: String,",
+        "}",
+        "## not hidden",
+        "```",
+    ];
+    assert_eq!(
+        readme::render(comments),
+        [
+            "```rust",
+            "use guzzle::Guzzle;",
+            "",
+            "#[derive(Default, Guzzle)]",
+            "struct Example {",
+            "    #[allow(unused)]",
+            "    basic: String,",
+            "}",
+            "# not hidden",
+            "```",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn other_code_fences_are_verbatim() {
+    let comments = vec![
+        "```text",
+        "# a heading in text",
+        "    indented",
+        "```",
+        "# Heading",
+    ];
+    assert_eq!(readme::render(comments.clone()), comments.join("\n"));
+}